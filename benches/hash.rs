@@ -0,0 +1,53 @@
+//! Speed and distribution of the built-in lattice hashes.
+//!
+//! Run with `cargo bench --bench hash`. For every backend this prints the time per hash, the largest deviation of a
+//! 16 bucket histogram from a uniform distribution, and the correlation between horizontally adjacent lattice values.
+
+use perlin2d::{HashBackend, LatticeHasher};
+use std::hint::black_box;
+use std::time::Instant;
+
+const SIDE: i64 = 1024;
+
+fn main() {
+    for &backend in [HashBackend::Prime, HashBackend::Wy, HashBackend::Xx].iter() {
+        let start = Instant::now();
+        let mut sum = 0.0;
+        for y in 0..SIDE {
+            for x in 0..SIDE {
+                sum += black_box(backend).hash(black_box(x), black_box(y));
+            }
+        }
+        let elapsed = start.elapsed();
+        black_box(sum);
+        let per_hash = elapsed.as_nanos() as f64 / (SIDE * SIDE) as f64;
+
+        let mut buckets = [0usize; 16];
+        let (mut sxy, mut sx, mut sy, mut sxx, mut syy, mut n) = (0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+        for y in 0..SIDE {
+            for x in 0..SIDE {
+                let a = backend.hash(x, y);
+                let b = backend.hash(x + 1, y);
+                let bucket = (((a + 1.0) * 0.5) * 16.0) as usize;
+                buckets[bucket.min(15)] += 1;
+                sxy += a * b;
+                sx += a;
+                sy += b;
+                sxx += a * a;
+                syy += b * b;
+                n += 1.0;
+            }
+        }
+        let expected = (SIDE * SIDE) as f64 / 16.0;
+        let deviation = buckets
+            .iter()
+            .map(|&c| f64::abs(c as f64 - expected) / expected)
+            .fold(0.0, f64::max);
+        let correlation = (n * sxy - sx * sy) / (f64::sqrt(n * sxx - sx * sx) * f64::sqrt(n * syy - sy * sy));
+
+        println!(
+            "{:?}: {:.2} ns/hash, histogram deviation {:.4}, neighbor correlation {:+.5}",
+            backend, per_hash, deviation, correlation
+        );
+    }
+}
@@ -0,0 +1,557 @@
+//! Measurements over generated noise maps.
+//!
+//! [`power_spectrum`] computes the radially averaged power spectrum of a map, which shows how much each spatial
+//! frequency contributes to the result. Fractal noise with a persistence of `p` and a lacunarity of `l` aims for a
+//! power law falloff, and [`PowerSpectrum::slope`] estimates its exponent so parameters can be checked against it.
+//!
+//! ```
+//! use perlin2d::analysis::power_spectrum;
+//! use perlin2d::{NoiseSource, PerlinNoise2D, Region};
+//!
+//! let perlin = PerlinNoise2D::new(6, 1.0, 1.0, 0.5, 2.0, (32.0, 32.0), 0.0, 3);
+//! let map = perlin.generate_map(Region::new((0.0, 0.0), (512.0, 512.0)), (128, 128));
+//! let spectrum = power_spectrum(&map);
+//!
+//! // coarse features dominate, so power falls off with frequency
+//! assert!(spectrum.slope().unwrap() < 0.0);
+//! ```
+//!
+//! [`roughness`] condenses the steepness of an area into a single number, for example to check whether a spot is flat
+//! enough to build on.
+//!
+//! [`stratified_stats`] summarizes every cell of a coarse partition of a region, which helps to balance what each
+//! part of a map receives and to reject seeds whose noise is degenerate. [`audit_seed`] runs a set of quick checks
+//! for such seeds directly on the lattice.
+
+use crate::{FractalMode, NoiseMap, NoiseSource, PerlinConfig, Region};
+use std::f64::consts::PI;
+
+/// Radially averaged power spectrum of a [`NoiseMap`]
+///
+/// * `frequencies` - Center of each frequency bin, in cycles per unit of the noise plane.
+/// * `power` - Mean power of the Fourier modes in each bin.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PowerSpectrum {
+    frequencies: Vec<f64>,
+    power: Vec<f64>,
+}
+
+impl PowerSpectrum {
+    /// Getter function for frequencies
+    pub fn get_frequencies(&self) -> &[f64] {
+        &self.frequencies
+    }
+    /// Getter function for power
+    pub fn get_power(&self) -> &[f64] {
+        &self.power
+    }
+
+    /// Least squares slope of `ln(power)` against `ln(frequency)`
+    ///
+    /// A spectrum following `power ~ frequency^-b` has a slope of `-b`. Returns `None` when fewer than two bins hold
+    /// any power.
+    pub fn slope(&self) -> Option<f64> {
+        let points: Vec<(f64, f64)> = self
+            .frequencies
+            .iter()
+            .zip(self.power.iter())
+            .filter(|&(_, &p)| p > 0.0)
+            .map(|(&f, &p)| (f.ln(), p.ln()))
+            .collect();
+        if points.len() < 2 {
+            return None;
+        }
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let cov: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+        let var: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.0 - mean_x)).sum();
+        Some(cov / var)
+    }
+}
+
+/// Computes the radially averaged power spectrum of a map
+///
+/// The mean is removed before the transform, and the power of a mode is the squared magnitude of its DFT coefficient
+/// divided by the square of the sample count. Bins are spaced by one cycle over the longer side of the map region
+/// and reach up to the lower of the two Nyquist frequencies. Power of two resolutions use a fast Fourier transform,
+/// other sizes fall back to a direct transform that is quadratic in the side length.
+pub fn power_spectrum(map: &NoiseMap) -> PowerSpectrum {
+    let (w, h) = map.get_resolution();
+    let region = map.get_region();
+    let (width, height) = (region.width().abs(), region.height().abs());
+    if w == 0 || h == 0 || width == 0.0 || height == 0.0 {
+        return PowerSpectrum {
+            frequencies: Vec::new(),
+            power: Vec::new(),
+        };
+    }
+
+    let mean = map.get_values().iter().sum::<f64>() / (w * h) as f64;
+    let mut re: Vec<f64> = map.get_values().iter().map(|v| v - mean).collect();
+    let mut im = vec![0.0; w * h];
+    for (row_re, row_im) in re.chunks_mut(w).zip(im.chunks_mut(w)) {
+        fft(row_re, row_im);
+    }
+    let mut col_re = vec![0.0; h];
+    let mut col_im = vec![0.0; h];
+    for i in 0..w {
+        for j in 0..h {
+            col_re[j] = re[j * w + i];
+            col_im[j] = im[j * w + i];
+        }
+        fft(&mut col_re, &mut col_im);
+        for j in 0..h {
+            re[j * w + i] = col_re[j];
+            im[j * w + i] = col_im[j];
+        }
+    }
+
+    let step = 1.0 / f64::max(width, height);
+    let nyquist = f64::min(0.5 * w as f64 / width, 0.5 * h as f64 / height);
+    let bins = (nyquist / step) as usize;
+    let mut sums = vec![0.0; bins + 1];
+    let mut counts = vec![0usize; bins + 1];
+    let norm = ((w * h) as f64).powi(2);
+    for j in 0..h {
+        let fy = signed_index(j, h) as f64 / height;
+        for i in 0..w {
+            let fx = signed_index(i, w) as f64 / width;
+            let bin = (f64::sqrt(fx * fx + fy * fy) / step).round() as usize;
+            if bin <= bins {
+                let k = j * w + i;
+                sums[bin] += (re[k] * re[k] + im[k] * im[k]) / norm;
+                counts[bin] += 1;
+            }
+        }
+    }
+
+    // bin 0 only holds the removed mean
+    let (frequencies, power) = (1..=bins)
+        .filter(|&b| counts[b] > 0)
+        .map(|b| (b as f64 * step, sums[b] / counts[b] as f64))
+        .unzip();
+    PowerSpectrum { frequencies, power }
+}
+
+/// Mean gradient magnitude of a source over a region, sampled at the given `(width, height)` resolution
+///
+/// See [`map_roughness`].
+///
+/// ```
+/// use perlin2d::analysis::roughness;
+/// use perlin2d::{PerlinNoise2D, Region};
+///
+/// let hills = PerlinNoise2D::new(6, 20.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 11);
+/// let mut plains = hills;
+/// plains.set_amplitude(2.0);
+///
+/// let site = Region::new((0.0, 0.0), (50.0, 50.0));
+/// assert!(roughness(&plains, site, (32, 32)) < roughness(&hills, site, (32, 32)));
+/// ```
+pub fn roughness<S: NoiseSource + ?Sized>(source: &S, region: Region, resolution: (usize, usize)) -> f64 {
+    map_roughness(&source.generate_map(region, resolution))
+}
+
+/// Mean gradient magnitude of a map, in value units per unit of the noise plane
+///
+/// Gradients are estimated with central differences, one sided along the edges. A flat map has a roughness of zero
+/// and for smooth noise the result stays roughly the same across resolutions. Maps with fewer than two samples along an axis
+/// contribute no gradient along it.
+pub fn map_roughness(map: &NoiseMap) -> f64 {
+    let (w, h) = map.get_resolution();
+    if w == 0 || h == 0 {
+        return 0.0;
+    }
+    let (step_x, step_y) = map.get_region().step((w, h));
+    let derivative = |lo: f64, hi: f64, span: usize, step: f64| {
+        if span == 0 {
+            0.0
+        } else {
+            (hi - lo) / (span as f64 * step)
+        }
+    };
+
+    let mut total = 0.0;
+    for j in 0..h {
+        let (j0, j1) = (j.saturating_sub(1), usize::min(j + 1, h - 1));
+        for i in 0..w {
+            let (i0, i1) = (i.saturating_sub(1), usize::min(i + 1, w - 1));
+            let dx = derivative(map.get_value(i0, j), map.get_value(i1, j), i1 - i0, step_x);
+            let dy = derivative(map.get_value(i, j0), map.get_value(i, j1), j1 - j0, step_y);
+            total += f64::sqrt(dx * dx + dy * dy);
+        }
+    }
+    total / (w * h) as f64
+}
+
+/// Statistics of the samples in one cell of a [`StratifiedStats`] partition
+///
+/// * `region` - The area covered by the cell.
+/// * `mean` - Mean of the samples.
+/// * `variance` - Population variance of the samples.
+/// * `min`, `max` - Extremes of the samples.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CellStats {
+    pub region: Region,
+    pub mean: f64,
+    pub variance: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Per cell statistics over a partition of a region, see [`stratified_stats`]
+///
+/// Member variables:
+///
+/// * `cells` - The `(columns, rows)` of the partition.
+/// * `stats` - The statistics of every cell, row by row.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StratifiedStats {
+    cells: (usize, usize),
+    stats: Vec<CellStats>,
+}
+
+impl StratifiedStats {
+    /// Getter function for the `(columns, rows)` of the partition
+    pub fn get_cells(&self) -> (usize, usize) {
+        self.cells
+    }
+    /// Getter function for the statistics of every cell, row by row
+    pub fn get_stats(&self) -> &[CellStats] {
+        &self.stats
+    }
+    /// Getter function for the statistics of the cell in column `i` and row `j`
+    pub fn get_cell(&self, i: usize, j: usize) -> &CellStats {
+        assert!(i < self.cells.0 && j < self.cells.1, "cell out of bounds");
+        &self.stats[j * self.cells.0 + i]
+    }
+
+    /// Mean over the whole region
+    pub fn mean(&self) -> f64 {
+        if self.stats.is_empty() {
+            return 0.0;
+        }
+        self.stats.iter().map(|cell| cell.mean).sum::<f64>() / self.stats.len() as f64
+    }
+
+    /// Difference between the highest and the lowest cell mean
+    ///
+    /// A balanced map has a small spread, while a seed that puts all its high values in one corner has a large one.
+    pub fn mean_spread(&self) -> f64 {
+        let (lo, hi) = self
+            .stats
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), cell| {
+                (lo.min(cell.mean), hi.max(cell.mean))
+            });
+        if lo > hi {
+            0.0
+        } else {
+            hi - lo
+        }
+    }
+
+    /// Lowest variance of any cell, close to zero when part of the region is flat
+    pub fn min_variance(&self) -> f64 {
+        self.stats
+            .iter()
+            .map(|cell| cell.variance)
+            .fold(None, |lo: Option<f64>, v| Some(lo.map_or(v, |lo| lo.min(v))))
+            .unwrap_or(0.0)
+    }
+}
+
+/// Splits a region into a grid of `(columns, rows)` cells and returns the statistics of the source in each of them
+///
+/// Every cell is sampled on a `(width, height)` grid of `samples` points placed at the centers of equal sub-cells,
+/// so all cells weigh the same and samples never fall on the border shared by two cells.
+///
+/// ```
+/// use perlin2d::analysis::stratified_stats;
+/// use perlin2d::{PerlinNoise2D, Region};
+///
+/// let perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 7);
+/// let stats = stratified_stats(&perlin, Region::new((0.0, 0.0), (1024.0, 1024.0)), (2, 2), (32, 32));
+///
+/// // reject seeds that leave one quadrant much higher than another, or one of them flat
+/// let balanced = stats.mean_spread() < 0.5 && stats.min_variance() > 1e-3;
+/// assert_eq!(stats.get_stats().len(), 4);
+/// ```
+pub fn stratified_stats<S: NoiseSource + ?Sized>(
+    source: &S,
+    region: Region,
+    cells: (usize, usize),
+    samples: (usize, usize),
+) -> StratifiedStats {
+    let (cell_w, cell_h) = region.step(cells);
+    let mut values = vec![0.0; samples.0 * samples.1];
+    let mut stats = Vec::with_capacity(cells.0 * cells.1);
+    for j in 0..cells.1 {
+        for i in 0..cells.0 {
+            let (x0, y0) = region.sample_point(cells, i, j);
+            let cell = Region::new((x0, y0), (x0 + cell_w, y0 + cell_h));
+            // shift the grid by half a step to sample the centers of the sub-cells
+            let (dx, dy) = cell.step(samples);
+            let centered = Region::new(
+                (x0 + 0.5 * dx, y0 + 0.5 * dy),
+                (x0 + cell_w + 0.5 * dx, y0 + cell_h + 0.5 * dy),
+            );
+            source.fill_region(centered, samples, &mut values);
+            stats.push(cell_stats(cell, &values));
+        }
+    }
+    StratifiedStats { cells, stats }
+}
+
+/// Mean, variance and extremes of the samples of a cell, with Welford's update for a stable variance
+fn cell_stats(region: Region, values: &[f64]) -> CellStats {
+    let (mut mean, mut m2) = (0.0, 0.0);
+    let (mut min, mut max) = (f64::INFINITY, f64::NEG_INFINITY);
+    for (n, &value) in values.iter().enumerate() {
+        let delta = value - mean;
+        mean += delta / (n + 1) as f64;
+        m2 += delta * (value - mean);
+        min = min.min(value);
+        max = max.max(value);
+    }
+    if values.is_empty() {
+        return CellStats {
+            region,
+            mean: 0.0,
+            variance: 0.0,
+            min: 0.0,
+            max: 0.0,
+        };
+    }
+    CellStats {
+        region,
+        mean,
+        variance: m2 / values.len() as f64,
+        min,
+        max,
+    }
+}
+
+/// Number of base octave lattice cells covered along each axis by [`audit_seed`]
+pub const AUDIT_CELLS: usize = 64;
+/// Samples per lattice cell along each axis taken by [`audit_seed`]
+const AUDIT_DENSITY: usize = 2;
+/// Lags in lattice cells at which [`audit_seed`] measures autocorrelation, far beyond the reach of a single cell
+const AUDIT_LAGS: [usize; 3] = [8, 16, 24];
+
+/// A problem found by [`audit_seed`]
+///
+/// * `Bias` - The mean of the octave sum is far from zero.
+/// * `LowVariance` - The values barely vary, the noise looks flat.
+/// * `AxisCorrelation` - Values stay correlated over long distances along an axis, which shows as stripes or
+///   repetition.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SeedIssue {
+    Bias,
+    LowVariance,
+    AxisCorrelation,
+}
+
+/// Result of [`audit_seed`]
+///
+/// Member variables:
+///
+/// * `seed` - The audited seed.
+/// * `mean` - Mean of the octave sum, relative to its largest possible magnitude.
+/// * `std_dev` - Standard deviation of the octave sum, relative to its largest possible magnitude.
+/// * `autocorrelation` - The largest autocorrelation magnitude along `x` and `y` at long lags.
+/// * `issues` - The checks the seed failed.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SeedReport {
+    seed: i32,
+    mean: f64,
+    std_dev: f64,
+    autocorrelation: (f64, f64),
+    issues: Vec<SeedIssue>,
+}
+
+impl SeedReport {
+    /// Getter function for seed
+    pub fn get_seed(&self) -> i32 {
+        self.seed
+    }
+    /// Getter function for the relative mean
+    pub fn get_mean(&self) -> f64 {
+        self.mean
+    }
+    /// Getter function for the relative standard deviation
+    pub fn get_std_dev(&self) -> f64 {
+        self.std_dev
+    }
+    /// Getter function for the long range autocorrelation along `x` and `y`
+    pub fn get_autocorrelation(&self) -> (f64, f64) {
+        self.autocorrelation
+    }
+    /// Getter function for issues
+    pub fn get_issues(&self) -> &[SeedIssue] {
+        &self.issues
+    }
+
+    /// Whether the seed passed every check
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Checks whether `seed` produces degenerate noise with the parameters of `config`
+///
+/// The octave sum is sampled over [`AUDIT_CELLS`] lattice cells of the first octave along each axis, ignoring the
+/// scale, transform, exponent, amplitude and bias of the configuration and the seed stored in it. A seed fails when
+///
+/// * in `Fbm` mode, the mean deviates from zero by more than `5%` of the largest possible magnitude,
+/// * the standard deviation is below `5%` of that magnitude,
+/// * or the autocorrelation at a lag of 8 to 24 cells exceeds `0.3` along either axis.
+///
+/// Regular seeds stay well within these bounds, so rerolling until [`SeedReport::is_ok`] does not visibly narrow the
+/// variety of worlds.
+///
+/// ```
+/// use perlin2d::analysis::audit_seed;
+/// use perlin2d::PerlinConfig;
+///
+/// let config = PerlinConfig::from(&perlin2d::PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 0));
+/// assert!(audit_seed(42, &config).is_ok());
+///
+/// // `V1` saturates its 32 bit lattice coordinates this far from the origin
+/// assert!(!audit_seed(i32::MAX, &config).is_ok());
+/// ```
+pub fn audit_seed(seed: i32, config: &PerlinConfig) -> SeedReport {
+    let mut perlin = config.build();
+    perlin.set_seed(seed);
+
+    let n = AUDIT_CELLS * AUDIT_DENSITY;
+    let step = (
+        1.0 / (AUDIT_DENSITY as f64 * config.frequency.0),
+        1.0 / (AUDIT_DENSITY as f64 * config.frequency.1),
+    );
+    let mut values = Vec::with_capacity(n * n);
+    for j in 0..n {
+        for i in 0..n {
+            values.push(perlin.get_noise_raw(i as f64 * step.0, j as f64 * step.1));
+        }
+    }
+
+    let (lo, hi) = perlin.octave_range();
+    let norm = f64::max(lo.abs(), hi.abs());
+    let norm = if norm > 0.0 { norm } else { 1.0 };
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / values.len() as f64;
+
+    let correlation = |dx: usize, dy: usize| {
+        if variance <= 0.0 {
+            // a constant field is perfectly correlated with itself
+            return 1.0;
+        }
+        let mut sum = 0.0;
+        for j in 0..n - dy {
+            for i in 0..n - dx {
+                sum += (values[j * n + i] - mean) * (values[(j + dy) * n + i + dx] - mean);
+            }
+        }
+        f64::abs(sum / ((n - dx) * (n - dy)) as f64 / variance)
+    };
+    let autocorrelation = AUDIT_LAGS.iter().fold((0.0, 0.0), |(ax, ay): (f64, f64), &lag| {
+        let lag = lag * AUDIT_DENSITY;
+        (ax.max(correlation(lag, 0)), ay.max(correlation(0, lag)))
+    });
+
+    let (mean, std_dev) = (mean / norm, variance.sqrt() / norm);
+    let mut issues = Vec::new();
+    if config.mode == FractalMode::Fbm && mean.abs() > 0.05 {
+        issues.push(SeedIssue::Bias);
+    }
+    if std_dev < 0.05 {
+        issues.push(SeedIssue::LowVariance);
+    }
+    if autocorrelation.0 > 0.3 || autocorrelation.1 > 0.3 {
+        issues.push(SeedIssue::AxisCorrelation);
+    }
+    SeedReport {
+        seed,
+        mean,
+        std_dev,
+        autocorrelation,
+        issues,
+    }
+}
+
+/// Frequency index of DFT coefficient `k` of `n`, negative for the upper half
+fn signed_index(k: usize, n: usize) -> i64 {
+    if k <= n / 2 {
+        k as i64
+    } else {
+        k as i64 - n as i64
+    }
+}
+
+/// In-place discrete Fourier transform
+fn fft(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+    if n <= 1 {
+        return;
+    }
+    if !n.is_power_of_two() {
+        dft(re, im);
+        return;
+    }
+
+    // bit reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f64;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (wi, wr) = f64::sin_cos(angle * k as f64);
+                let a = start + k;
+                let b = a + len / 2;
+                let xr = re[b] * wr - im[b] * wi;
+                let xi = re[b] * wi + im[b] * wr;
+                re[b] = re[a] - xr;
+                im[b] = im[a] - xi;
+                re[a] += xr;
+                im[a] += xi;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// Direct transform for sizes that are not a power of two
+fn dft(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+    let mut out_re = vec![0.0; n];
+    let mut out_im = vec![0.0; n];
+    for k in 0..n {
+        for t in 0..n {
+            let (s, c) = f64::sin_cos(-2.0 * PI * ((k * t) % n) as f64 / n as f64);
+            out_re[k] += re[t] * c - im[t] * s;
+            out_im[k] += re[t] * s + im[t] * c;
+        }
+    }
+    re.copy_from_slice(&out_re);
+    im.copy_from_slice(&out_im);
+}
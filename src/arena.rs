@@ -0,0 +1,98 @@
+//! Pool of reusable output buffers for repeated grid generation.
+//!
+//! Regenerating chunk heightmaps every frame allocates and frees a buffer per chunk. A [`GridArena`] keeps returned
+//! buffers around and hands them out again, so steady state generation does not touch the allocator.
+//!
+//! ```
+//! use perlin2d::{GridArena, PerlinNoise2D, Region};
+//!
+//! let perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (16.0, 16.0), 0.0, 7);
+//! let mut arena = GridArena::new();
+//! for frame in 0..3 {
+//!     let region = Region::new((frame as f64 * 64.0, 0.0), (frame as f64 * 64.0 + 64.0, 64.0));
+//!     let map = arena.generate_map(&perlin, region, (64, 64));
+//!     // ... upload the map ...
+//!     arena.recycle(map);
+//! }
+//! assert_eq!(arena.len(), 1);
+//! ```
+
+use crate::{NoiseMap, NoiseSource, Region};
+
+/// A pool of `f64` buffers that can be rented and returned
+///
+/// * `free` - Returned buffers waiting to be rented again.
+#[derive(Clone, Debug, Default)]
+pub struct GridArena {
+    free: Vec<Vec<f64>>,
+}
+
+impl GridArena {
+    /// Create and return a new empty GridArena object
+    pub fn new() -> Self {
+        Self { free: Vec::new() }
+    }
+
+    /// Number of buffers waiting in the pool
+    pub fn len(&self) -> usize {
+        self.free.len()
+    }
+    /// Returns true if the pool holds no buffers
+    pub fn is_empty(&self) -> bool {
+        self.free.is_empty()
+    }
+
+    /// Rent a buffer of `len` zeros
+    ///
+    /// The smallest pooled buffer with enough capacity is reused. If none is large enough the largest one grows,
+    /// and a new buffer is only allocated when the pool is empty.
+    pub fn rent(&mut self, len: usize) -> Vec<f64> {
+        let best = (0..self.free.len())
+            .filter(|&i| self.free[i].capacity() >= len)
+            .min_by_key(|&i| self.free[i].capacity())
+            .or_else(|| (0..self.free.len()).max_by_key(|&i| self.free[i].capacity()));
+        match best {
+            Some(i) => {
+                let mut buffer = self.free.swap_remove(i);
+                buffer.clear();
+                buffer.resize(len, 0.0);
+                buffer
+            }
+            None => vec![0.0; len],
+        }
+    }
+
+    /// Return a buffer to the pool
+    pub fn give_back(&mut self, buffer: Vec<f64>) {
+        self.free.push(buffer);
+    }
+
+    /// Return the buffer of a map to the pool
+    pub fn recycle(&mut self, map: NoiseMap) {
+        self.give_back(map.into_values());
+    }
+
+    /// Drop every pooled buffer
+    pub fn clear(&mut self) {
+        self.free.clear();
+    }
+
+    /// Samples the region into a rented buffer, see [`NoiseSource::generate_map`]
+    pub fn generate_map<S: NoiseSource + ?Sized>(
+        &mut self,
+        source: &S,
+        region: Region,
+        resolution: (usize, usize),
+    ) -> NoiseMap {
+        let mut values = self.rent(resolution.0 * resolution.1);
+        source.fill_region(region, resolution, &mut values);
+        NoiseMap::from_values(region, resolution, values)
+    }
+
+    /// Samples scattered points into a rented buffer, see [`NoiseSource::sample_points_into`]
+    pub fn sample_points<S: NoiseSource + ?Sized>(&mut self, source: &S, points: &[(f64, f64)]) -> Vec<f64> {
+        let mut values = self.rent(points.len());
+        source.sample_points_into(points, &mut values);
+        values
+    }
+}
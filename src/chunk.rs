@@ -0,0 +1,370 @@
+//! Chunk generation that summarizes the values while they are produced.
+//!
+//! Servers streaming a world decide what to do with a chunk from a few numbers: whether it is all sea, how high it
+//! reaches, which biome covers most of it. [`generate_chunk`] returns those in a [`ChunkSummary`] next to the map,
+//! gathered row by row while each row is still in cache instead of scanning the finished buffer again.
+//!
+//! ```
+//! use perlin2d::chunk::{generate_chunk, generate_chunk_with_biomes};
+//! use perlin2d::{NoiseSource, PerlinNoise2D, Region};
+//!
+//! let perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 21);
+//! let region = Region::new((0.0, 0.0), (64.0, 64.0));
+//!
+//! let (map, summary) = generate_chunk(&perlin, region, (32, 32), 0.0);
+//! assert_eq!(map, perlin.generate_map(region, (32, 32)));
+//! assert!(summary.get_min() <= summary.get_mean() && summary.get_mean() <= summary.get_max());
+//! assert_eq!(summary.get_dominant_biome(), None);
+//!
+//! // biome 1 above sea level, biome 0 below it
+//! let biome = |x: f64, y: f64| (perlin.get_noise(x, y) >= 0.0) as u32;
+//! let (_, summary) = generate_chunk_with_biomes(&perlin, region, (32, 32), 0.0, biome);
+//! let expected = if summary.get_water_fraction() > 0.5 { 0 } else { 1 };
+//! assert_eq!(summary.get_dominant_biome(), Some(expected));
+//! ```
+//!
+//! Neighboring chunks generated at different resolutions crack along their shared edge, because the finer chunk has
+//! border samples between those of the coarser one. Chunks laid out with [`closed_layout`] both sample the shared edge,
+//! and [`stitch_edge`] moves the border samples of the finer chunk onto the straight segments of the coarser border.
+//!
+//! ```
+//! use perlin2d::chunk::{closed_layout, generate_chunk, stitch_edge, ChunkEdge};
+//! use perlin2d::{PerlinNoise2D, Region};
+//!
+//! let perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 21);
+//! let (near, near_resolution) = closed_layout(Region::new((0.0, 0.0), (64.0, 64.0)), (64, 64));
+//! let (far, far_resolution) = closed_layout(Region::new((64.0, 0.0), (128.0, 64.0)), (16, 16));
+//! let (mut near_map, _) = generate_chunk(&perlin, near, near_resolution, 0.0);
+//! let (far_map, _) = generate_chunk(&perlin, far, far_resolution, 0.0);
+//!
+//! stitch_edge(&mut near_map, &far_map, ChunkEdge::MaxX);
+//! for j in 0..=16 {
+//!     // every fourth border sample of the near chunk is a border sample of the far chunk
+//!     assert_eq!(near_map.get_value(64, 4 * j), far_map.get_value(0, j));
+//! }
+//! let midpoint = 0.5 * (far_map.get_value(0, 0) + far_map.get_value(0, 1));
+//! assert!((near_map.get_value(64, 2) - midpoint).abs() < 1e-12);
+//! ```
+
+use crate::{NoiseMap, NoiseSource, Region};
+use std::collections::BTreeMap;
+
+/// Statistics of a generated chunk
+///
+/// Member variables:
+///
+/// * `min` - The lowest value.
+/// * `max` - The highest value.
+/// * `mean` - The mean value.
+/// * `water_fraction` - The fraction of samples below the sea level, in `[0, 1]`.
+/// * `dominant_biome` - The biome ID covering the most samples, the lowest ID on ties. `None` when generated without
+///   biomes.
+///
+/// Every statistic of an empty chunk is zero.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChunkSummary {
+    min: f64,
+    max: f64,
+    mean: f64,
+    water_fraction: f64,
+    dominant_biome: Option<u32>,
+}
+
+impl ChunkSummary {
+    /// Summarizes an existing map, see [`generate_chunk`]
+    pub fn from_map(map: &NoiseMap, sea_level: f64) -> Self {
+        let mut accumulator = Accumulator::new(sea_level);
+        accumulator.add_row(map.get_values());
+        accumulator.finish(None)
+    }
+
+    /// Getter function for min
+    pub fn get_min(&self) -> f64 {
+        self.min
+    }
+    /// Getter function for max
+    pub fn get_max(&self) -> f64 {
+        self.max
+    }
+    /// Getter function for mean
+    pub fn get_mean(&self) -> f64 {
+        self.mean
+    }
+    /// Getter function for water_fraction
+    pub fn get_water_fraction(&self) -> f64 {
+        self.water_fraction
+    }
+    /// Getter function for dominant_biome
+    pub fn get_dominant_biome(&self) -> Option<u32> {
+        self.dominant_biome
+    }
+
+    /// Returns true if every sample lies below the sea level
+    pub fn is_submerged(&self) -> bool {
+        self.water_fraction == 1.0
+    }
+    /// Returns true if no sample lies below the sea level
+    pub fn is_dry(&self) -> bool {
+        self.water_fraction == 0.0
+    }
+}
+
+/// Side of a chunk
+///
+/// * `MinX` - The column at the smallest `x`.
+/// * `MaxX` - The column at the largest `x`.
+/// * `MinY` - The row at the smallest `y`.
+/// * `MaxY` - The row at the largest `y`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChunkEdge {
+    MinX,
+    MaxX,
+    MinY,
+    MaxY,
+}
+
+impl ChunkEdge {
+    /// The side of a neighbor that touches this side
+    pub fn opposite(self) -> Self {
+        match self {
+            ChunkEdge::MinX => ChunkEdge::MaxX,
+            ChunkEdge::MaxX => ChunkEdge::MinX,
+            ChunkEdge::MinY => ChunkEdge::MaxY,
+            ChunkEdge::MaxY => ChunkEdge::MinY,
+        }
+    }
+}
+
+/// Samples a region like [`NoiseSource::generate_map`] and summarizes the values in the same pass
+///
+/// Samples strictly below `sea_level` count as water. The map holds exactly the values `generate_map` returns.
+pub fn generate_chunk<S: NoiseSource + ?Sized>(
+    source: &S,
+    region: Region,
+    resolution: (usize, usize),
+    sea_level: f64,
+) -> (NoiseMap, ChunkSummary) {
+    let mut map = NoiseMap::new(region, resolution);
+    let mut accumulator = Accumulator::new(sea_level);
+    fill_rows(source, region, resolution, map.get_values_mut(), |_, row| {
+        accumulator.add_row(row)
+    });
+    (map, accumulator.finish(None))
+}
+
+/// Samples a region like [`generate_chunk`] and also finds the dominant biome
+///
+/// `biome` gives the biome ID at a point, as in [`crate::placement::place`], and is evaluated at every sample point.
+pub fn generate_chunk_with_biomes<S: NoiseSource + ?Sized, B: Fn(f64, f64) -> u32>(
+    source: &S,
+    region: Region,
+    resolution: (usize, usize),
+    sea_level: f64,
+    biome: B,
+) -> (NoiseMap, ChunkSummary) {
+    let mut map = NoiseMap::new(region, resolution);
+    let mut accumulator = Accumulator::new(sea_level);
+    let mut counts: BTreeMap<u32, usize> = BTreeMap::new();
+    fill_rows(source, region, resolution, map.get_values_mut(), |j, row| {
+        accumulator.add_row(row);
+        for i in 0..row.len() {
+            let (x, y) = region.sample_point(resolution, i, j);
+            *counts.entry(biome(x, y)).or_insert(0) += 1;
+        }
+    });
+    // the map iterates in ascending order and only strictly larger counts replace the best, so ties keep the lowest ID
+    let dominant = counts
+        .iter()
+        .fold(None, |best: Option<(u32, usize)>, (&id, &count)| match best {
+            Some((_, most)) if most >= count => best,
+            _ => Some((id, count)),
+        });
+    (map, accumulator.finish(dominant.map(|(id, _)| id)))
+}
+
+/// Region and resolution that split `region` into `resolution` cells with samples on all four edges
+///
+/// Regions are sampled at the minimum corner of every cell and exclude their maximum edge. The returned layout grows
+/// the region by one cell and the resolution by one sample along each axis, so the last row and column fall on the
+/// maximum edge. Neighboring chunks laid out this way both sample the edge they share, as [`stitch_edge`] requires.
+pub fn closed_layout(region: Region, resolution: (usize, usize)) -> (Region, (usize, usize)) {
+    let (step_x, step_y) = region.step(resolution);
+    (
+        Region::new(region.min, (region.max.0 + step_x, region.max.1 + step_y)),
+        (resolution.0 + 1, resolution.1 + 1),
+    )
+}
+
+/// Moves the border samples of `map` along `edge` onto the border of a neighbor generated at a lower resolution
+///
+/// Both maps have to sample the edge they share, see [`closed_layout`], and `neighbor` has to touch `map` at `edge`.
+/// Every border sample of `map` is replaced by the linear interpolation of the two nearest border samples of the
+/// neighbor, so both chunks describe the same polyline along the edge and meshes built from them close without
+/// cracks. The match is exact when the resolution of `map` is a whole multiple of the one of `neighbor`, which places
+/// every neighbor sample on a sample of `map`. Stitching the coarser of two chunks leaves it unchanged.
+///
+/// Panics if the border of `neighbor` does not lie on the border of `map`.
+pub fn stitch_edge(map: &mut NoiseMap, neighbor: &NoiseMap, edge: ChunkEdge) {
+    let border = Border::new(map, edge);
+    let other = Border::new(neighbor, edge.opposite());
+    if border.len == 0 || other.len == 0 {
+        return;
+    }
+    let tolerance = 1e-6 * f64::max(border.step.abs(), other.step.abs());
+    assert!(
+        (border.across - other.across).abs() <= tolerance,
+        "the neighbor does not share the edge of the map"
+    );
+    let stitched: Vec<f64> = (0..border.len)
+        .map(|k| {
+            let along = border.origin + k as f64 * border.step;
+            let mut u = ((along - other.origin) / other.step).clamp(0.0, (other.len - 1) as f64);
+            // samples at the same position take the neighbor value exactly, despite rounding of the positions
+            if (u - u.round()).abs() < 1e-9 {
+                u = u.round();
+            }
+            let k0 = u.floor() as usize;
+            let k1 = usize::min(k0 + 1, other.len - 1);
+            let t = u - k0 as f64;
+            let (a, b) = (other.get(neighbor, k0), other.get(neighbor, k1));
+            a + (b - a) * t
+        })
+        .collect();
+    for (k, value) in stitched.into_iter().enumerate() {
+        border.set(map, k, value);
+    }
+}
+
+/// The samples of a map along one of its sides
+struct Border {
+    edge: ChunkEdge,
+    /// Number of samples along the side
+    len: usize,
+    /// Position of the first sample along the side
+    origin: f64,
+    /// Distance between samples along the side
+    step: f64,
+    /// Position of the side across it
+    across: f64,
+}
+
+impl Border {
+    fn new(map: &NoiseMap, edge: ChunkEdge) -> Self {
+        let (w, h) = map.get_resolution();
+        let coords = map.get_coord_map();
+        let (origin_x, origin_y) = coords.get_origin();
+        let (step_x, step_y) = coords.get_cell_size();
+        let (len, origin, step, index) = match edge {
+            ChunkEdge::MinX => (h, origin_y, step_y, 0),
+            ChunkEdge::MaxX => (h, origin_y, step_y, w.saturating_sub(1)),
+            ChunkEdge::MinY => (w, origin_x, step_x, 0),
+            ChunkEdge::MaxY => (w, origin_x, step_x, h.saturating_sub(1)),
+        };
+        let across = match edge {
+            ChunkEdge::MinX | ChunkEdge::MaxX => coords.grid_to_world(index as f64, 0.0).0,
+            ChunkEdge::MinY | ChunkEdge::MaxY => coords.grid_to_world(0.0, index as f64).1,
+        };
+        let len = if w == 0 || h == 0 { 0 } else { len };
+        Self {
+            edge,
+            len,
+            origin,
+            step,
+            across,
+        }
+    }
+
+    /// `(column, row)` of the `k`th sample along the side
+    fn cell(&self, map: &NoiseMap, k: usize) -> (usize, usize) {
+        let (w, h) = map.get_resolution();
+        match self.edge {
+            ChunkEdge::MinX => (0, k),
+            ChunkEdge::MaxX => (w - 1, k),
+            ChunkEdge::MinY => (k, 0),
+            ChunkEdge::MaxY => (k, h - 1),
+        }
+    }
+
+    fn get(&self, map: &NoiseMap, k: usize) -> f64 {
+        let (i, j) = self.cell(map, k);
+        map.get_value(i, j)
+    }
+
+    fn set(&self, map: &mut NoiseMap, k: usize, value: f64) {
+        let (i, j) = self.cell(map, k);
+        map.set_value(i, j, value);
+    }
+}
+
+/// Fills `out` one row at a time through [`NoiseSource::fill_rows`] and hands every finished row to `visit`
+fn fill_rows<S: NoiseSource + ?Sized, F: FnMut(usize, &[f64])>(
+    source: &S,
+    region: Region,
+    resolution: (usize, usize),
+    out: &mut [f64],
+    mut visit: F,
+) {
+    if resolution.0 == 0 {
+        return;
+    }
+    for (j, row) in out.chunks_mut(resolution.0).enumerate() {
+        source.fill_rows(region, resolution, j, row);
+        visit(j, row);
+    }
+}
+
+/// Running statistics of the values of a chunk
+struct Accumulator {
+    sea_level: f64,
+    count: usize,
+    sum: f64,
+    water: usize,
+    min: f64,
+    max: f64,
+}
+
+impl Accumulator {
+    fn new(sea_level: f64) -> Self {
+        Self {
+            sea_level,
+            count: 0,
+            sum: 0.0,
+            water: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    fn add_row(&mut self, row: &[f64]) {
+        for &value in row {
+            self.sum += value;
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+            if value < self.sea_level {
+                self.water += 1;
+            }
+        }
+        self.count += row.len();
+    }
+
+    fn finish(self, dominant_biome: Option<u32>) -> ChunkSummary {
+        if self.count == 0 {
+            return ChunkSummary {
+                min: 0.0,
+                max: 0.0,
+                mean: 0.0,
+                water_fraction: 0.0,
+                dominant_biome,
+            };
+        }
+        ChunkSummary {
+            min: self.min,
+            max: self.max,
+            mean: self.sum / self.count as f64,
+            water_fraction: self.water as f64 / self.count as f64,
+            dominant_biome,
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Large scale climate fields to combine with noise.
+//!
+//! Temperature mostly follows latitude: warm around the equator and cold towards the poles. A [`LatitudeGradient`]
+//! provides those bands as a [`NoiseSource`] along the `y` axis, and blending it with a noise generator perturbs the
+//! bands into a believable temperature map.
+//!
+//! ```
+//! use perlin2d::{BlendedNoise, LatitudeGradient, NoiseSource, PerlinNoise2D};
+//!
+//! // 30 degrees at the equator on y = 0, -20 degrees at the poles 1000 units north and south
+//! let bands = LatitudeGradient::new(0.0, 1000.0, 30.0, -20.0);
+//! let weather = PerlinNoise2D::new(4, 1.0, 0.5, 0.5, 2.0, (200.0, 200.0), 0.0, 9);
+//! let layers: Vec<(Box<dyn NoiseSource>, f64)> = vec![(Box::new(bands), 1.0), (Box::new(weather), 5.0)];
+//! let temperature = BlendedNoise::new(layers);
+//!
+//! assert!(temperature.get_noise(300.0, 0.0) > temperature.get_noise(300.0, 900.0));
+//! let (min, max) = temperature.get_range();
+//! // the bands move by at most five times the range of the weather noise
+//! assert!(min >= -30.0 && max <= 40.0);
+//! ```
+
+use crate::NoiseSource;
+
+/// How the value changes between the equator and the poles
+///
+/// * `Linear` - In proportion to the distance from the equator.
+/// * `Cosine` - With the cosine of the latitude, like the sunlight a spherical planet receives. Values stay close to
+///   the equator value in the tropics and change fastest towards the poles.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LatitudeProfile {
+    #[default]
+    Linear,
+    Cosine,
+}
+
+/// Value bands that only depend on the `y` coordinate
+///
+/// Member variables:
+///
+/// * `equator` - The `y` coordinate of the equator.
+/// * `pole_distance` - The distance along `y` from the equator to either pole. Points beyond it take the pole value.
+/// * `equator_value` - The value on the equator.
+/// * `pole_value` - The value at and beyond the poles.
+/// * `profile` - The shape of the change between the two. Defaults to `Linear`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LatitudeGradient {
+    equator: f64,
+    pole_distance: f64,
+    equator_value: f64,
+    pole_value: f64,
+    profile: LatitudeProfile,
+}
+
+impl LatitudeGradient {
+    /// Create and return a new LatitudeGradient object with a linear profile
+    ///
+    /// Panics if `pole_distance` is not a positive finite number.
+    pub fn new(equator: f64, pole_distance: f64, equator_value: f64, pole_value: f64) -> Self {
+        assert_pole_distance(pole_distance);
+        Self {
+            equator,
+            pole_distance,
+            equator_value,
+            pole_value,
+            profile: LatitudeProfile::Linear,
+        }
+    }
+
+    /// Getter function for equator
+    pub fn get_equator(&self) -> f64 {
+        self.equator
+    }
+    /// Getter function for pole_distance
+    pub fn get_pole_distance(&self) -> f64 {
+        self.pole_distance
+    }
+    /// Getter function for equator_value
+    pub fn get_equator_value(&self) -> f64 {
+        self.equator_value
+    }
+    /// Getter function for pole_value
+    pub fn get_pole_value(&self) -> f64 {
+        self.pole_value
+    }
+    /// Getter function for profile
+    pub fn get_profile(&self) -> LatitudeProfile {
+        self.profile
+    }
+
+    /// Setter function for equator
+    pub fn set_equator(&mut self, equator: f64) {
+        self.equator = equator;
+    }
+    /// Setter function for pole_distance
+    ///
+    /// Panics if `pole_distance` is not a positive finite number.
+    pub fn set_pole_distance(&mut self, pole_distance: f64) {
+        assert_pole_distance(pole_distance);
+        self.pole_distance = pole_distance;
+    }
+    /// Setter function for equator_value
+    pub fn set_equator_value(&mut self, equator_value: f64) {
+        self.equator_value = equator_value;
+    }
+    /// Setter function for pole_value
+    pub fn set_pole_value(&mut self, pole_value: f64) {
+        self.pole_value = pole_value;
+    }
+    /// Setter function for profile
+    pub fn set_profile(&mut self, profile: LatitudeProfile) {
+        self.profile = profile;
+    }
+
+    /// Latitude of a `y` coordinate, from `0` on the equator to `1` at and beyond the poles
+    pub fn get_latitude(&self, y: f64) -> f64 {
+        (f64::abs(y - self.equator) / self.pole_distance).min(1.0)
+    }
+}
+
+impl NoiseSource for LatitudeGradient {
+    fn get_noise(&self, _x: f64, y: f64) -> f64 {
+        let latitude = self.get_latitude(y);
+        let weight = match self.profile {
+            LatitudeProfile::Linear => latitude,
+            LatitudeProfile::Cosine => 1.0 - f64::cos(latitude * std::f64::consts::FRAC_PI_2),
+        };
+        self.equator_value + (self.pole_value - self.equator_value) * weight
+    }
+
+    fn get_range(&self) -> (f64, f64) {
+        (
+            f64::min(self.equator_value, self.pole_value),
+            f64::max(self.equator_value, self.pole_value),
+        )
+    }
+}
+
+fn assert_pole_distance(pole_distance: f64) {
+    assert!(
+        pole_distance > 0.0 && pole_distance.is_finite(),
+        "pole distance must be positive and finite"
+    );
+}
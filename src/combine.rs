@@ -0,0 +1,355 @@
+//! Combinators that build new noise sources out of existing ones.
+
+use crate::{BoundaryMode, LatticeHasher, NoiseSource, PerlinNoise2D, PrimeHasher, Region};
+
+/// Weighted sum of several noise sources
+///
+/// The output at every point is `sum(weight * source)`. Weights are used as given and are not normalized, so
+/// `[(continents, 0.7), (detail, 0.3)]` keeps the overall magnitude of its inputs while `[(a, 1.0), (b, 1.0)]` adds
+/// them. The output range is tracked from the ranges of the inputs, taking negative weights into account.
+///
+/// # Example
+///
+/// ```
+/// use perlin2d::{BlendedNoise, NoiseSource, PerlinNoise2D};
+///
+/// let continents = PerlinNoise2D::new(2, 1.0, 0.5, 0.5, 2.0, (400.0, 400.0), 0.0, 1);
+/// let detail = PerlinNoise2D::new(6, 1.0, 2.0, 0.5, 2.0, (50.0, 50.0), 0.0, 2);
+/// let terrain = BlendedNoise::new(vec![(continents, 0.7), (detail, 0.3)]);
+///
+/// let (min, max) = terrain.get_range();
+/// let height = terrain.get_noise(5.0, 10.0);
+/// assert!(min <= height && height <= max);
+/// ```
+#[derive(Clone)]
+pub struct BlendedNoise<S> {
+    sources: Vec<(S, f64)>,
+}
+
+impl<S: NoiseSource> BlendedNoise<S> {
+    /// Create and return a new BlendedNoise object from `(source, weight)` pairs
+    pub fn new(sources: Vec<(S, f64)>) -> Self {
+        Self { sources }
+    }
+
+    /// Add another source with the given weight
+    pub fn push(&mut self, source: S, weight: f64) {
+        self.sources.push((source, weight));
+    }
+
+    /// Getter function for the `(source, weight)` pairs
+    pub fn get_sources(&self) -> &[(S, f64)] {
+        &self.sources
+    }
+
+    /// Mutable access to the `(source, weight)` pairs, for example to retune weights
+    pub fn get_sources_mut(&mut self) -> &mut [(S, f64)] {
+        &mut self.sources
+    }
+}
+
+impl<S: NoiseSource> NoiseSource for BlendedNoise<S> {
+    fn get_noise(&self, x: f64, y: f64) -> f64 {
+        self.sources
+            .iter()
+            .map(|(source, weight)| weight * source.get_noise(x, y))
+            .sum()
+    }
+
+    fn get_range(&self) -> (f64, f64) {
+        self.sources.iter().fold((0.0, 0.0), |(min, max), (source, weight)| {
+            let (lo, hi) = source.get_range();
+            let (a, b) = (weight * lo, weight * hi);
+            (min + f64::min(a, b), max + f64::max(a, b))
+        })
+    }
+}
+
+/// Uses one noise source to decide how much of another shows through
+///
+/// At every point the value of the `control` source is mapped to a weight in `[0, 1]` and the output is
+/// `weight * source`. By default the weight is the control value remapped linearly from its range, so the source
+/// fades in smoothly where the control is high.
+///
+/// With a threshold set, the weight is `1` where the control lies inside `[lower, upper]` and `0` outside, with a
+/// smooth transition of width `falloff` centered on each bound. This is the building block for rules such as
+/// "mountains only appear on continents".
+#[derive(Clone)]
+pub struct Masked<C, S> {
+    control: C,
+    source: S,
+    threshold: Option<(f64, f64, f64)>,
+}
+
+impl<C: NoiseSource, S: NoiseSource> Masked<C, S> {
+    /// Create and return a new Masked object
+    pub fn new(control: C, source: S) -> Self {
+        Self {
+            control,
+            source,
+            threshold: None,
+        }
+    }
+
+    /// Getter function for the control source
+    pub fn get_control(&self) -> &C {
+        &self.control
+    }
+    /// Getter function for the masked source
+    pub fn get_source(&self) -> &S {
+        &self.source
+    }
+    /// Getter function for the `(lower, upper, falloff)` threshold
+    pub fn get_threshold(&self) -> Option<(f64, f64, f64)> {
+        self.threshold
+    }
+
+    /// Setter function for the threshold, `falloff` is the width of the smooth edge around each bound
+    pub fn set_threshold(&mut self, lower: f64, upper: f64, falloff: f64) {
+        self.threshold = Some((lower, upper, f64::max(falloff, 0.0)));
+    }
+    /// Remove the threshold and go back to a linear remap of the control range
+    pub fn clear_threshold(&mut self) {
+        self.threshold = None;
+    }
+
+    /// Returns the weight in `[0, 1]` applied to the source at the given point
+    pub fn get_weight(&self, x: f64, y: f64) -> f64 {
+        let control = self.control.get_noise(x, y);
+        match self.threshold {
+            Some((lower, upper, falloff)) => threshold_weight(control, lower, upper, falloff),
+            None => {
+                let (min, max) = self.control.get_range();
+                if max > min {
+                    ((control - min) / (max - min)).clamp(0.0, 1.0)
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+}
+
+impl<C: NoiseSource, S: NoiseSource> NoiseSource for Masked<C, S> {
+    fn get_noise(&self, x: f64, y: f64) -> f64 {
+        let weight = self.get_weight(x, y);
+        if weight == 0.0 {
+            return 0.0;
+        }
+        weight * self.source.get_noise(x, y)
+    }
+
+    fn get_range(&self) -> (f64, f64) {
+        let (min, max) = self.source.get_range();
+        (f64::min(min, 0.0), f64::max(max, 0.0))
+    }
+}
+
+/// Restricts a noise source to a region and repeats, mirrors or clamps it outside
+///
+/// Sampling a larger area, for example with [`NoiseSource::generate_map`], then produces a texture that tiles or
+/// mirrors the chosen sub-region.
+///
+/// ```
+/// use perlin2d::{BoundaryMode, Bounded, NoiseSource, PerlinNoise2D, Region};
+///
+/// let perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (30.0, 30.0), 0.0, 6);
+/// let tile = Region::new((0.0, 0.0), (64.0, 64.0));
+///
+/// let repeated = Bounded::new(&perlin, tile, BoundaryMode::Repeat);
+/// assert_eq!(repeated.get_noise(70.0, -10.0), perlin.get_noise(6.0, 54.0));
+///
+/// let mirrored = Bounded::new(&perlin, tile, BoundaryMode::Mirror);
+/// assert_eq!(mirrored.get_noise(70.0, -10.0), perlin.get_noise(58.0, 10.0));
+/// ```
+#[derive(Clone)]
+pub struct Bounded<S> {
+    source: S,
+    region: Region,
+    mode: BoundaryMode,
+}
+
+impl<S: NoiseSource> Bounded<S> {
+    /// Create and return a new Bounded object
+    pub fn new(source: S, region: Region, mode: BoundaryMode) -> Self {
+        Self { source, region, mode }
+    }
+
+    /// Getter function for the source
+    pub fn get_source(&self) -> &S {
+        &self.source
+    }
+    /// Getter function for region
+    pub fn get_region(&self) -> Region {
+        self.region
+    }
+    /// Getter function for mode
+    pub fn get_mode(&self) -> BoundaryMode {
+        self.mode
+    }
+
+    /// Setter function for region
+    pub fn set_region(&mut self, region: Region) {
+        self.region = region;
+    }
+    /// Setter function for mode
+    pub fn set_mode(&mut self, mode: BoundaryMode) {
+        self.mode = mode;
+    }
+}
+
+impl<S: NoiseSource> NoiseSource for Bounded<S> {
+    fn get_noise(&self, x: f64, y: f64) -> f64 {
+        let (x, y) = self.region.apply_boundary(self.mode, x, y);
+        self.source.get_noise(x, y)
+    }
+
+    fn get_range(&self) -> (f64, f64) {
+        self.source.get_range()
+    }
+}
+
+/// Adds a high frequency detail layer on top of a base layer, fading out octaves too fine for the view
+///
+/// `lod` is the footprint of one pixel in input units, for example the distance to the camera times the size of a
+/// pixel at unit distance. An octave of the detail generator shows fully while its wavelength spans at least four
+/// pixels, and fades smoothly to zero, the mean of an octave, before reaching the two pixels of the Nyquist limit.
+/// Octaves that would alias never show up, so close-up texturing stays stable while the camera moves. A `lod` of
+/// zero or less keeps every octave.
+///
+/// The detail layer is summed as `Fbm` whatever the mode of its generator, and its exponent is not applied. Its
+/// amplitude and bias are used as usual.
+///
+/// ```
+/// use perlin2d::{DetailBlend, NoiseSource, PerlinNoise2D};
+///
+/// let terrain = PerlinNoise2D::new(6, 50.0, 0.5, 0.5, 2.0, (500.0, 500.0), 0.0, 1);
+/// let grain = PerlinNoise2D::new(8, 0.5, 1.0, 0.6, 2.0, (4.0, 4.0), 0.0, 2);
+/// let mut surface = DetailBlend::new(terrain, grain, 0.0);
+///
+/// // next to the camera every octave of the grain shows
+/// let near = surface.get_noise(10.0, 20.0);
+/// assert_eq!(surface.get_octave_weight(7, 0.0), 1.0);
+///
+/// // far away the grain fades out and only the terrain is left
+/// surface.set_lod(100.0);
+/// assert_eq!(surface.get_noise(10.0, 20.0), terrain.get_noise(10.0, 20.0) + grain.get_bias());
+/// ```
+#[derive(Clone)]
+pub struct DetailBlend<S, H = PrimeHasher> {
+    base: S,
+    detail: PerlinNoise2D<H>,
+    lod: f64,
+}
+
+impl<S: NoiseSource, H: LatticeHasher> DetailBlend<S, H> {
+    /// Create and return a new DetailBlend object
+    pub fn new(base: S, detail: PerlinNoise2D<H>, lod: f64) -> Self {
+        Self { base, detail, lod }
+    }
+
+    /// Getter function for the base layer
+    pub fn get_base(&self) -> &S {
+        &self.base
+    }
+    /// Getter function for the detail generator
+    pub fn get_detail(&self) -> &PerlinNoise2D<H> {
+        &self.detail
+    }
+    /// Getter function for lod
+    pub fn get_lod(&self) -> f64 {
+        self.lod
+    }
+
+    /// Setter function for lod, the footprint of a pixel used by [`NoiseSource::get_noise`]
+    pub fn set_lod(&mut self, lod: f64) {
+        self.lod = lod;
+    }
+
+    /// Returns the weight in `[0, 1]` of detail octave `octave` for a pixel footprint of `lod`
+    pub fn get_octave_weight(&self, octave: usize, lod: f64) -> f64 {
+        if lod <= 0.0 {
+            return 1.0;
+        }
+        let frequency = self.base_frequency() * self.detail.lacunarity.abs().powi(octave as i32);
+        // pixels covered by one wavelength, zero at two and one at four
+        smooth_step((1.0 / (frequency * lod) - 2.0) / 2.0)
+    }
+
+    /// Returns the blended value at `(x, y)` for a pixel footprint of `lod`, for views whose footprint varies per pixel
+    pub fn get_noise_lod(&self, x: f64, y: f64, lod: f64) -> f64 {
+        let detail = &self.detail;
+        let base = self.base.get_noise(x, y);
+        let (x, y) = detail.to_unit_space(x, y);
+        let seed = detail.seed as f64;
+        let mut t = 0.0;
+        let mut amp = 1.0;
+        let (mut freq_x, mut freq_y) = detail.frequency;
+        for k in 0..detail.octaves.max(0) as usize {
+            let weight = self.get_octave_weight(k, lod);
+            if weight == 0.0 && detail.lacunarity.abs() >= 1.0 {
+                // finer octaves only fade further
+                break;
+            }
+            if weight > 0.0 {
+                t += weight * detail.get_value(y * freq_y + seed, x * freq_x + seed, seed) * amp;
+            }
+            amp *= detail.persistence;
+            freq_x *= detail.lacunarity;
+            freq_y *= detail.lacunarity;
+        }
+        base + (detail.bias + detail.get_effective_amplitude() * t)
+    }
+
+    /// Cycles per input unit of the first detail octave, along its fastest axis
+    fn base_frequency(&self) -> f64 {
+        let detail = &self.detail;
+        let stretch = match detail.transform {
+            Some(transform) => {
+                let m = transform.matrix;
+                let stretch = f64::max(m[0][0].abs() + m[0][1].abs(), m[1][0].abs() + m[1][1].abs());
+                (stretch, stretch)
+            }
+            None => (1.0 / detail.scale.0.abs(), 1.0 / detail.scale.1.abs()),
+        };
+        f64::max(
+            detail.frequency.0.abs() * stretch.0,
+            detail.frequency.1.abs() * stretch.1,
+        )
+    }
+}
+
+impl<S: NoiseSource, H: LatticeHasher> NoiseSource for DetailBlend<S, H> {
+    fn get_noise(&self, x: f64, y: f64) -> f64 {
+        self.get_noise_lod(x, y, self.lod)
+    }
+
+    fn get_range(&self) -> (f64, f64) {
+        let detail = &self.detail;
+        let mut sum = 0.0;
+        let mut amp: f64 = 1.0;
+        for _ in 0..detail.octaves.max(0) {
+            sum += amp.abs();
+            amp *= detail.persistence;
+        }
+        let spread = detail.get_effective_amplitude().abs() * sum;
+        let (lo, hi) = self.base.get_range();
+        (lo + detail.bias - spread, hi + detail.bias + spread)
+    }
+}
+
+pub(crate) fn threshold_weight(value: f64, lower: f64, upper: f64, falloff: f64) -> f64 {
+    // the edges may not overlap, so the falloff is limited to the width of the band
+    let half = 0.5 * f64::min(falloff, upper - lower);
+    if half <= 0.0 {
+        return if value < lower || value > upper { 0.0 } else { 1.0 };
+    }
+    let rise = smooth_step((value - (lower - half)) / (2.0 * half));
+    let fall = smooth_step(((upper + half) - value) / (2.0 * half));
+    f64::min(rise, fall)
+}
+
+fn smooth_step(t: f64) -> f64 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
@@ -0,0 +1,514 @@
+//! Plain data description of a [`PerlinNoise2D`] generator.
+//!
+//! A [`PerlinConfig`] holds the same parameters as the generator itself but with public fields, so presets can be
+//! stored in data files and edited without recompiling. With the `toml` or `json` features enabled it can be read
+//! from and written to those formats directly.
+//!
+//! ```toml
+//! octaves = 6
+//! amplitude = 10.0
+//! frequency = 0.5      # or [0.5, 2.0] for a different frequency per axis
+//! persistence = 1.0
+//! lacunarity = 2.0
+//! scale = [100.0, 100.0]
+//! bias = 0.5
+//! seed = 101
+//! version = "V1"
+//! interpolation = "Cubic"
+//! mode = "Fbm"         # or { HybridMultifractal = { offset = 0.7 } }
+//! exponent = 1.0
+//! normalized = false
+//! ```
+
+use crate::{Affine, AlgorithmVersion, FractalMode, Interpolation, PerlinNoise2D};
+use std::convert::TryFrom;
+use std::fmt;
+
+/// Serializable set of parameters for a [`PerlinNoise2D`] generator
+///
+/// See [`PerlinNoise2D`] for the meaning of each field.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PerlinConfig {
+    /// Clamped to [`PerlinConfig::effective_octaves`] when building a generator.
+    pub octaves: usize,
+    pub amplitude: f64,
+    /// Written as a single number when both axes share the same frequency.
+    #[cfg_attr(feature = "serde", serde(with = "axis_pair"))]
+    pub frequency: (f64, f64),
+    pub persistence: f64,
+    pub lacunarity: f64,
+    pub scale: (f64, f64),
+    pub bias: f64,
+    pub seed: i32,
+    /// Defaults to `V1` when missing, so presets written before versioning keep their output.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub version: AlgorithmVersion,
+    /// Defaults to `Cubic` when missing. `Custom` kernels can not be serialized.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub interpolation: Interpolation,
+    /// Defaults to `Fbm` when missing.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub mode: FractalMode,
+    /// Defaults to `1.0` when missing, which leaves the output unchanged.
+    #[cfg_attr(feature = "serde", serde(default = "default_exponent"))]
+    pub exponent: f64,
+    /// Replaces `scale` when present.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub transform: Option<Affine>,
+    /// Defaults to `false` when missing.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub normalized: bool,
+}
+
+/// Error returned when a configuration can not be loaded, saved or validated
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfigError {
+    /// The input could not be parsed, or a field is missing or has the wrong type.
+    Parse(String),
+    /// The configuration could not be written to the requested format.
+    Serialize(String),
+    /// A field parsed correctly but holds a value the generator can not use.
+    InvalidField { field: &'static str, reason: &'static str },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Parse(msg) => write!(f, "failed to parse configuration: {}", msg),
+            ConfigError::Serialize(msg) => write!(f, "failed to serialize configuration: {}", msg),
+            ConfigError::InvalidField { field, reason } => write!(f, "invalid value for `{}`: {}", field, reason),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl PerlinConfig {
+    /// Check that every field holds a value the generator can use
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let finite = [
+            ("amplitude", self.amplitude),
+            ("frequency", self.frequency.0),
+            ("frequency", self.frequency.1),
+            ("persistence", self.persistence),
+            ("lacunarity", self.lacunarity),
+            ("scale", self.scale.0),
+            ("scale", self.scale.1),
+            ("bias", self.bias),
+            ("exponent", self.exponent),
+        ];
+        for &(field, value) in finite.iter() {
+            if !value.is_finite() {
+                return Err(ConfigError::InvalidField {
+                    field,
+                    reason: "must be a finite number",
+                });
+            }
+        }
+        let mode_parameter = match self.mode {
+            FractalMode::Fbm => 0.0,
+            FractalMode::HybridMultifractal { offset } => offset,
+            FractalMode::SwissTurbulence { warp } => warp,
+        };
+        if !mode_parameter.is_finite() {
+            return Err(ConfigError::InvalidField {
+                field: "mode",
+                reason: "parameters must be finite numbers",
+            });
+        }
+        if self.exponent <= 0.0 {
+            return Err(ConfigError::InvalidField {
+                field: "exponent",
+                reason: "must be positive",
+            });
+        }
+        if let Some(transform) = self.transform {
+            let m = transform.matrix;
+            let values = [
+                m[0][0],
+                m[0][1],
+                m[1][0],
+                m[1][1],
+                transform.translation.0,
+                transform.translation.1,
+            ];
+            if !values.iter().all(|v| v.is_finite()) {
+                return Err(ConfigError::InvalidField {
+                    field: "transform",
+                    reason: "must only hold finite numbers",
+                });
+            }
+        }
+        if self.scale.0 == 0.0 || self.scale.1 == 0.0 {
+            return Err(ConfigError::InvalidField {
+                field: "scale",
+                reason: "must not be zero",
+            });
+        }
+        Ok(())
+    }
+
+    /// Number of octaves that can change the output, at most `octaves`
+    ///
+    /// Octaves stop contributing once their gain relative to the first octave drops below the `f64` precision, or once
+    /// their lattice cells become smaller than the distance between neighboring `f64` values one unit from the
+    /// origin. A configuration with at least one octave always keeps the first. Configurations whose octaves never
+    /// fade are limited to `i32::MAX`.
+    ///
+    /// ```
+    /// use perlin2d::PerlinConfig;
+    ///
+    /// let mut config = PerlinConfig::from(&perlin2d::PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 0));
+    /// assert_eq!(config.effective_octaves(), 6);
+    ///
+    /// // halving the gain of every octave reaches the f64 precision after 53 of them
+    /// config.octaves = 1000;
+    /// assert_eq!(config.effective_octaves(), 53);
+    /// assert_eq!(config.build().get_octaves(), 53);
+    /// ```
+    pub fn effective_octaves(&self) -> usize {
+        // stretch of unit space relative to input coordinates along each axis
+        let stretch = match self.transform {
+            Some(transform) => {
+                let m = transform.matrix;
+                let stretch = f64::max(m[0][0].abs() + m[0][1].abs(), m[1][0].abs() + m[1][1].abs());
+                (stretch, stretch)
+            }
+            None => (1.0 / self.scale.0.abs(), 1.0 / self.scale.1.abs()),
+        };
+        let frequency = f64::min(self.frequency.0.abs() * stretch.0, self.frequency.1.abs() * stretch.1);
+        // octave k contributes while persistence^k >= EPSILON and frequency * lacunarity^k <= 1 / EPSILON
+        let by_gain = (f64::EPSILON.ln() / self.persistence.abs().ln()).floor() + 1.0;
+        let by_frequency = (-(f64::EPSILON * frequency).ln() / self.lacunarity.abs().ln()).floor() + 1.0;
+        let count = |limit: f64| {
+            if limit.is_nan() || limit <= 0.0 {
+                // the octaves do not fade in this direction
+                usize::MAX
+            } else {
+                limit as usize
+            }
+        };
+        let contributing = usize::min(count(by_gain), count(by_frequency)).clamp(1, i32::MAX as usize);
+        usize::min(self.octaves, contributing)
+    }
+
+    /// Create a generator from this configuration, with the octaves clamped to [`PerlinConfig::effective_octaves`]
+    pub fn build(&self) -> PerlinNoise2D {
+        let mut perlin = PerlinNoise2D::new(
+            self.effective_octaves() as i32,
+            self.amplitude,
+            self.frequency.0,
+            self.persistence,
+            self.lacunarity,
+            self.scale,
+            self.bias,
+            self.seed,
+        );
+        perlin.set_axis_frequency(self.frequency);
+        perlin.set_version(self.version);
+        perlin.set_interpolation(self.interpolation);
+        perlin.set_mode(self.mode);
+        perlin.set_exponent(self.exponent);
+        perlin.set_transform(self.transform);
+        perlin.set_normalized(self.normalized);
+        perlin
+    }
+
+    /// Parse and validate a configuration from a TOML document
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(s: &str) -> Result<Self, ConfigError> {
+        let config: Self = toml::from_str(s).map_err(|e| ConfigError::Parse(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Serialize the configuration as a TOML document
+    #[cfg(feature = "toml")]
+    pub fn to_toml_string(&self) -> Result<String, ConfigError> {
+        toml::to_string(self).map_err(|e| ConfigError::Serialize(e.to_string()))
+    }
+
+    /// Parse and validate a configuration from a JSON document
+    #[cfg(feature = "json")]
+    pub fn from_json_str(s: &str) -> Result<Self, ConfigError> {
+        let config: Self = serde_json::from_str(s).map_err(|e| ConfigError::Parse(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Serialize the configuration as a pretty-printed JSON document
+    #[cfg(feature = "json")]
+    pub fn to_json_string(&self) -> Result<String, ConfigError> {
+        serde_json::to_string_pretty(self).map_err(|e| ConfigError::Serialize(e.to_string()))
+    }
+}
+
+/// Leading bytes of the binary encoding, followed by the format version
+const MAGIC: [u8; 3] = *b"P2D";
+const FORMAT_VERSION: u8 = 2;
+/// Length of the first format version, which ends before the `normalized` flag
+const V1_ENCODED_LEN: usize = 144;
+
+impl PerlinConfig {
+    /// Length in bytes of the encoding produced by [`PerlinConfig::to_bytes`]
+    pub const ENCODED_LEN: usize = 145;
+
+    /// Encode the configuration in a fixed-layout little-endian binary format
+    ///
+    /// The encoding is always [`PerlinConfig::ENCODED_LEN`] bytes long and starts with the bytes `P2D` and a format
+    /// version, so it can be embedded in file headers and handshakes without serde. `Custom` interpolation kernels can
+    /// not be encoded.
+    ///
+    /// ```
+    /// use perlin2d::{PerlinConfig, PerlinNoise2D};
+    ///
+    /// let perlin = PerlinNoise2D::new(6, 10.0, 0.5, 1.0, 2.0, (100.0, 100.0), 0.5, 101);
+    /// let bytes = perlin.to_bytes().unwrap();
+    /// assert_eq!(bytes.len(), PerlinConfig::ENCODED_LEN);
+    ///
+    /// let restored = PerlinNoise2D::from_bytes(&bytes).unwrap();
+    /// assert_eq!(restored.get_noise(3.0, 4.0), perlin.get_noise(3.0, 4.0));
+    /// ```
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ConfigError> {
+        let mut out = Vec::with_capacity(Self::ENCODED_LEN);
+        out.extend_from_slice(&MAGIC);
+        out.push(FORMAT_VERSION);
+        out.extend_from_slice(&i32::try_from(self.octaves).unwrap_or(i32::MAX).to_le_bytes());
+        for value in [
+            self.amplitude,
+            self.frequency.0,
+            self.frequency.1,
+            self.persistence,
+            self.lacunarity,
+            self.scale.0,
+            self.scale.1,
+            self.bias,
+        ]
+        .iter()
+        {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        out.extend_from_slice(&self.seed.to_le_bytes());
+        out.push(match self.version {
+            AlgorithmVersion::V1 => 1,
+            AlgorithmVersion::V2 => 2,
+            AlgorithmVersion::V3 => 3,
+            AlgorithmVersion::ImprovedGradient => 4,
+        });
+        out.push(match self.interpolation {
+            Interpolation::Cubic => 0,
+            Interpolation::Linear => 1,
+            Interpolation::Cosine => 2,
+            Interpolation::Quintic => 3,
+            Interpolation::Custom(_) => {
+                return Err(ConfigError::Serialize(
+                    "custom interpolation kernels can not be encoded".to_string(),
+                ))
+            }
+        });
+        let (mode, parameter) = match self.mode {
+            FractalMode::Fbm => (0, 0.0),
+            FractalMode::HybridMultifractal { offset } => (1, offset),
+            FractalMode::SwissTurbulence { warp } => (2, warp),
+        };
+        out.push(mode);
+        out.extend_from_slice(&parameter.to_le_bytes());
+        out.extend_from_slice(&self.exponent.to_le_bytes());
+        let transform = self.transform.unwrap_or_default();
+        out.push(self.transform.is_some() as u8);
+        let m = transform.matrix;
+        for value in [
+            m[0][0],
+            m[0][1],
+            m[1][0],
+            m[1][1],
+            transform.translation.0,
+            transform.translation.1,
+        ]
+        .iter()
+        {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        out.push(self.normalized as u8);
+        debug_assert_eq!(out.len(), Self::ENCODED_LEN);
+        Ok(out)
+    }
+
+    /// Decode and validate a configuration written by [`PerlinConfig::to_bytes`]
+    ///
+    /// Extra bytes after the encoding are ignored. Encodings of the first format version, written before the
+    /// `normalized` flag existed, are still accepted and decode with it turned off.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ConfigError> {
+        if bytes.len() < 4 {
+            return Err(ConfigError::Parse("encoding is too short".to_string()));
+        }
+        if bytes[..3] != MAGIC {
+            return Err(ConfigError::Parse("missing P2D header".to_string()));
+        }
+        let len = match bytes[3] {
+            1 => V1_ENCODED_LEN,
+            FORMAT_VERSION => Self::ENCODED_LEN,
+            v => return Err(ConfigError::Parse(format!("unsupported format version {}", v))),
+        };
+        if bytes.len() < len {
+            return Err(ConfigError::Parse("encoding is too short".to_string()));
+        }
+        let mut reader = ByteReader { bytes, position: 4 };
+        let octaves = usize::try_from(reader.i32())
+            .map_err(|_| ConfigError::Parse("octave count must not be negative".to_string()))?;
+        let amplitude = reader.f64();
+        let frequency = (reader.f64(), reader.f64());
+        let persistence = reader.f64();
+        let lacunarity = reader.f64();
+        let scale = (reader.f64(), reader.f64());
+        let bias = reader.f64();
+        let seed = reader.i32();
+        let version = match reader.u8() {
+            1 => AlgorithmVersion::V1,
+            2 => AlgorithmVersion::V2,
+            3 => AlgorithmVersion::V3,
+            4 => AlgorithmVersion::ImprovedGradient,
+            v => return Err(ConfigError::Parse(format!("unknown algorithm version {}", v))),
+        };
+        let interpolation = match reader.u8() {
+            0 => Interpolation::Cubic,
+            1 => Interpolation::Linear,
+            2 => Interpolation::Cosine,
+            3 => Interpolation::Quintic,
+            v => return Err(ConfigError::Parse(format!("unknown interpolation {}", v))),
+        };
+        let mode = reader.u8();
+        let parameter = reader.f64();
+        let mode = match mode {
+            0 => FractalMode::Fbm,
+            1 => FractalMode::HybridMultifractal { offset: parameter },
+            2 => FractalMode::SwissTurbulence { warp: parameter },
+            v => return Err(ConfigError::Parse(format!("unknown fractal mode {}", v))),
+        };
+        let exponent = reader.f64();
+        let has_transform = reader.u8() != 0;
+        let matrix = [[reader.f64(), reader.f64()], [reader.f64(), reader.f64()]];
+        let translation = (reader.f64(), reader.f64());
+        let normalized = len > V1_ENCODED_LEN && reader.u8() != 0;
+
+        let config = Self {
+            octaves,
+            amplitude,
+            frequency,
+            persistence,
+            lacunarity,
+            scale,
+            bias,
+            seed,
+            version,
+            interpolation,
+            mode,
+            exponent,
+            transform: if has_transform {
+                Some(Affine::new(matrix, translation))
+            } else {
+                None
+            },
+            normalized,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// Reads little-endian values from a buffer whose length has already been checked
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl ByteReader<'_> {
+    fn take<const N: usize>(&mut self) -> [u8; N] {
+        let mut out = [0; N];
+        out.copy_from_slice(&self.bytes[self.position..self.position + N]);
+        self.position += N;
+        out
+    }
+    fn u8(&mut self) -> u8 {
+        self.take::<1>()[0]
+    }
+    fn i32(&mut self) -> i32 {
+        i32::from_le_bytes(self.take())
+    }
+    fn f64(&mut self) -> f64 {
+        f64::from_le_bytes(self.take())
+    }
+}
+
+impl PerlinNoise2D {
+    /// Encode the generator state with [`PerlinConfig::to_bytes`]
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ConfigError> {
+        PerlinConfig::from(self).to_bytes()
+    }
+
+    /// Create a generator from bytes written by [`PerlinNoise2D::to_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ConfigError> {
+        PerlinConfig::from_bytes(bytes).map(|config| config.build())
+    }
+}
+
+impl<H> From<&PerlinNoise2D<H>> for PerlinConfig {
+    fn from(perlin: &PerlinNoise2D<H>) -> Self {
+        Self {
+            octaves: perlin.get_octaves().max(0) as usize,
+            amplitude: perlin.get_amplitude(),
+            frequency: perlin.get_axis_frequency(),
+            persistence: perlin.get_persistence(),
+            lacunarity: perlin.get_lacunarity(),
+            scale: perlin.get_scale(),
+            bias: perlin.get_bias(),
+            seed: perlin.get_seed(),
+            version: perlin.get_version(),
+            interpolation: perlin.get_interpolation(),
+            mode: perlin.get_mode(),
+            exponent: perlin.get_exponent(),
+            transform: perlin.get_transform(),
+            normalized: perlin.get_normalized(),
+        }
+    }
+}
+
+impl From<PerlinConfig> for PerlinNoise2D {
+    fn from(config: PerlinConfig) -> Self {
+        config.build()
+    }
+}
+
+#[cfg(feature = "serde")]
+fn default_exponent() -> f64 {
+    1.0
+}
+
+/// Reads a per-axis pair from either a single number or a two element array
+#[cfg(feature = "serde")]
+mod axis_pair {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum AxisPair {
+        Both(f64),
+        PerAxis(f64, f64),
+    }
+
+    pub fn serialize<S: Serializer>(value: &(f64, f64), serializer: S) -> Result<S::Ok, S::Error> {
+        if value.0 == value.1 {
+            AxisPair::Both(value.0).serialize(serializer)
+        } else {
+            AxisPair::PerAxis(value.0, value.1).serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<(f64, f64), D::Error> {
+        Ok(match AxisPair::deserialize(deserializer)? {
+            AxisPair::Both(value) => (value, value),
+            AxisPair::PerAxis(x, y) => (x, y),
+        })
+    }
+}
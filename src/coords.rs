@@ -0,0 +1,145 @@
+//! Conversions between positions in the noise plane and grid indices.
+//!
+//! Every grid of the crate places the sample in column `i` and row `j` at `min + (i * step_x, j * step_y)` of its
+//! region: samples sit on the minimum corner of their cell, not in its center. A [`CoordMap`] captures that layout
+//! once so features computed on a grid can be placed in the world, and world positions looked up in a grid, without
+//! redoing the arithmetic by hand. [`Region::coord_map`] and [`crate::NoiseMap::get_coord_map`] return the layout used by
+//! the sampling functions.
+//!
+//! ```
+//! use perlin2d::{CoordMap, NoiseSource, PerlinNoise2D, Region};
+//!
+//! let perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 4);
+//! let map = perlin.generate_map(Region::new((100.0, 50.0), (164.0, 82.0)), (32, 16));
+//! let coords = map.get_coord_map();
+//!
+//! assert_eq!(coords.cell_to_world(3, 2), map.get_position(3, 2));
+//! assert_eq!(coords.world_to_cell(107.9, 55.1), (3, 2));
+//! assert_eq!(coords.nearest_cell(107.9, 55.1), (4, 3));
+//! assert_eq!(coords.cell_center(3, 2), (107.0, 55.0));
+//! ```
+
+use crate::Region;
+
+/// Direction in which the rows of a grid advance along `y`
+///
+/// * `Ascending` - Row `j + 1` lies at a larger `y` than row `j`. This is the layout of every [`crate::NoiseMap`].
+/// * `Descending` - Row `j + 1` lies at a smaller `y` than row `j`, as in images whose first row is the top edge of a
+///   `y` up world.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RowOrder {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+/// Layout of a grid in the noise plane
+///
+/// Member variables:
+///
+/// * `origin` - The position of the sample in column `0` and row `0`.
+/// * `cell_size` - The distance between neighboring samples along `x` and `y`.
+/// * `row_order` - The direction in which rows advance along `y`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoordMap {
+    origin: (f64, f64),
+    cell_size: (f64, f64),
+    row_order: RowOrder,
+}
+
+impl CoordMap {
+    /// Create and return a new CoordMap object with ascending rows
+    pub fn new(origin: (f64, f64), cell_size: (f64, f64)) -> Self {
+        Self {
+            origin,
+            cell_size,
+            row_order: RowOrder::Ascending,
+        }
+    }
+
+    /// The layout of a region sampled at the given `(width, height)` resolution
+    pub fn from_region(region: Region, resolution: (usize, usize)) -> Self {
+        Self::new(region.min, region.step(resolution))
+    }
+
+    /// Getter function for origin
+    pub fn get_origin(&self) -> (f64, f64) {
+        self.origin
+    }
+    /// Getter function for cell_size
+    pub fn get_cell_size(&self) -> (f64, f64) {
+        self.cell_size
+    }
+    /// Getter function for row_order
+    pub fn get_row_order(&self) -> RowOrder {
+        self.row_order
+    }
+
+    /// Setter function for origin
+    pub fn set_origin(&mut self, origin: (f64, f64)) {
+        self.origin = origin;
+    }
+    /// Setter function for cell_size
+    pub fn set_cell_size(&mut self, cell_size: (f64, f64)) {
+        self.cell_size = cell_size;
+    }
+    /// Setter function for row_order
+    pub fn set_row_order(&mut self, row_order: RowOrder) {
+        self.row_order = row_order;
+    }
+
+    /// Position of the sample in column `i` and row `j`
+    #[inline]
+    pub fn cell_to_world(&self, i: isize, j: isize) -> (f64, f64) {
+        self.grid_to_world(i as f64, j as f64)
+    }
+
+    /// Position halfway between the sample in column `i` and row `j` and the sample diagonally after it
+    pub fn cell_center(&self, i: isize, j: isize) -> (f64, f64) {
+        self.grid_to_world(i as f64 + 0.5, j as f64 + 0.5)
+    }
+
+    /// Position of fractional grid coordinates, the inverse of [`CoordMap::world_to_grid`]
+    #[inline]
+    pub fn grid_to_world(&self, u: f64, v: f64) -> (f64, f64) {
+        let (step_x, step_y) = self.signed_step();
+        (self.origin.0 + u * step_x, self.origin.1 + v * step_y)
+    }
+
+    /// Fractional grid coordinates of a position, `(column, row)` with samples at whole numbers
+    #[inline]
+    pub fn world_to_grid(&self, x: f64, y: f64) -> (f64, f64) {
+        let (step_x, step_y) = self.signed_step();
+        ((x - self.origin.0) / step_x, (y - self.origin.1) / step_y)
+    }
+
+    /// `(column, row)` of the cell containing a position, whose sample is the one at or before it along both axes
+    pub fn world_to_cell(&self, x: f64, y: f64) -> (isize, isize) {
+        let (u, v) = self.world_to_grid(x, y);
+        (u.floor() as isize, v.floor() as isize)
+    }
+
+    /// `(column, row)` of the sample nearest to a position
+    pub fn nearest_cell(&self, x: f64, y: f64) -> (isize, isize) {
+        let (u, v) = self.world_to_grid(x, y);
+        (u.round() as isize, v.round() as isize)
+    }
+
+    /// The area between the sample in column `i` and row `j` and the sample diagonally after it
+    pub fn cell_region(&self, i: isize, j: isize) -> Region {
+        let (x0, y0) = self.cell_to_world(i, j);
+        let (x1, y1) = self.cell_to_world(i + 1, j + 1);
+        Region::new((x0.min(x1), y0.min(y1)), (x0.max(x1), y0.max(y1)))
+    }
+
+    /// Distance between neighboring samples, negative along `y` for descending rows
+    #[inline]
+    fn signed_step(&self) -> (f64, f64) {
+        match self.row_order {
+            RowOrder::Ascending => self.cell_size,
+            RowOrder::Descending => (self.cell_size.0, -self.cell_size.1),
+        }
+    }
+}
@@ -0,0 +1,310 @@
+//! Connected cave and dungeon layouts for tile based games.
+//!
+//! [`generate_cave`] thresholds a noise source into walls and floor, smooths the result with a cellular automaton
+//! into rounded caverns, and repairs connectivity so that every floor tile can be reached from every other one.
+//! Small pockets are filled in and the remaining caverns are joined by the shortest tunnels through the rock. The
+//! result is a [`TileGrid`] that only depends on the source, region, resolution and options, so a seed always gives
+//! the same level.
+//!
+//! ```
+//! use perlin2d::dungeon::{generate_cave, CaveOptions, Tile};
+//! use perlin2d::{PerlinNoise2D, Region};
+//!
+//! let perlin = PerlinNoise2D::new(3, 1.0, 0.5, 0.5, 2.0, (3.0, 3.0), 0.0, 7);
+//! let cave = generate_cave(&perlin, Region::new((0.0, 0.0), (80.0, 50.0)), (80, 50), &CaveOptions::new());
+//!
+//! assert!(cave.floor_count() > 0);
+//! assert_eq!(cave.region_count(), 1);
+//! // the level is enclosed by walls
+//! assert!((0..80).all(|i| cave.get_tile(i, 0) == Tile::Wall && cave.get_tile(i, 49) == Tile::Wall));
+//! ```
+
+use crate::{NoiseMap, NoiseSource, Region};
+use std::collections::VecDeque;
+
+/// A cell of a [`TileGrid`]
+///
+/// * `Wall` - Solid rock.
+/// * `Floor` - Open space that can be walked on.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Tile {
+    Wall,
+    Floor,
+}
+
+/// Settings of [`generate_cave`]
+///
+/// Member variables:
+///
+/// * `threshold` - Samples below it start out as floor, the others as wall. Defaults to `0.0`.
+/// * `smoothing` - Rounds of the cellular automaton. Every round turns a tile into wall when at least five of the
+///   nine tiles around and including it are walls, and into floor otherwise. Defaults to `4`.
+/// * `min_region` - Floor regions with fewer tiles are filled in rather than connected. The largest region is always
+///   kept. Defaults to `8`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CaveOptions {
+    threshold: f64,
+    smoothing: u32,
+    min_region: usize,
+}
+
+impl Default for CaveOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CaveOptions {
+    /// Create and return a new CaveOptions object with the default settings
+    pub fn new() -> Self {
+        Self {
+            threshold: 0.0,
+            smoothing: 4,
+            min_region: 8,
+        }
+    }
+
+    /// Getter function for threshold
+    pub fn get_threshold(&self) -> f64 {
+        self.threshold
+    }
+    /// Getter function for smoothing
+    pub fn get_smoothing(&self) -> u32 {
+        self.smoothing
+    }
+    /// Getter function for min_region
+    pub fn get_min_region(&self) -> usize {
+        self.min_region
+    }
+
+    /// Setter function for threshold
+    pub fn set_threshold(&mut self, threshold: f64) {
+        self.threshold = threshold;
+    }
+    /// Setter function for smoothing
+    pub fn set_smoothing(&mut self, smoothing: u32) {
+        self.smoothing = smoothing;
+    }
+    /// Setter function for min_region
+    pub fn set_min_region(&mut self, min_region: usize) {
+        self.min_region = min_region;
+    }
+}
+
+/// A grid of tiles, returned by [`generate_cave`]
+///
+/// Member variables:
+///
+/// * `width` - Number of columns.
+/// * `height` - Number of rows.
+/// * `tiles` - Row-major tiles.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TileGrid {
+    width: usize,
+    height: usize,
+    tiles: Vec<Tile>,
+}
+
+impl TileGrid {
+    /// Getter function for width
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+    /// Getter function for height
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+    /// Getter function for the row-major tiles
+    pub fn get_tiles(&self) -> &[Tile] {
+        &self.tiles
+    }
+    /// Getter function for the tile in column `i` and row `j`
+    pub fn get_tile(&self, i: usize, j: usize) -> Tile {
+        self.tiles[j * self.width + i]
+    }
+    /// Whether the tile in column `i` and row `j` is floor
+    pub fn is_floor(&self, i: usize, j: usize) -> bool {
+        self.get_tile(i, j) == Tile::Floor
+    }
+    /// Number of floor tiles
+    pub fn floor_count(&self) -> usize {
+        self.tiles.iter().filter(|&&tile| tile == Tile::Floor).count()
+    }
+    /// Number of separate floor regions, connected through the four direct neighbors of every tile
+    pub fn region_count(&self) -> usize {
+        label_regions(&self.tiles, self.width, self.height).1.len()
+    }
+}
+
+/// Generates a connected cave layout from a source over a region, with one tile per sample at the given
+/// `(width, height)` resolution
+///
+/// See [`map_cave`].
+pub fn generate_cave<S: NoiseSource + ?Sized>(
+    source: &S,
+    region: Region,
+    resolution: (usize, usize),
+    options: &CaveOptions,
+) -> TileGrid {
+    map_cave(&source.generate_map(region, resolution), options)
+}
+
+/// Generates a connected cave layout from the values of a map
+///
+/// Tiles start out as floor where the value lies below the threshold and are then smoothed. The outermost ring of
+/// tiles is always wall, so the level is enclosed. Floor regions smaller than `min_region` are filled in, and every
+/// remaining region is joined to the largest one by carving the shortest tunnel of floor tiles through the walls
+/// between them, so all floor tiles end up connected through their four direct neighbors. Maps without any floor
+/// after smoothing give a grid of walls.
+pub fn map_cave(map: &NoiseMap, options: &CaveOptions) -> TileGrid {
+    let (w, h) = map.get_resolution();
+    let border = |index: usize| {
+        let (i, j) = (index % w, index / w);
+        i == 0 || j == 0 || i + 1 == w || j + 1 == h
+    };
+    let mut tiles: Vec<Tile> = map
+        .get_values()
+        .iter()
+        .enumerate()
+        .map(|(index, &value)| {
+            if value < options.threshold && !border(index) {
+                Tile::Floor
+            } else {
+                Tile::Wall
+            }
+        })
+        .collect();
+
+    for _ in 0..options.smoothing {
+        let previous = tiles.clone();
+        for (index, tile) in tiles.iter_mut().enumerate() {
+            if border(index) {
+                continue;
+            }
+            let (i, j) = (index % w, index / w);
+            let mut walls = 0;
+            for nj in j - 1..=j + 1 {
+                for ni in i - 1..=i + 1 {
+                    if previous[nj * w + ni] == Tile::Wall {
+                        walls += 1;
+                    }
+                }
+            }
+            *tile = if walls >= 5 { Tile::Wall } else { Tile::Floor };
+        }
+    }
+
+    // fill in small pockets, keeping the largest region
+    let (labels, sizes) = label_regions(&tiles, w, h);
+    let largest = (0..sizes.len()).max_by(|&a, &b| sizes[a].cmp(&sizes[b]).then(b.cmp(&a)));
+    let largest = match largest {
+        Some(largest) => largest,
+        None => {
+            return TileGrid {
+                width: w,
+                height: h,
+                tiles,
+            }
+        }
+    };
+    for (tile, &label) in tiles.iter_mut().zip(labels.iter()) {
+        if label != NO_REGION && label != largest && sizes[label] < options.min_region {
+            *tile = Tile::Wall;
+        }
+    }
+
+    // grow the connected set from the largest region, tunneling to the nearest unconnected floor each time
+    let mut connected: Vec<bool> = labels.iter().map(|&label| label == largest).collect();
+    loop {
+        let mut parent = vec![usize::MAX; w * h];
+        let mut queue: VecDeque<usize> = (0..w * h).filter(|&index| connected[index]).collect();
+        for &index in queue.iter() {
+            parent[index] = index;
+        }
+        let mut reached = None;
+        while let Some(index) = queue.pop_front() {
+            if tiles[index] == Tile::Floor && !connected[index] {
+                reached = Some(index);
+                break;
+            }
+            for neighbor in neighbors(index, w, h) {
+                if parent[neighbor] == usize::MAX && !border(neighbor) {
+                    parent[neighbor] = index;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        let reached = match reached {
+            Some(reached) => reached,
+            None => break,
+        };
+        // carve the tunnel back to the connected set
+        let mut index = parent[reached];
+        while !connected[index] {
+            tiles[index] = Tile::Floor;
+            connected[index] = true;
+            index = parent[index];
+        }
+        // and take in the region it reached
+        let mut stack = vec![reached];
+        connected[reached] = true;
+        while let Some(index) = stack.pop() {
+            for neighbor in neighbors(index, w, h) {
+                if tiles[neighbor] == Tile::Floor && !connected[neighbor] {
+                    connected[neighbor] = true;
+                    stack.push(neighbor);
+                }
+            }
+        }
+    }
+
+    TileGrid {
+        width: w,
+        height: h,
+        tiles,
+    }
+}
+
+/// Label of wall tiles in [`label_regions`]
+const NO_REGION: usize = usize::MAX;
+
+/// Flood fills the floor regions of a grid, returning the region of every tile in row-major order of their first
+/// tile and the size of every region
+fn label_regions(tiles: &[Tile], w: usize, h: usize) -> (Vec<usize>, Vec<usize>) {
+    let mut labels = vec![NO_REGION; tiles.len()];
+    let mut sizes = Vec::new();
+    let mut stack = Vec::new();
+    for start in 0..tiles.len() {
+        if tiles[start] != Tile::Floor || labels[start] != NO_REGION {
+            continue;
+        }
+        let label = sizes.len();
+        let mut size = 0;
+        labels[start] = label;
+        stack.push(start);
+        while let Some(index) = stack.pop() {
+            size += 1;
+            for neighbor in neighbors(index, w, h) {
+                if tiles[neighbor] == Tile::Floor && labels[neighbor] == NO_REGION {
+                    labels[neighbor] = label;
+                    stack.push(neighbor);
+                }
+            }
+        }
+        sizes.push(size);
+    }
+    (labels, sizes)
+}
+
+/// The four direct neighbors of a tile that lie inside the grid
+fn neighbors(index: usize, w: usize, h: usize) -> impl Iterator<Item = usize> {
+    let (i, j) = (index % w, index / w);
+    let left = if i > 0 { Some(index - 1) } else { None };
+    let right = if i + 1 < w { Some(index + 1) } else { None };
+    let up = if j > 0 { Some(index - w) } else { None };
+    let down = if j + 1 < h { Some(index + w) } else { None };
+    left.into_iter().chain(right).chain(up).chain(down)
+}
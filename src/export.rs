@@ -0,0 +1,162 @@
+//! Plain data export of noise maps for analysis tools.
+//!
+//! [`write_csv`] writes one line of comma separated values per map row, and [`write_npy`] writes a NumPy `.npy`
+//! file holding a `(height, width)` array of little-endian `f64`, which `numpy.load` reads directly. Both keep every
+//! value exact: CSV values are printed with the shortest representation that parses back to the same `f64`.
+//!
+//! [`write_xyz`] and [`write_ply`] write a map as a point cloud instead, one point per sample at its position in the
+//! noise plane with the value as height, optionally colored by a [`Gradient`]. Point cloud tools such as CloudCompare
+//! and MeshLab open both formats. Samples that are not finite are left out, so setting cells to NaN exports only part
+//! of a map.
+//!
+//! ```
+//! use perlin2d::export::{write_csv, write_npy};
+//! use perlin2d::{NoiseSource, PerlinNoise2D, Region};
+//!
+//! let perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 101);
+//! let map = perlin.generate_map(Region::new((0.0, 0.0), (64.0, 32.0)), (4, 2));
+//!
+//! let mut csv = Vec::new();
+//! write_csv(&map, &mut csv).unwrap();
+//! let first: f64 = String::from_utf8(csv).unwrap().split(',').next().unwrap().parse().unwrap();
+//! assert_eq!(first, map.get_value(0, 0));
+//!
+//! let mut npy = Vec::new();
+//! write_npy(&map, &mut npy).unwrap();
+//! assert_eq!(&npy[..6], b"\x93NUMPY");
+//! assert_eq!(npy.len() % 64, (4 * 2 * 8) % 64);
+//! ```
+
+use crate::gradient::Gradient;
+use crate::NoiseMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Alignment of the start of the array data required by NumPy
+const NPY_ALIGNMENT: usize = 64;
+
+/// Writes a map as comma separated values, one line per row starting with row `0`
+pub fn write_csv<W: Write>(map: &NoiseMap, mut writer: W) -> io::Result<()> {
+    let width = map.get_width();
+    if width == 0 {
+        return Ok(());
+    }
+    for row in map.get_values().chunks(width) {
+        for (i, value) in row.iter().enumerate() {
+            if i > 0 {
+                writer.write_all(b",")?;
+            }
+            write!(writer, "{}", value)?;
+        }
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()
+}
+
+/// Saves a map as a CSV file, see [`write_csv`]
+pub fn save_csv<P: AsRef<Path>>(map: &NoiseMap, path: P) -> io::Result<()> {
+    write_csv(map, BufWriter::new(File::create(path)?))
+}
+
+/// Writes a map in the NumPy `.npy` format, version 1.0, as a C ordered `(height, width)` array of `<f8`
+pub fn write_npy<W: Write>(map: &NoiseMap, mut writer: W) -> io::Result<()> {
+    let (w, h) = map.get_resolution();
+    let mut header = format!("{{'descr': '<f8', 'fortran_order': False, 'shape': ({}, {}), }}", h, w);
+    // magic, version and header length take 10 bytes, the header is padded with spaces and ends with a newline
+    let unpadded = 10 + header.len() + 1;
+    let padding = (NPY_ALIGNMENT - unpadded % NPY_ALIGNMENT) % NPY_ALIGNMENT;
+    header.push_str(&" ".repeat(padding));
+    header.push('\n');
+
+    writer.write_all(b"\x93NUMPY\x01\x00")?;
+    writer.write_all(&(header.len() as u16).to_le_bytes())?;
+    writer.write_all(header.as_bytes())?;
+    for value in map.get_values() {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    writer.flush()
+}
+
+/// Saves a map as a `.npy` file, see [`write_npy`]
+pub fn save_npy<P: AsRef<Path>>(map: &NoiseMap, path: P) -> io::Result<()> {
+    write_npy(map, BufWriter::new(File::create(path)?))
+}
+
+/// Writes a map as an XYZ point cloud, one line of `x y z` per finite sample in row-major order
+///
+/// With a gradient every line continues with the `r g b` color of the value as integers from `0` to `255`.
+pub fn write_xyz<W: Write>(map: &NoiseMap, gradient: Option<&Gradient>, mut writer: W) -> io::Result<()> {
+    for (x, y, z) in points(map) {
+        write!(writer, "{} {} {}", x, y, z)?;
+        if let Some(gradient) = gradient {
+            let [r, g, b] = gradient.get_color(z);
+            write!(writer, " {} {} {}", r, g, b)?;
+        }
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()
+}
+
+/// Saves a map as an XYZ point cloud file, see [`write_xyz`]
+pub fn save_xyz<P: AsRef<Path>>(map: &NoiseMap, gradient: Option<&Gradient>, path: P) -> io::Result<()> {
+    write_xyz(map, gradient, BufWriter::new(File::create(path)?))
+}
+
+/// Writes a map as a binary little-endian PLY point cloud with one vertex per finite sample in row-major order
+///
+/// Vertices have `double` properties `x`, `y` and `z`, followed by `uchar` properties `red`, `green` and `blue` when
+/// a gradient is given.
+///
+/// ```
+/// use perlin2d::export::write_ply;
+/// use perlin2d::gradient::Gradient;
+/// use perlin2d::{NoiseSource, PerlinNoise2D, Region};
+///
+/// let perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 101);
+/// let mut map = perlin.generate_map(Region::new((0.0, 0.0), (64.0, 32.0)), (4, 2));
+/// // leave out one sample
+/// map.set_value(0, 0, f64::NAN);
+///
+/// let mut ply = Vec::new();
+/// write_ply(&map, Some(&Gradient::terrain()), &mut ply).unwrap();
+/// let header = String::from_utf8_lossy(&ply);
+/// assert!(header.starts_with("ply\nformat binary_little_endian 1.0\nelement vertex 7\n"));
+/// ```
+pub fn write_ply<W: Write>(map: &NoiseMap, gradient: Option<&Gradient>, mut writer: W) -> io::Result<()> {
+    let count = points(map).count();
+    writer.write_all(b"ply\nformat binary_little_endian 1.0\n")?;
+    writeln!(writer, "element vertex {}", count)?;
+    writer.write_all(b"property double x\nproperty double y\nproperty double z\n")?;
+    if gradient.is_some() {
+        writer.write_all(b"property uchar red\nproperty uchar green\nproperty uchar blue\n")?;
+    }
+    writer.write_all(b"end_header\n")?;
+    for (x, y, z) in points(map) {
+        for value in [x, y, z].iter() {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        if let Some(gradient) = gradient {
+            writer.write_all(&gradient.get_color(z))?;
+        }
+    }
+    writer.flush()
+}
+
+/// Saves a map as a binary PLY point cloud file, see [`write_ply`]
+pub fn save_ply<P: AsRef<Path>>(map: &NoiseMap, gradient: Option<&Gradient>, path: P) -> io::Result<()> {
+    write_ply(map, gradient, BufWriter::new(File::create(path)?))
+}
+
+/// Positions and values of the finite samples of a map in row-major order
+fn points(map: &NoiseMap) -> impl Iterator<Item = (f64, f64, f64)> + '_ {
+    let width = map.get_width();
+    map.get_values()
+        .iter()
+        .enumerate()
+        .filter(|(_, value)| value.is_finite())
+        .map(move |(index, &value)| {
+            let (x, y) = map.get_position(index % width, index / width);
+            (x, y, value)
+        })
+}
@@ -0,0 +1,509 @@
+//! A small expression language for writing noise pipelines as strings.
+//!
+//! An expression is compiled into a [`Graph`], so pipelines can be kept in configuration files and tweaked without
+//! recompiling:
+//!
+//! ```
+//! use perlin2d::graph::{Graph, Node};
+//! use perlin2d::{NoiseSource, PerlinConfig, PerlinNoise2D};
+//!
+//! let compiled: Graph = "clamp(0.6*perlin(seed=1,oct=6,scale=50) + 0.4*ridged(seed=2), -0.5, 1)".parse().unwrap();
+//!
+//! // the same pipeline built by hand
+//! let first = PerlinNoise2D::new(6, 1.0, 1.0, 0.5, 2.0, (50.0, 50.0), 0.0, 1);
+//! let second = PerlinNoise2D::new(6, 1.0, 1.0, 0.5, 2.0, (1.0, 1.0), 0.0, 2);
+//! let mut graph = Graph::new();
+//! let first = graph.add(Node::Perlin(PerlinConfig::from(&first)));
+//! let second = graph.add(Node::Perlin(PerlinConfig::from(&second)));
+//! let abs = graph.add(Node::Abs(second));
+//! let ridged = graph.add(Node::ScaleBias { input: abs, scale: -1.0, bias: 1.0 });
+//! let blend = graph.add(Node::Blend(vec![(first, 0.6), (ridged, 0.4)]));
+//! let output = graph.add(Node::Clamp { input: blend, min: -0.5, max: 1.0 });
+//! graph.set_output(output);
+//!
+//! for &(x, y) in [(0.0, 0.0), (5.0, 10.0), (-37.25, 81.5), (1234.5, -987.25)].iter() {
+//!     assert!((compiled.get_noise(x, y) - graph.get_noise(x, y)).abs() < 1e-12);
+//! }
+//! ```
+//!
+//! Expressions are built from numbers, `+`, `-`, `*`, `/`, parentheses and the following functions:
+//!
+//! * `perlin(..)` - Perlin noise. Takes named arguments, all optional: `seed` (0), `oct`/`octaves` (6),
+//!   `amp`/`amplitude` (1), `freq`/`frequency` (1, both axes), `freq_x`, `freq_y`, `pers`/`persistence` (0.5),
+//!   `lac`/`lacunarity` (2), `scale` (1, both axes), `scale_x`, `scale_y`, `bias` (0), `exp`/`exponent` (1) and
+//!   `norm`/`normalized` (0, any other integer divides the amplitude by the sum of the octave gains).
+//! * `ridged(..)` - `1 - abs(perlin(..))`, with the same arguments as `perlin`.
+//! * `billow(..)` - `2 * abs(perlin(..)) - 1`, with the same arguments as `perlin`.
+//! * `hybrid(..)` - Hybrid multifractal noise, see [`crate::FractalMode`]. Takes the arguments of `perlin` and
+//!   `offset` (0.7).
+//! * `swiss(..)` - Swiss turbulence, see [`crate::FractalMode`]. Takes the arguments of `perlin` and `warp` (0.15).
+//! * `abs(a)`, `clamp(a, min, max)`, `min(a, b, ..)` and `max(a, b, ..)`.
+//! * `mask(control, source)` or `mask(control, source, lower, upper, falloff)`, see [`crate::Masked`].
+//!
+//! Division is only supported by constants.
+
+use crate::graph::{Graph, Node, NodeId};
+use crate::{FractalMode, PerlinConfig};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+/// Error returned when an expression can not be compiled
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    /// Byte offset in the expression where the error was found.
+    pub position: usize,
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at position {}: {}", self.position, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Compile an expression into a graph whose output node is the value of the expression
+///
+/// Errors point at the byte offset of the token or call where the problem was found.
+///
+/// ```
+/// use perlin2d::expr::compile;
+///
+/// let fails = |source: &str| {
+///     let e = compile(source).unwrap_err();
+///     (e.position, e.message)
+/// };
+/// assert_eq!(fails("mask(clamp(perlin(), 1, 0), perlin())"), (5, "`clamp` expects `min` to be at most `max`".into()));
+/// assert_eq!(fails("perlin(seed=1.5)"), (7, "`seed` must be an integer".into()));
+/// assert_eq!(fails("perlin(bogus=1)"), (7, "unknown argument `bogus`".into()));
+/// assert_eq!(fails("foo(1)"), (0, "unknown function `foo`".into()));
+/// assert_eq!(fails("abs(1, 2)"), (0, "`abs` expects one argument".into()));
+/// assert_eq!(fails("1 / perlin()"), (2, "division is only supported by constants".into()));
+/// assert_eq!(fails("perlin() +"), (10, "unexpected end of expression".into()));
+/// assert_eq!(fails("perlin(seed=1) 2"), (15, "unexpected input after the end of the expression".into()));
+/// ```
+pub fn compile(source: &str) -> Result<Graph, ParseError> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        end: source.len(),
+        graph: Graph::new(),
+    };
+    let value = parser.expr()?;
+    if let Some(token) = parser.tokens.get(parser.pos) {
+        return Err(error(
+            token.position,
+            "unexpected input after the end of the expression",
+        ));
+    }
+    let output = parser.node(value);
+    parser.graph.set_output(output);
+    Ok(parser.graph)
+}
+
+impl FromStr for Graph {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        compile(s)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Kind {
+    Number(f64),
+    Ident(String),
+    LParen,
+    RParen,
+    Comma,
+    Equals,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+}
+
+#[derive(Clone, Debug)]
+struct Token {
+    kind: Kind,
+    position: usize,
+}
+
+fn error(position: usize, message: &str) -> ParseError {
+    ParseError {
+        position,
+        message: message.to_string(),
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, ParseError> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        let start = i;
+        let kind = match c {
+            b' ' | b'\t' | b'\n' | b'\r' => {
+                i += 1;
+                continue;
+            }
+            b'(' => Kind::LParen,
+            b')' => Kind::RParen,
+            b',' => Kind::Comma,
+            b'=' => Kind::Equals,
+            b'+' => Kind::Plus,
+            b'-' => Kind::Minus,
+            b'*' => Kind::Star,
+            b'/' => Kind::Slash,
+            b'0'..=b'9' | b'.' => {
+                while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'.') {
+                    i += 1;
+                }
+                // exponent, e.g. 1e-3
+                if i < bytes.len() && (bytes[i] == b'e' || bytes[i] == b'E') {
+                    i += 1;
+                    if i < bytes.len() && (bytes[i] == b'+' || bytes[i] == b'-') {
+                        i += 1;
+                    }
+                    while i < bytes.len() && bytes[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+                let text = &source[start..i];
+                let value = text.parse().map_err(|_| error(start, "invalid number"))?;
+                tokens.push(Token {
+                    kind: Kind::Number(value),
+                    position: start,
+                });
+                continue;
+            }
+            c if c.is_ascii_alphabetic() || c == b'_' => {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                tokens.push(Token {
+                    kind: Kind::Ident(source[start..i].to_string()),
+                    position: start,
+                });
+                continue;
+            }
+            _ => return Err(error(start, "unexpected character")),
+        };
+        tokens.push(Token { kind, position: start });
+        i += 1;
+    }
+    Ok(tokens)
+}
+
+/// Intermediate value, constants are folded instead of becoming nodes where possible
+#[derive(Copy, Clone)]
+enum Value {
+    Constant(f64),
+    Node(NodeId),
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    end: usize,
+    graph: Graph,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Kind> {
+        self.tokens.get(self.pos).map(|t| &t.kind)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens.get(self.pos).map(|t| t.position).unwrap_or(self.end)
+    }
+
+    fn expect(&mut self, kind: Kind, message: &str) -> Result<(), ParseError> {
+        if self.peek() == Some(&kind) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(error(self.position(), message))
+        }
+    }
+
+    fn node(&mut self, value: Value) -> NodeId {
+        match value {
+            Value::Constant(c) => self.graph.add(Node::Constant(c)),
+            Value::Node(id) => id,
+        }
+    }
+
+    fn scale_bias(&mut self, value: Value, scale: f64, bias: f64) -> Value {
+        match value {
+            Value::Constant(c) => Value::Constant(c * scale + bias),
+            Value::Node(input) => Value::Node(self.graph.add(Node::ScaleBias { input, scale, bias })),
+        }
+    }
+
+    fn expr(&mut self) -> Result<Value, ParseError> {
+        let mut lhs = self.term()?;
+        loop {
+            let negate = match self.peek() {
+                Some(Kind::Plus) => false,
+                Some(Kind::Minus) => true,
+                _ => return Ok(lhs),
+            };
+            self.pos += 1;
+            let mut rhs = self.term()?;
+            if negate {
+                rhs = self.scale_bias(rhs, -1.0, 0.0);
+            }
+            lhs = match (lhs, rhs) {
+                (Value::Constant(a), Value::Constant(b)) => Value::Constant(a + b),
+                (Value::Constant(c), v @ Value::Node(_)) | (v @ Value::Node(_), Value::Constant(c)) => {
+                    self.scale_bias(v, 1.0, c)
+                }
+                (Value::Node(a), Value::Node(b)) => Value::Node(self.graph.add(Node::Add(vec![a, b]))),
+            };
+        }
+    }
+
+    fn term(&mut self) -> Result<Value, ParseError> {
+        let mut lhs = self.unary()?;
+        loop {
+            let divide = match self.peek() {
+                Some(Kind::Star) => false,
+                Some(Kind::Slash) => true,
+                _ => return Ok(lhs),
+            };
+            let position = self.position();
+            self.pos += 1;
+            let rhs = self.unary()?;
+            lhs = match (lhs, rhs, divide) {
+                (_, Value::Node(_), true) => return Err(error(position, "division is only supported by constants")),
+                (_, Value::Constant(0.0), true) => return Err(error(position, "division by zero")),
+                (v, Value::Constant(c), true) => self.scale_bias(v, 1.0 / c, 0.0),
+                (Value::Constant(a), Value::Constant(b), false) => Value::Constant(a * b),
+                (Value::Constant(c), v @ Value::Node(_), false) | (v @ Value::Node(_), Value::Constant(c), false) => {
+                    self.scale_bias(v, c, 0.0)
+                }
+                (Value::Node(a), Value::Node(b), false) => Value::Node(self.graph.add(Node::Multiply(vec![a, b]))),
+            };
+        }
+    }
+
+    fn unary(&mut self) -> Result<Value, ParseError> {
+        match self.peek() {
+            Some(Kind::Minus) => {
+                self.pos += 1;
+                let value = self.unary()?;
+                Ok(self.scale_bias(value, -1.0, 0.0))
+            }
+            Some(Kind::Plus) => {
+                self.pos += 1;
+                self.unary()
+            }
+            _ => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Value, ParseError> {
+        let position = self.position();
+        match self.peek().cloned() {
+            Some(Kind::Number(value)) => {
+                self.pos += 1;
+                Ok(Value::Constant(value))
+            }
+            Some(Kind::LParen) => {
+                self.pos += 1;
+                let value = self.expr()?;
+                self.expect(Kind::RParen, "expected `)`")?;
+                Ok(value)
+            }
+            Some(Kind::Ident(name)) => {
+                self.pos += 1;
+                self.expect(Kind::LParen, "expected `(` after function name")?;
+                self.call(&name, position)
+            }
+            Some(_) => Err(error(position, "expected a number, function or `(`")),
+            None => Err(error(position, "unexpected end of expression")),
+        }
+    }
+
+    fn call(&mut self, name: &str, position: usize) -> Result<Value, ParseError> {
+        match name {
+            "perlin" | "ridged" | "billow" | "hybrid" | "swiss" => {
+                let config = self.perlin_args(name)?;
+                let perlin = self.graph.add(Node::Perlin(config));
+                Ok(match name {
+                    "ridged" => {
+                        let abs = self.graph.add(Node::Abs(perlin));
+                        self.scale_bias(Value::Node(abs), -1.0, 1.0)
+                    }
+                    "billow" => {
+                        let abs = self.graph.add(Node::Abs(perlin));
+                        self.scale_bias(Value::Node(abs), 2.0, -1.0)
+                    }
+                    _ => Value::Node(perlin),
+                })
+            }
+            "abs" | "clamp" | "min" | "max" | "mask" => {
+                let args = self.args()?;
+                let count = args.len();
+                let arity_error = |expected: &str| error(position, &format!("`{}` expects {}", name, expected));
+                match name {
+                    "abs" if count == 1 => {
+                        let input = self.node(args[0]);
+                        Ok(Value::Node(self.graph.add(Node::Abs(input))))
+                    }
+                    "abs" => Err(arity_error("one argument")),
+                    "clamp" if count == 3 => {
+                        let (min, max) = match (args[1], args[2]) {
+                            (Value::Constant(min), Value::Constant(max)) => (min, max),
+                            _ => return Err(error(position, "`clamp` bounds must be constants")),
+                        };
+                        // written so that NaN bounds fail as well
+                        let ordered = min <= max;
+                        if !ordered {
+                            return Err(error(position, "`clamp` expects `min` to be at most `max`"));
+                        }
+                        let input = self.node(args[0]);
+                        Ok(Value::Node(self.graph.add(Node::Clamp { input, min, max })))
+                    }
+                    "clamp" => Err(arity_error("three arguments")),
+                    "min" | "max" if count >= 1 => {
+                        let inputs = args.into_iter().map(|a| self.node(a)).collect();
+                        let node = if name == "min" {
+                            Node::Min(inputs)
+                        } else {
+                            Node::Max(inputs)
+                        };
+                        Ok(Value::Node(self.graph.add(node)))
+                    }
+                    "min" | "max" => Err(arity_error("at least one argument")),
+                    _ if count == 2 || count == 5 => {
+                        let threshold = if count == 5 {
+                            match (args[2], args[3], args[4]) {
+                                (Value::Constant(lower), Value::Constant(upper), Value::Constant(falloff)) => {
+                                    Some((lower, upper, f64::max(falloff, 0.0)))
+                                }
+                                _ => return Err(error(position, "`mask` thresholds must be constants")),
+                            }
+                        } else {
+                            None
+                        };
+                        let control = self.node(args[0]);
+                        let source = self.node(args[1]);
+                        Ok(Value::Node(self.graph.add(Node::Mask {
+                            control,
+                            source,
+                            threshold,
+                        })))
+                    }
+                    _ => Err(arity_error("two or five arguments")),
+                }
+            }
+            _ => Err(error(position, &format!("unknown function `{}`", name))),
+        }
+    }
+
+    fn args(&mut self) -> Result<Vec<Value>, ParseError> {
+        let mut args = Vec::new();
+        if self.peek() == Some(&Kind::RParen) {
+            self.pos += 1;
+            return Ok(args);
+        }
+        loop {
+            args.push(self.expr()?);
+            match self.peek() {
+                Some(Kind::Comma) => self.pos += 1,
+                Some(Kind::RParen) => {
+                    self.pos += 1;
+                    return Ok(args);
+                }
+                _ => return Err(error(self.position(), "expected `,` or `)`")),
+            }
+        }
+    }
+
+    fn perlin_args(&mut self, function: &str) -> Result<PerlinConfig, ParseError> {
+        let mut config = PerlinConfig {
+            octaves: 6,
+            amplitude: 1.0,
+            frequency: (1.0, 1.0),
+            persistence: 0.5,
+            lacunarity: 2.0,
+            scale: (1.0, 1.0),
+            bias: 0.0,
+            seed: 0,
+            version: Default::default(),
+            interpolation: Default::default(),
+            mode: Default::default(),
+            exponent: 1.0,
+            transform: None,
+            normalized: false,
+        };
+        config.mode = match function {
+            "hybrid" => FractalMode::HybridMultifractal { offset: 0.7 },
+            "swiss" => FractalMode::SwissTurbulence { warp: 0.15 },
+            _ => FractalMode::Fbm,
+        };
+        if self.peek() == Some(&Kind::RParen) {
+            self.pos += 1;
+            return Ok(config);
+        }
+        loop {
+            let position = self.position();
+            let name = match self.peek().cloned() {
+                Some(Kind::Ident(name)) => name,
+                _ => return Err(error(position, "expected a named argument such as `seed=1`")),
+            };
+            self.pos += 1;
+            self.expect(Kind::Equals, "expected `=` after argument name")?;
+            let value = match self.expr()? {
+                Value::Constant(value) => value,
+                Value::Node(_) => return Err(error(position, "noise arguments must be constants")),
+            };
+            let integer = |value: f64| {
+                if value.fract() == 0.0 && value >= i32::MIN as f64 && value <= i32::MAX as f64 {
+                    Ok(value as i32)
+                } else {
+                    Err(error(position, &format!("`{}` must be an integer", name)))
+                }
+            };
+            match name.as_str() {
+                "seed" => config.seed = integer(value)?,
+                "oct" | "octaves" => {
+                    config.octaves = usize::try_from(integer(value)?)
+                        .map_err(|_| error(position, "`octaves` must not be negative"))?
+                }
+                "amp" | "amplitude" => config.amplitude = value,
+                "freq" | "frequency" => config.frequency = (value, value),
+                "freq_x" => config.frequency.0 = value,
+                "freq_y" => config.frequency.1 = value,
+                "pers" | "persistence" => config.persistence = value,
+                "lac" | "lacunarity" => config.lacunarity = value,
+                "scale" => config.scale = (value, value),
+                "scale_x" => config.scale.0 = value,
+                "scale_y" => config.scale.1 = value,
+                "bias" => config.bias = value,
+                "exp" | "exponent" => config.exponent = value,
+                "norm" | "normalized" => config.normalized = integer(value)? != 0,
+                "offset" if function == "hybrid" => config.mode = FractalMode::HybridMultifractal { offset: value },
+                "warp" if function == "swiss" => config.mode = FractalMode::SwissTurbulence { warp: value },
+                _ => return Err(error(position, &format!("unknown argument `{}`", name))),
+            }
+            match self.peek() {
+                Some(Kind::Comma) => self.pos += 1,
+                Some(Kind::RParen) => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => return Err(error(self.position(), "expected `,` or `)`")),
+            }
+        }
+        config.validate().map_err(|e| error(self.position(), &e.to_string()))?;
+        Ok(config)
+    }
+}
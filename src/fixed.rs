@@ -0,0 +1,270 @@
+//! Integer only noise generation for targets without a floating point unit, enabled by the `fixed` feature.
+//!
+//! [`FixedPerlin2D`] mirrors [`crate::PerlinNoise2D`] using Q16.16 fixed point numbers: an `i32` whose lower 16 bits
+//! hold the fractional part. Sampling only uses `i32` and `i64` arithmetic and this module only depends on `core`,
+//! so it runs at usable speed on microcontrollers such as the Cortex-M0. Build the crate with
+//! `default-features = false, features = ["fixed"]` to use it without the standard library. Output follows the floor based lattice of
+//! [`crate::AlgorithmVersion::V3`] with the default prime hash and cubic interpolation, within the precision of 16
+//! fractional bits.
+//!
+//! ```
+//! use perlin2d::fixed::{self, FixedPerlin2D};
+//! use perlin2d::{AlgorithmVersion, PerlinNoise2D};
+//!
+//! let perlin = FixedPerlin2D::new(
+//!     4,
+//!     fixed::ONE,
+//!     fixed::ONE / 2,
+//!     fixed::ONE / 2,
+//!     2 * fixed::ONE,
+//!     (16 * fixed::ONE, 16 * fixed::ONE),
+//!     0,
+//!     7,
+//! );
+//!
+//! // matches floating point V3 noise with the same parameters
+//! let mut float = PerlinNoise2D::new(4, 1.0, 0.5, 0.5, 2.0, (16.0, 16.0), 0.0, 7);
+//! float.set_version(AlgorithmVersion::V3);
+//! for j in 0..20 {
+//!     for i in 0..20 {
+//!         let (x, y) = (i as f64 * 3.7 - 30.0, j as f64 * 2.9 - 20.0);
+//!         let noise = perlin.get_noise(fixed::from_f64(x), fixed::from_f64(y));
+//!         assert!((fixed::to_f64(noise) - float.get_noise(x, y)).abs() < 2e-4);
+//!     }
+//! }
+//! ```
+
+/// A Q16.16 fixed point number
+pub type Fixed = i32;
+
+/// Number of fractional bits in a [`Fixed`]
+pub const FRAC_BITS: u32 = 16;
+
+/// `1.0` as a [`Fixed`]
+pub const ONE: Fixed = 1 << FRAC_BITS;
+
+/// Converts a float to the nearest [`Fixed`], rounding halfway cases away from zero and saturating at the
+/// representable range
+pub fn from_f64(value: f64) -> Fixed {
+    let scaled = value * ONE as f64;
+    // `f64::round` needs the standard library
+    let rounded = if scaled >= 0.0 { scaled + 0.5 } else { scaled - 0.5 };
+    rounded as Fixed
+}
+
+/// Converts a [`Fixed`] to a float
+pub fn to_f64(value: Fixed) -> f64 {
+    value as f64 / ONE as f64
+}
+
+#[inline]
+fn mul(a: Fixed, b: Fixed) -> Fixed {
+    ((a as i64 * b as i64) >> FRAC_BITS) as Fixed
+}
+
+/// Fixed point Perlin noise generator
+///
+/// The parameters have the same meaning as in [`crate::PerlinNoise2D`], expressed as [`Fixed`] numbers. Scale
+/// components must not be zero.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FixedPerlin2D {
+    octaves: u32,
+    amplitude: Fixed,
+    frequency: Fixed,
+    persistence: Fixed,
+    lacunarity: Fixed,
+    scale: (Fixed, Fixed),
+    inv_scale: (i64, i64),
+    bias: Fixed,
+    seed: i32,
+}
+
+impl FixedPerlin2D {
+    /// Create and return a new FixedPerlin2D object
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        octaves: u32,
+        amplitude: Fixed,
+        frequency: Fixed,
+        persistence: Fixed,
+        lacunarity: Fixed,
+        scale: (Fixed, Fixed),
+        bias: Fixed,
+        seed: i32,
+    ) -> Self {
+        Self {
+            octaves,
+            amplitude,
+            frequency,
+            persistence,
+            lacunarity,
+            scale,
+            inv_scale: inverse(scale),
+            bias,
+            seed,
+        }
+    }
+
+    /// Getter function for octaves
+    pub fn get_octaves(&self) -> u32 {
+        self.octaves
+    }
+    /// Getter function for amplitude
+    pub fn get_amplitude(&self) -> Fixed {
+        self.amplitude
+    }
+    /// Getter function for frequency
+    pub fn get_frequency(&self) -> Fixed {
+        self.frequency
+    }
+    /// Getter function for persistence
+    pub fn get_persistence(&self) -> Fixed {
+        self.persistence
+    }
+    /// Getter function for lacunarity
+    pub fn get_lacunarity(&self) -> Fixed {
+        self.lacunarity
+    }
+    /// Getter function for scale
+    pub fn get_scale(&self) -> (Fixed, Fixed) {
+        self.scale
+    }
+    /// Getter function for bias
+    pub fn get_bias(&self) -> Fixed {
+        self.bias
+    }
+    /// Getter function for seed
+    pub fn get_seed(&self) -> i32 {
+        self.seed
+    }
+
+    /// Setter function for octaves
+    pub fn set_octaves(&mut self, octaves: u32) {
+        self.octaves = octaves;
+    }
+    /// Setter function for amplitude
+    pub fn set_amplitude(&mut self, amplitude: Fixed) {
+        self.amplitude = amplitude;
+    }
+    /// Setter function for frequency
+    pub fn set_frequency(&mut self, frequency: Fixed) {
+        self.frequency = frequency;
+    }
+    /// Setter function for persistence
+    pub fn set_persistence(&mut self, persistence: Fixed) {
+        self.persistence = persistence;
+    }
+    /// Setter function for lacunarity
+    pub fn set_lacunarity(&mut self, lacunarity: Fixed) {
+        self.lacunarity = lacunarity;
+    }
+    /// Setter function for scale
+    pub fn set_scale(&mut self, scale: (Fixed, Fixed)) {
+        self.scale = scale;
+        self.inv_scale = inverse(scale);
+    }
+    /// Setter function for bias
+    pub fn set_bias(&mut self, bias: Fixed) {
+        self.bias = bias;
+    }
+    /// Setter function for seed
+    pub fn set_seed(&mut self, seed: i32) {
+        self.seed = seed;
+    }
+
+    /// generates and returns 2D perlin noise
+    pub fn get_noise(&self, x: Fixed, y: Fixed) -> Fixed {
+        let x = (x as i64 * self.inv_scale.0) >> FRAC_BITS;
+        let y = (y as i64 * self.inv_scale.1) >> FRAC_BITS;
+        self.bias.wrapping_add(mul(self.amplitude, self.total(x, y)))
+    }
+
+    fn total(&self, x: i64, y: i64) -> Fixed {
+        let mut t: Fixed = 0;
+        let mut amp: Fixed = ONE;
+        let mut freq = self.frequency as i64;
+        let seed = (self.seed as i64) << FRAC_BITS;
+
+        for _ in 0..self.octaves {
+            let sx = ((x * freq) >> FRAC_BITS) + seed;
+            let sy = ((y * freq) >> FRAC_BITS) + seed;
+            t = t.wrapping_add(mul(self.get_value(sy, sx), amp));
+            amp = mul(amp, self.persistence);
+            freq = (freq * self.lacunarity as i64) >> FRAC_BITS;
+        }
+        t
+    }
+
+    fn interpolate(&self, x: Fixed, y: Fixed, a: Fixed) -> Fixed {
+        mul(x, fade(ONE - a)) + mul(y, fade(a))
+    }
+
+    fn get_value(&self, x: i64, y: i64) -> Fixed {
+        // the arithmetic shift floors, so the lattice is continuous across the origin
+        let x_int = (x >> FRAC_BITS) as i32;
+        let y_int = (y >> FRAC_BITS) as i32;
+        let x_frac = (x & (ONE as i64 - 1)) as Fixed;
+        let y_frac = (y & (ONE as i64 - 1)) as Fixed;
+        let n = |dx: i32, dy: i32| hash(x_int.wrapping_add(dx), y_int.wrapping_add(dy));
+
+        // noise values
+        let n01 = n(-1, -1);
+        let n02 = n(1, -1);
+        let n03 = n(-1, 1);
+        let n04 = n(1, 1);
+        let n05 = n(-1, 0);
+        let n06 = n(1, 0);
+        let n07 = n(0, -1);
+        let n08 = n(0, 1);
+        let n09 = n(0, 0);
+
+        let n12 = n(2, -1);
+        let n14 = n(2, 1);
+        let n16 = n(2, 0);
+
+        let n23 = n(-1, 2);
+        let n24 = n(1, 2);
+        let n28 = n(0, 2);
+
+        let n34 = n(2, 2);
+
+        // find the noise values of the four corners, weights are 1/16, 1/8 and 1/4
+        let x0y0 = ((n01 + n02 + n03 + n04) >> 4) + ((n05 + n06 + n07 + n08) >> 3) + (n09 >> 2);
+        let x1y0 = ((n07 + n12 + n08 + n14) >> 4) + ((n09 + n16 + n02 + n04) >> 3) + (n06 >> 2);
+        let x0y1 = ((n05 + n06 + n23 + n24) >> 4) + ((n03 + n04 + n09 + n28) >> 3) + (n08 >> 2);
+        let x1y1 = ((n09 + n16 + n28 + n34) >> 4) + ((n08 + n14 + n06 + n24) >> 3) + (n04 >> 2);
+
+        // interpolate between those values according to the x and y fractions
+        let v1 = self.interpolate(x0y0, x1y0, x_frac);
+        let v2 = self.interpolate(x0y1, x1y1, x_frac);
+        self.interpolate(v1, v2, y_frac)
+    }
+}
+
+fn inverse(scale: (Fixed, Fixed)) -> (i64, i64) {
+    ((1i64 << 32) / scale.0 as i64, (1i64 << 32) / scale.1 as i64)
+}
+
+/// `3a^2 - 2a^3` for `a` in `[0, ONE]`
+#[inline]
+fn fade(a: Fixed) -> Fixed {
+    let a_sqr = mul(a, a);
+    3 * a_sqr - 2 * mul(a_sqr, a)
+}
+
+/// The prime hash of [`crate::PrimeHasher`] in 32 bit arithmetic
+///
+/// Only the lower 31 bits of the 64 bit hash are used, and those only depend on the lower 32 bits of every
+/// intermediate product, so wrapping `u32` math gives the same lattice values.
+#[inline]
+fn hash(x: i32, y: i32) -> Fixed {
+    let n = (x as u32).wrapping_add((y as u32).wrapping_mul(57));
+    let n = (n << 13) ^ n;
+    let t = n
+        .wrapping_mul(n)
+        .wrapping_mul(n.wrapping_mul(15731).wrapping_add(789221))
+        .wrapping_add(1376312589);
+    let t = t & 0x7fffffff;
+    // 1 - t / 2^30
+    ONE - (t >> 14) as Fixed
+}
@@ -0,0 +1,302 @@
+//! Streamlines that follow the gradient of a noise field.
+//!
+//! [`trace_streamline`] follows the gradient of a source, or the contours running perpendicular to it, from a single
+//! start point. [`streamlines`] covers a whole region with evenly spaced lines, which is the basis of hatching, wind
+//! and ocean current maps and other stylized renderings driven directly by the field.
+//!
+//! ```
+//! use perlin2d::flow::{streamlines, FlowDirection, FlowOptions};
+//! use perlin2d::{PerlinNoise2D, Region};
+//!
+//! let perlin = PerlinNoise2D::new(4, 1.0, 0.5, 0.5, 2.0, (64.0, 64.0), 0.0, 17);
+//! let region = Region::new((0.0, 0.0), (256.0, 256.0));
+//!
+//! let mut options = FlowOptions::new(8.0);
+//! options.set_direction(FlowDirection::Contour);
+//! let lines = streamlines(&perlin, region, &options);
+//!
+//! assert!(!lines.is_empty());
+//! for line in &lines {
+//!     assert!(line.len() >= 2);
+//!     // every point stays inside the region
+//!     assert!(line.iter().all(|&(x, y)| x >= 0.0 && x <= 256.0 && y >= 0.0 && y <= 256.0));
+//! }
+//! ```
+
+use crate::{NoiseSource, Region};
+use std::collections::HashMap;
+
+/// Which way streamlines run relative to the gradient
+///
+/// * `Gradient` - Along the gradient, crossing contours at right angles like water running downhill.
+/// * `Contour` - Perpendicular to the gradient, along the contours of the field.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FlowDirection {
+    #[default]
+    Gradient,
+    Contour,
+}
+
+/// Settings of streamline tracing
+///
+/// Member variables:
+///
+/// * `direction` - Which way the lines run. Defaults to `Gradient`.
+/// * `separation` - The distance between neighboring lines placed by [`streamlines`]. Lines end when they come
+///   closer than half of it to another line.
+/// * `step` - The integration step length. Defaults to a quarter of the separation.
+/// * `max_length` - The longest a line can grow in each direction from its start. Defaults to infinity.
+/// * `min_length` - Lines placed by [`streamlines`] that end up shorter are dropped. Defaults to the separation.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FlowOptions {
+    direction: FlowDirection,
+    separation: f64,
+    step: f64,
+    max_length: f64,
+    min_length: f64,
+}
+
+impl FlowOptions {
+    /// Create and return a new FlowOptions object for lines spaced `separation` apart
+    ///
+    /// Panics if `separation` is not a positive finite number.
+    pub fn new(separation: f64) -> Self {
+        assert!(
+            separation > 0.0 && separation.is_finite(),
+            "separation must be positive and finite"
+        );
+        Self {
+            direction: FlowDirection::Gradient,
+            separation,
+            step: 0.25 * separation,
+            max_length: f64::INFINITY,
+            min_length: separation,
+        }
+    }
+
+    /// Getter function for direction
+    pub fn get_direction(&self) -> FlowDirection {
+        self.direction
+    }
+    /// Getter function for separation
+    pub fn get_separation(&self) -> f64 {
+        self.separation
+    }
+    /// Getter function for step
+    pub fn get_step(&self) -> f64 {
+        self.step
+    }
+    /// Getter function for max_length
+    pub fn get_max_length(&self) -> f64 {
+        self.max_length
+    }
+    /// Getter function for min_length
+    pub fn get_min_length(&self) -> f64 {
+        self.min_length
+    }
+
+    /// Setter function for direction
+    pub fn set_direction(&mut self, direction: FlowDirection) {
+        self.direction = direction;
+    }
+    /// Setter function for step
+    ///
+    /// Panics if `step` is not a positive finite number.
+    pub fn set_step(&mut self, step: f64) {
+        assert!(step > 0.0 && step.is_finite(), "step must be positive and finite");
+        self.step = step;
+    }
+    /// Setter function for max_length
+    pub fn set_max_length(&mut self, max_length: f64) {
+        self.max_length = max_length;
+    }
+    /// Setter function for min_length
+    pub fn set_min_length(&mut self, min_length: f64) {
+        self.min_length = min_length;
+    }
+}
+
+/// Traces a streamline through `start` in both directions until it leaves the region, reaches a flat spot of the
+/// field, comes back to itself or grows to the maximum length
+///
+/// The line is integrated with the midpoint method over the normalized direction field, and the gradient is
+/// estimated with central differences a tenth of a step wide. The points run from one end of the line to the other,
+/// with `start` among them. Returns just `start` when it lies outside the region.
+pub fn trace_streamline<S: NoiseSource + ?Sized>(
+    source: &S,
+    start: (f64, f64),
+    region: Region,
+    options: &FlowOptions,
+) -> Vec<(f64, f64)> {
+    let occupancy = Occupancy::new(region, options.separation);
+    trace(source, start, region, options, &occupancy, false)
+}
+
+/// Covers a region with evenly spaced streamlines
+///
+/// Candidate start points lie on a grid with the spacing of the lines, visited row by row, and every candidate
+/// closer than the separation to an existing line is skipped. Lines stop where they come within half the separation
+/// of an earlier line, so no two lines touch. The result only depends on the source, region and options.
+pub fn streamlines<S: NoiseSource + ?Sized>(source: &S, region: Region, options: &FlowOptions) -> Vec<Vec<(f64, f64)>> {
+    let mut occupancy = Occupancy::new(region, options.separation);
+    let mut lines = Vec::new();
+    let (min, max) = (region.min, region.max);
+    let columns = ((max.0 - min.0) / options.separation).ceil().max(0.0) as usize;
+    let rows = ((max.1 - min.1) / options.separation).ceil().max(0.0) as usize;
+    for j in 0..rows {
+        for i in 0..columns {
+            let seed = (
+                min.0 + (i as f64 + 0.5) * options.separation,
+                min.1 + (j as f64 + 0.5) * options.separation,
+            );
+            if !inside(region, seed) || occupancy.is_near(seed, options.separation) {
+                continue;
+            }
+            let line = trace(source, seed, region, options, &occupancy, true);
+            if polyline_length(&line) >= options.min_length && line.len() >= 2 {
+                for &point in line.iter() {
+                    occupancy.insert(point);
+                }
+                lines.push(line);
+            }
+        }
+    }
+    lines
+}
+
+fn trace<S: NoiseSource + ?Sized>(
+    source: &S,
+    start: (f64, f64),
+    region: Region,
+    options: &FlowOptions,
+    occupancy: &Occupancy,
+    avoid: bool,
+) -> Vec<(f64, f64)> {
+    if !inside(region, start) {
+        return vec![start];
+    }
+    // points of the line itself, added once they lie a separation behind the tip so the line can end where it
+    // closes a loop
+    let mut own = Occupancy::new(region, options.separation);
+    let lag = (options.separation / options.step).ceil() as usize + 1;
+    let half = |sign: f64, own: &mut Occupancy| {
+        let mut points: Vec<(f64, f64)> = Vec::new();
+        let mut position = start;
+        let mut length = 0.0;
+        while length + options.step <= options.max_length {
+            let next = match advance(source, position, options, sign) {
+                Some(next) => next,
+                None => break,
+            };
+            let blocked = (avoid && occupancy.is_near(next, 0.5 * options.separation))
+                || own.is_near(next, 0.5 * options.separation);
+            if !inside(region, next) || blocked {
+                break;
+            }
+            points.push(next);
+            // the first points stay out, they lie next to the start of the other half
+            if points.len() > 2 * lag {
+                own.insert(points[points.len() - 1 - lag]);
+            }
+            position = next;
+            length += options.step;
+        }
+        points
+    };
+    let backward = half(-1.0, &mut own);
+    let forward = half(1.0, &mut own);
+    let mut line: Vec<(f64, f64)> = backward.into_iter().rev().collect();
+    line.push(start);
+    line.extend(forward);
+    line
+}
+
+/// One midpoint step of length `options.step`, `None` where the field is flat
+fn advance<S: NoiseSource + ?Sized>(
+    source: &S,
+    (x, y): (f64, f64),
+    options: &FlowOptions,
+    sign: f64,
+) -> Option<(f64, f64)> {
+    let h = 0.5 * options.step;
+    let (dx, dy) = direction(source, (x, y), options)?;
+    let (mx, my) = (x + sign * h * dx, y + sign * h * dy);
+    let (dx, dy) = direction(source, (mx, my), options)?;
+    Some((x + sign * options.step * dx, y + sign * options.step * dy))
+}
+
+/// Unit vector of the direction field at a point
+fn direction<S: NoiseSource + ?Sized>(source: &S, (x, y): (f64, f64), options: &FlowOptions) -> Option<(f64, f64)> {
+    let e = 0.05 * options.step;
+    let gx = (source.get_noise(x + e, y) - source.get_noise(x - e, y)) / (2.0 * e);
+    let gy = (source.get_noise(x, y + e) - source.get_noise(x, y - e)) / (2.0 * e);
+    let norm = f64::hypot(gx, gy);
+    if !norm.is_normal() {
+        return None;
+    }
+    match options.direction {
+        FlowDirection::Gradient => Some((gx / norm, gy / norm)),
+        FlowDirection::Contour => Some((-gy / norm, gx / norm)),
+    }
+}
+
+fn inside(region: Region, (x, y): (f64, f64)) -> bool {
+    x >= region.min.0 && x <= region.max.0 && y >= region.min.1 && y <= region.max.1
+}
+
+fn polyline_length(points: &[(f64, f64)]) -> f64 {
+    points
+        .windows(2)
+        .map(|w| f64::hypot(w[1].0 - w[0].0, w[1].1 - w[0].1))
+        .sum()
+}
+
+/// Points of lines bucketed into square cells, for distance queries
+///
+/// Only cells holding points are stored, so a short line costs the same in a small region as in a huge one.
+struct Occupancy {
+    origin: (f64, f64),
+    cell: f64,
+    buckets: HashMap<(i64, i64), Vec<(f64, f64)>>,
+}
+
+impl Occupancy {
+    fn new(region: Region, cell: f64) -> Self {
+        Self {
+            origin: region.min,
+            cell,
+            buckets: HashMap::new(),
+        }
+    }
+
+    fn bucket(&self, (x, y): (f64, f64)) -> (i64, i64) {
+        (
+            ((x - self.origin.0) / self.cell).floor() as i64,
+            ((y - self.origin.1) / self.cell).floor() as i64,
+        )
+    }
+
+    fn insert(&mut self, point: (f64, f64)) {
+        self.buckets.entry(self.bucket(point)).or_default().push(point);
+    }
+
+    /// Whether a stored point lies closer than `distance`, which must not exceed the cell size
+    fn is_near(&self, point: (f64, f64), distance: f64) -> bool {
+        let (i, j) = self.bucket(point);
+        for nj in j.saturating_sub(1)..=j.saturating_add(1) {
+            for ni in i.saturating_sub(1)..=i.saturating_add(1) {
+                let near = self.buckets.get(&(ni, nj)).is_some_and(|bucket| {
+                    bucket
+                        .iter()
+                        .any(|&(x, y)| f64::hypot(x - point.0, y - point.1) < distance)
+                });
+                if near {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
@@ -0,0 +1,106 @@
+//! Godot bindings through godot-rust, enabled by the `godot` feature.
+//!
+//! Linking this crate into a GDExtension library registers a `PerlinNoise2D` class, so GDScript produces exactly the
+//! same values as Rust code using the same parameters:
+//!
+//! ```gdscript
+//! var noise = PerlinNoise2D.create(6, 10.0, 0.5, 1.0, 2.0, Vector2(100, 100), 0.5, 101)
+//! var height = noise.get_noise(5.0, 10.0)
+//!
+//! var image = Image.create_empty(256, 256, false, Image.FORMAT_RF)
+//! noise.fill_image(image, Vector2(0, 0), Vector2(1024, 1024))
+//! ```
+
+use crate::{NoiseSource, Region};
+use godot::classes::image::Format;
+use godot::classes::{IRefCounted, Image, RefCounted};
+use godot::prelude::*;
+
+/// The generator exposed to Godot as `PerlinNoise2D`
+#[derive(GodotClass)]
+#[class(base = RefCounted, rename = PerlinNoise2D)]
+pub struct GodotPerlinNoise2D {
+    perlin: crate::PerlinNoise2D,
+}
+
+#[godot_api]
+impl IRefCounted for GodotPerlinNoise2D {
+    fn init(_base: Base<RefCounted>) -> Self {
+        Self {
+            perlin: crate::PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 0),
+        }
+    }
+}
+
+#[godot_api]
+impl GodotPerlinNoise2D {
+    /// Create a generator with the same parameters as [`crate::PerlinNoise2D::new`]
+    #[func]
+    #[allow(clippy::too_many_arguments)]
+    fn create(
+        octaves: i32,
+        amplitude: f64,
+        frequency: f64,
+        persistence: f64,
+        lacunarity: f64,
+        scale: Vector2,
+        bias: f64,
+        seed: i32,
+    ) -> Gd<Self> {
+        Gd::from_object(Self {
+            perlin: crate::PerlinNoise2D::new(
+                octaves,
+                amplitude,
+                frequency,
+                persistence,
+                lacunarity,
+                (scale.x as f64, scale.y as f64),
+                bias,
+                seed,
+            ),
+        })
+    }
+
+    /// Returns the noise value at the given point
+    #[func]
+    fn get_noise(&self, x: f64, y: f64) -> f64 {
+        self.perlin.get_noise(x, y)
+    }
+
+    /// Getter function for seed
+    #[func]
+    fn get_seed(&self) -> i32 {
+        self.perlin.get_seed()
+    }
+
+    /// Setter function for seed
+    #[func]
+    fn set_seed(&mut self, seed: i32) {
+        self.perlin.set_seed(seed);
+    }
+
+    /// Samples the area from `min` to `max` into every pixel of `image`, converting it to `FORMAT_RF`
+    ///
+    /// Pixel `(0, 0)` is sampled at `min`, like row `0` of [`NoiseSource::generate_map`].
+    #[func]
+    fn fill_image(&self, mut image: Gd<Image>, min: Vector2, max: Vector2) {
+        let (width, height) = (image.get_width(), image.get_height());
+        let region = Region::new((min.x as f64, min.y as f64), (max.x as f64, max.y as f64));
+        let map = self
+            .perlin
+            .generate_map(region, (width.max(0) as usize, height.max(0) as usize));
+        let bytes: Vec<u8> = map
+            .get_values()
+            .iter()
+            .flat_map(|&value| (value as f32).to_le_bytes())
+            .collect();
+        image.set_data(width, height, false, Format::RF, &PackedByteArray::from(bytes));
+    }
+}
+
+impl GodotPerlinNoise2D {
+    /// Getter function for the wrapped generator
+    pub fn get_perlin(&self) -> &crate::PerlinNoise2D {
+        &self.perlin
+    }
+}
@@ -0,0 +1,90 @@
+//! Color ramps that map noise values to colors.
+
+use crate::NoiseMap;
+
+/// An RGB color
+pub type Color = [u8; 3];
+
+/// Piecewise linear color ramp
+///
+/// A gradient is a list of `(value, color)` stops sorted by value. Values between two stops blend their colors
+/// linearly and values outside the stops take the color of the nearest end.
+///
+/// ```
+/// use perlin2d::gradient::Gradient;
+///
+/// let gradient = Gradient::new(vec![(0.0, [0, 0, 0]), (1.0, [255, 255, 255])]);
+/// assert_eq!(gradient.get_color(0.5), [128, 128, 128]);
+/// assert_eq!(gradient.get_color(2.0), [255, 255, 255]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Gradient {
+    stops: Vec<(f64, Color)>,
+}
+
+impl Gradient {
+    /// Create and return a new Gradient object from `(value, color)` stops in any order
+    pub fn new(mut stops: Vec<(f64, Color)>) -> Self {
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { stops }
+    }
+
+    /// Hypsometric tints for terrain with sea level at `0.0` and values in `[-1, 1]`
+    ///
+    /// Deep water fades into shallows below zero, and land rises from beach through lowland green and highland brown
+    /// to snow at `1.0`.
+    pub fn terrain() -> Self {
+        Self::new(vec![
+            (-1.0, [0, 0, 96]),
+            (-0.25, [0, 64, 160]),
+            (0.0, [64, 160, 224]),
+            (0.02, [224, 208, 144]),
+            (0.1, [64, 160, 64]),
+            (0.35, [32, 112, 40]),
+            (0.6, [128, 104, 72]),
+            (0.85, [176, 168, 160]),
+            (1.0, [255, 255, 255]),
+        ])
+    }
+
+    /// Black at `min` to white at `max`
+    pub fn grayscale(min: f64, max: f64) -> Self {
+        Self::new(vec![(min, [0, 0, 0]), (max, [255, 255, 255])])
+    }
+
+    /// Getter function for the sorted `(value, color)` stops
+    pub fn get_stops(&self) -> &[(f64, Color)] {
+        &self.stops
+    }
+
+    /// Add a stop, keeping the stops sorted
+    pub fn add_stop(&mut self, value: f64, color: Color) {
+        let index = self.stops.partition_point(|stop| stop.0 <= value);
+        self.stops.insert(index, (value, color));
+    }
+
+    /// Returns the color of the given value, black if the gradient has no stops
+    pub fn get_color(&self, value: f64) -> Color {
+        let index = self.stops.partition_point(|stop| stop.0 <= value);
+        if index == 0 {
+            return self.stops.first().map_or([0, 0, 0], |stop| stop.1);
+        }
+        if index == self.stops.len() {
+            return self.stops[index - 1].1;
+        }
+        let (lo, a) = self.stops[index - 1];
+        let (hi, b) = self.stops[index];
+        let t = (value - lo) / (hi - lo);
+        let mut color = [0; 3];
+        for c in 0..3 {
+            color[c] = (a[c] as f64 + (b[c] as f64 - a[c] as f64) * t).round() as u8;
+        }
+        color
+    }
+
+    /// Colors every value of a map, returning row-major RGB bytes
+    pub fn colorize(&self, map: &NoiseMap) -> Vec<u8> {
+        map.get_values().iter().flat_map(|&v| self.get_color(v)).collect()
+    }
+}
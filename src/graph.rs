@@ -0,0 +1,693 @@
+//! Noise pipelines described as a graph of nodes.
+//!
+//! A [`Graph`] holds noise sources, modifiers and combinators as [`Node`]s. Nodes refer to their inputs by
+//! [`NodeId`], and an input always has to be added before the nodes that use it, so a graph can never contain a
+//! cycle. Graphs can be evaluated one point at a time or over a whole [`Region`], in which case every node is
+//! computed once per region and nodes marked as cached keep their grid for later evaluations of the same region.
+//! Bands of rows, as filled by [`NoiseSource::fill_region_threaded`] and the [`crate::chunk`] functions, are computed
+//! node by node in the same way.
+//!
+//! With the `serde` feature enabled a graph can be serialized; call [`Graph::validate`] after loading one from an
+//! untrusted source.
+//!
+//! # Example
+//!
+//! ```
+//! use perlin2d::graph::{Graph, Node};
+//! use perlin2d::{NoiseSource, PerlinConfig, PerlinNoise2D, Region};
+//!
+//! let continents = PerlinNoise2D::new(2, 1.0, 0.5, 0.5, 2.0, (400.0, 400.0), 0.0, 1);
+//! let mountains = PerlinNoise2D::new(6, 1.0, 1.0, 0.5, 2.0, (50.0, 50.0), 0.0, 2);
+//!
+//! let mut graph = Graph::new();
+//! let control = graph.add(Node::Perlin(PerlinConfig::from(&continents)));
+//! let source = graph.add(Node::Perlin(PerlinConfig::from(&mountains)));
+//! let masked = graph.add(Node::Mask { control, source, threshold: Some((0.0, 1.0, 0.2)) });
+//! let terrain = graph.add(Node::Add(vec![control, masked]));
+//! graph.set_output(terrain);
+//!
+//! let height = graph.get_noise(5.0, 10.0);
+//!
+//! let region = Region::new((0.0, 0.0), (400.0, 300.0));
+//! let map = graph.generate_map(region, (80, 60));
+//! assert_eq!(graph.generate_map_threaded(region, (80, 60), 4), map);
+//! ```
+
+use crate::combine::threshold_weight;
+use crate::{ConfigError, NoiseMap, NoiseSource, PerlinConfig, PerlinNoise2D, Region};
+use std::fmt;
+use std::ops::Range;
+use std::sync::Mutex;
+
+/// Identifier of a node inside a [`Graph`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct NodeId(usize);
+
+impl NodeId {
+    /// Position of the node in the graph, in insertion order
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// A single operation in a [`Graph`]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Node {
+    /// Perlin noise generated from the given parameters.
+    Perlin(PerlinConfig),
+    /// The same value everywhere.
+    Constant(f64),
+    /// Sum of the inputs.
+    Add(Vec<NodeId>),
+    /// Product of the inputs.
+    Multiply(Vec<NodeId>),
+    /// Weighted sum of the inputs, see [`crate::BlendedNoise`].
+    Blend(Vec<(NodeId, f64)>),
+    /// `input * scale + bias`.
+    ScaleBias { input: NodeId, scale: f64, bias: f64 },
+    /// Absolute value of the input.
+    Abs(NodeId),
+    /// The input limited to `[min, max]`.
+    Clamp { input: NodeId, min: f64, max: f64 },
+    /// Smallest of the inputs.
+    Min(Vec<NodeId>),
+    /// Largest of the inputs.
+    Max(Vec<NodeId>),
+    /// The source weighted by the control, see [`crate::Masked`].
+    Mask {
+        control: NodeId,
+        source: NodeId,
+        threshold: Option<(f64, f64, f64)>,
+    },
+}
+
+impl Node {
+    /// Returns the nodes this node reads from
+    pub fn inputs(&self) -> Vec<NodeId> {
+        match self {
+            Node::Perlin(_) | Node::Constant(_) => Vec::new(),
+            Node::Add(inputs) | Node::Multiply(inputs) | Node::Min(inputs) | Node::Max(inputs) => inputs.clone(),
+            Node::Blend(inputs) => inputs.iter().map(|&(id, _)| id).collect(),
+            Node::ScaleBias { input, .. } | Node::Abs(input) | Node::Clamp { input, .. } => vec![*input],
+            Node::Mask { control, source, .. } => vec![*control, *source],
+        }
+    }
+}
+
+/// Error returned when a graph is not well formed
+#[derive(Clone, Debug, PartialEq)]
+pub enum GraphError {
+    /// A node reads from a node that does not come before it in the graph.
+    InvalidInput { node: NodeId, input: NodeId },
+    /// The output node does not exist.
+    InvalidOutput(NodeId),
+    /// The graph has no output node.
+    MissingOutput,
+    /// A `Clamp` node has a `min` above its `max` or a NaN bound.
+    InvalidBounds { node: NodeId, min: f64, max: f64 },
+    /// A `Perlin` node holds a configuration that fails [`PerlinConfig::validate`].
+    InvalidConfig { node: NodeId, error: ConfigError },
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::InvalidInput { node, input } => {
+                write!(
+                    f,
+                    "node {} reads from node {} which is not defined before it",
+                    node.0, input.0
+                )
+            }
+            GraphError::InvalidOutput(id) => write!(f, "output node {} does not exist", id.0),
+            GraphError::MissingOutput => write!(f, "graph has no output node"),
+            GraphError::InvalidBounds { node, min, max } => {
+                write!(
+                    f,
+                    "node {} clamps to [{}, {}], which is not a valid range",
+                    node.0, min, max
+                )
+            }
+            GraphError::InvalidConfig { node, error } => write!(f, "node {} is not valid: {}", node.0, error),
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+#[derive(Clone, Debug)]
+struct CachedGrid {
+    region: Region,
+    resolution: (usize, usize),
+    values: Vec<f64>,
+}
+
+/// A directed acyclic graph of noise nodes
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "GraphData", into = "GraphData"))]
+pub struct Graph {
+    nodes: Vec<Node>,
+    cached: Vec<bool>,
+    output: Option<NodeId>,
+    generators: Vec<Option<PerlinNoise2D>>,
+    cache: Mutex<Vec<Option<CachedGrid>>>,
+}
+
+/// The serialized form of a [`Graph`], without the generators and grids that are derived from the nodes
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct GraphData {
+    nodes: Vec<Node>,
+    #[serde(default)]
+    cached: Vec<bool>,
+    output: Option<NodeId>,
+}
+
+#[cfg(feature = "serde")]
+impl From<GraphData> for Graph {
+    fn from(data: GraphData) -> Self {
+        let generators = data.nodes.iter().map(build_generator).collect();
+        Self {
+            nodes: data.nodes,
+            cached: data.cached,
+            output: data.output,
+            generators,
+            cache: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Graph> for GraphData {
+    fn from(graph: Graph) -> Self {
+        Self {
+            nodes: graph.nodes,
+            cached: graph.cached,
+            output: graph.output,
+        }
+    }
+}
+
+impl Default for Graph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for Graph {
+    fn clone(&self) -> Self {
+        Self {
+            nodes: self.nodes.clone(),
+            cached: self.cached.clone(),
+            output: self.output,
+            generators: self.generators.clone(),
+            cache: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl fmt::Debug for Graph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Graph")
+            .field("nodes", &self.nodes)
+            .field("cached", &self.cached)
+            .field("output", &self.output)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Graph {
+    /// Create and return a new, empty Graph object
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            cached: Vec::new(),
+            output: None,
+            generators: Vec::new(),
+            cache: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Add a node and return its id
+    ///
+    /// Panics if the node is not valid, see [`Graph::try_add`].
+    pub fn add(&mut self, node: Node) -> NodeId {
+        match self.try_add(node) {
+            Ok(id) => id,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Add a node and return its id, or an error if it reads from a node that is not part of this graph, is a
+    /// `Clamp` node whose `min` lies above its `max` or is a `Perlin` node whose configuration is not valid
+    ///
+    /// The generator of a `Perlin` node is built once here rather than every time the node is evaluated.
+    ///
+    /// ```
+    /// use perlin2d::graph::{Graph, GraphError, Node};
+    /// use perlin2d::{PerlinConfig, PerlinNoise2D};
+    ///
+    /// let mut graph = Graph::new();
+    /// let input = graph.add(Node::Constant(0.5));
+    /// let error = graph.try_add(Node::Clamp { input, min: 1.0, max: 0.0 }).unwrap_err();
+    /// assert!(matches!(error, GraphError::InvalidBounds { node, .. } if node.index() == 1));
+    /// assert_eq!(error.to_string(), "node 1 clamps to [1, 0], which is not a valid range");
+    /// assert!(graph.try_add(Node::Clamp { input, min: f64::NAN, max: 1.0 }).is_err());
+    /// assert_eq!(graph.len(), 1);
+    ///
+    /// assert!(graph.try_add(Node::Clamp { input, min: 0.0, max: 1.0 }).is_ok());
+    ///
+    /// let mut config = PerlinConfig::from(&PerlinNoise2D::new(4, 1.0, 1.0, 0.5, 2.0, (50.0, 50.0), 0.0, 1));
+    /// config.scale = (0.0, 50.0);
+    /// let error = graph.try_add(Node::Perlin(config)).unwrap_err();
+    /// assert!(matches!(error, GraphError::InvalidConfig { .. }));
+    /// assert_eq!(error.to_string(), "node 2 is not valid: invalid value for `scale`: must not be zero");
+    /// ```
+    pub fn try_add(&mut self, node: Node) -> Result<NodeId, GraphError> {
+        let id = NodeId(self.nodes.len());
+        check_node(id, &node)?;
+        self.generators.push(build_generator(&node));
+        self.nodes.push(node);
+        self.cached.resize(self.nodes.len(), false);
+        Ok(id)
+    }
+
+    /// Replace an existing node, dropping any cached grids that depend on it
+    ///
+    /// Panics if the node reads from itself or from a node added after it, is a `Clamp` node whose `min` lies above
+    /// its `max` or is a `Perlin` node whose configuration is not valid.
+    pub fn set_node(&mut self, id: NodeId, node: Node) {
+        if let Err(e) = check_node(id, &node) {
+            panic!("{}", e);
+        }
+        self.generators[id.0] = build_generator(&node);
+        self.nodes[id.0] = node;
+        self.invalidate_from(id);
+    }
+
+    /// Getter function for a node
+    pub fn get_node(&self, id: NodeId) -> &Node {
+        &self.nodes[id.0]
+    }
+    /// Number of nodes in the graph
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+    /// Returns true if the graph has no nodes
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Getter function for the output node
+    pub fn get_output(&self) -> Option<NodeId> {
+        self.output
+    }
+    /// Setter function for the output node, used when the graph is sampled as a [`NoiseSource`]
+    pub fn set_output(&mut self, id: NodeId) {
+        assert!(id.0 < self.nodes.len(), "{}", GraphError::InvalidOutput(id));
+        self.output = Some(id);
+    }
+
+    /// Returns true if the node keeps its grid between region evaluations
+    pub fn is_cached(&self, id: NodeId) -> bool {
+        self.cached.get(id.0).copied().unwrap_or(false)
+    }
+    /// Choose whether the node keeps its grid between region evaluations
+    ///
+    /// Caching is useful for expensive nodes whose downstream nodes are being tweaked, since only the changed part
+    /// of the graph has to be recomputed when the same region is evaluated again.
+    pub fn set_cached(&mut self, id: NodeId, cached: bool) {
+        assert!(id.0 < self.nodes.len(), "node {} does not exist", id.0);
+        if self.cached.len() < self.nodes.len() {
+            self.cached.resize(self.nodes.len(), false);
+        }
+        self.cached[id.0] = cached;
+        if !cached {
+            if let Some(slot) = self.cache_lock().get_mut(id.0) {
+                *slot = None;
+            }
+        }
+    }
+    /// Drop every cached grid
+    pub fn clear_cache(&self) {
+        self.cache_lock().clear();
+    }
+
+    /// Check that every node only reads from nodes defined before it, that clamp bounds are ordered, that Perlin
+    /// configurations are valid and that the output exists
+    pub fn validate(&self) -> Result<(), GraphError> {
+        for (index, node) in self.nodes.iter().enumerate() {
+            check_node(NodeId(index), node)?;
+        }
+        match self.output {
+            Some(id) if id.0 >= self.nodes.len() => Err(GraphError::InvalidOutput(id)),
+            Some(_) => Ok(()),
+            None => Err(GraphError::MissingOutput),
+        }
+    }
+
+    /// Evaluate a node at a single point
+    ///
+    /// Every node is evaluated at most once, even when several nodes read from it.
+    pub fn evaluate(&self, id: NodeId, x: f64, y: f64) -> f64 {
+        let mut memo = vec![None; id.0 + 1];
+        self.evaluate_point(id, x, y, &mut memo)
+    }
+
+    fn evaluate_point(&self, id: NodeId, x: f64, y: f64, memo: &mut [Option<f64>]) -> f64 {
+        if let Some(value) = memo[id.0] {
+            return value;
+        }
+        let mut evaluate = |input: NodeId| self.evaluate_point(input, x, y, memo);
+        let value = match &self.nodes[id.0] {
+            Node::Perlin(config) => self.generator(id.0, config).get_noise(x, y),
+            Node::Constant(value) => *value,
+            Node::Add(inputs) => inputs.iter().map(|&i| evaluate(i)).sum(),
+            Node::Multiply(inputs) => inputs.iter().map(|&i| evaluate(i)).product(),
+            Node::Blend(inputs) => inputs.iter().map(|&(i, w)| w * evaluate(i)).sum(),
+            Node::ScaleBias { input, scale, bias } => evaluate(*input) * scale + bias,
+            Node::Abs(input) => f64::abs(evaluate(*input)),
+            Node::Clamp { input, min, max } => f64::max(f64::min(evaluate(*input), *max), *min),
+            Node::Min(inputs) => inputs.iter().map(|&i| evaluate(i)).fold(f64::INFINITY, f64::min),
+            Node::Max(inputs) => inputs.iter().map(|&i| evaluate(i)).fold(f64::NEG_INFINITY, f64::max),
+            Node::Mask {
+                control,
+                source,
+                threshold,
+            } => {
+                let value = evaluate(*control);
+                let weight = mask_weight(*threshold, || self.get_node_range(*control), value);
+                if weight == 0.0 {
+                    0.0
+                } else {
+                    weight * evaluate(*source)
+                }
+            }
+        };
+        memo[id.0] = Some(value);
+        value
+    }
+
+    /// Evaluate a node over a region at the given `(width, height)` resolution
+    ///
+    /// Every node the result depends on is computed once for the whole region.
+    pub fn evaluate_region(&self, id: NodeId, region: Region, resolution: (usize, usize)) -> NoiseMap {
+        let values = self.evaluate_rows(id, region, resolution, 0, resolution.1);
+        NoiseMap::from_values(region, resolution, values)
+    }
+
+    /// Returns the conservative `(min, max)` bounds of a node
+    pub fn get_node_range(&self, id: NodeId) -> (f64, f64) {
+        match &self.nodes[id.0] {
+            Node::Perlin(config) => self.generator(id.0, config).get_range(),
+            Node::Constant(value) => (*value, *value),
+            Node::Add(inputs) => inputs.iter().fold((0.0, 0.0), |(min, max), &i| {
+                let (lo, hi) = self.get_node_range(i);
+                (min + lo, max + hi)
+            }),
+            Node::Multiply(inputs) => inputs.iter().fold((1.0, 1.0), |(min, max), &i| {
+                let (lo, hi) = self.get_node_range(i);
+                let products = [min * lo, min * hi, max * lo, max * hi];
+                (
+                    products.iter().cloned().fold(f64::INFINITY, f64::min),
+                    products.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+                )
+            }),
+            Node::Blend(inputs) => inputs.iter().fold((0.0, 0.0), |(min, max), &(i, w)| {
+                let (lo, hi) = self.get_node_range(i);
+                (min + f64::min(w * lo, w * hi), max + f64::max(w * lo, w * hi))
+            }),
+            Node::ScaleBias { input, scale, bias } => {
+                let (lo, hi) = self.get_node_range(*input);
+                (
+                    f64::min(lo * scale, hi * scale) + bias,
+                    f64::max(lo * scale, hi * scale) + bias,
+                )
+            }
+            Node::Abs(input) => {
+                let (lo, hi) = self.get_node_range(*input);
+                if lo >= 0.0 {
+                    (lo, hi)
+                } else if hi <= 0.0 {
+                    (-hi, -lo)
+                } else {
+                    (0.0, f64::max(-lo, hi))
+                }
+            }
+            Node::Clamp { input, min, max } => {
+                let (lo, hi) = self.get_node_range(*input);
+                let clamp = |value: f64| f64::max(f64::min(value, *max), *min);
+                (clamp(lo), clamp(hi))
+            }
+            Node::Min(inputs) => inputs.iter().fold((f64::INFINITY, f64::INFINITY), |(min, max), &i| {
+                let (lo, hi) = self.get_node_range(i);
+                (f64::min(min, lo), f64::min(max, hi))
+            }),
+            Node::Max(inputs) => inputs
+                .iter()
+                .fold((f64::NEG_INFINITY, f64::NEG_INFINITY), |(min, max), &i| {
+                    let (lo, hi) = self.get_node_range(i);
+                    (f64::max(min, lo), f64::max(max, hi))
+                }),
+            Node::Mask { source, .. } => {
+                let (lo, hi) = self.get_node_range(*source);
+                (f64::min(lo, 0.0), f64::max(hi, 0.0))
+            }
+        }
+    }
+
+    /// Evaluate a node over `rows` rows of a region from `first_row` on, in row-major order
+    ///
+    /// Only the whole region is stored in node caches. Bands of it read from a cached grid but do not replace it.
+    fn evaluate_rows(
+        &self,
+        id: NodeId,
+        region: Region,
+        resolution: (usize, usize),
+        first_row: usize,
+        rows: usize,
+    ) -> Vec<f64> {
+        let mut needed = vec![false; id.0 + 1];
+        needed[id.0] = true;
+        for index in (0..=id.0).rev() {
+            if needed[index] {
+                for input in self.nodes[index].inputs() {
+                    needed[input.0] = true;
+                }
+            }
+        }
+
+        let whole = first_row == 0 && rows == resolution.1;
+        let band = first_row * resolution.0..(first_row + rows) * resolution.0;
+        let mut grids: Vec<Option<Vec<f64>>> = vec![None; id.0 + 1];
+        for index in 0..=id.0 {
+            if !needed[index] {
+                continue;
+            }
+            let grid = match self.cached_grid(index, region, resolution, band.clone()) {
+                Some(grid) => grid,
+                None => {
+                    let grid = self.compute_grid(index, &grids, region, resolution, first_row, band.len());
+                    if whole && self.is_cached(NodeId(index)) {
+                        self.store_grid(index, region, resolution, &grid);
+                    }
+                    grid
+                }
+            };
+            grids[index] = Some(grid);
+        }
+        grids[id.0].take().unwrap_or_default()
+    }
+
+    fn compute_grid(
+        &self,
+        index: usize,
+        grids: &[Option<Vec<f64>>],
+        region: Region,
+        resolution: (usize, usize),
+        first_row: usize,
+        len: usize,
+    ) -> Vec<f64> {
+        let input = |id: &NodeId| grids[id.0].as_deref().unwrap_or(&[]);
+        let mut out = vec![0.0; len];
+        match &self.nodes[index] {
+            Node::Perlin(config) => self
+                .generator(index, config)
+                .fill_rows(region, resolution, first_row, &mut out),
+            Node::Constant(value) => out.iter_mut().for_each(|v| *v = *value),
+            Node::Add(inputs) => {
+                for id in inputs {
+                    out.iter_mut().zip(input(id)).for_each(|(v, a)| *v += a);
+                }
+            }
+            Node::Multiply(inputs) => {
+                out.iter_mut().for_each(|v| *v = 1.0);
+                for id in inputs {
+                    out.iter_mut().zip(input(id)).for_each(|(v, a)| *v *= a);
+                }
+            }
+            Node::Blend(inputs) => {
+                for (id, weight) in inputs {
+                    out.iter_mut().zip(input(id)).for_each(|(v, a)| *v += weight * a);
+                }
+            }
+            Node::ScaleBias { input: id, scale, bias } => {
+                out.iter_mut().zip(input(id)).for_each(|(v, a)| *v = a * scale + bias);
+            }
+            Node::Abs(id) => out.iter_mut().zip(input(id)).for_each(|(v, a)| *v = f64::abs(*a)),
+            Node::Clamp { input: id, min, max } => {
+                out.iter_mut()
+                    .zip(input(id))
+                    .for_each(|(v, a)| *v = f64::max(f64::min(*a, *max), *min));
+            }
+            Node::Min(inputs) => {
+                out.iter_mut().for_each(|v| *v = f64::INFINITY);
+                for id in inputs {
+                    out.iter_mut().zip(input(id)).for_each(|(v, a)| *v = f64::min(*v, *a));
+                }
+            }
+            Node::Max(inputs) => {
+                out.iter_mut().for_each(|v| *v = f64::NEG_INFINITY);
+                for id in inputs {
+                    out.iter_mut().zip(input(id)).for_each(|(v, a)| *v = f64::max(*v, *a));
+                }
+            }
+            Node::Mask {
+                control,
+                source,
+                threshold,
+            } => {
+                let range = self.get_node_range(*control);
+                for ((v, c), s) in out.iter_mut().zip(input(control)).zip(input(source)) {
+                    *v = mask_weight(*threshold, || range, *c) * s;
+                }
+            }
+        }
+        out
+    }
+
+    /// The generator built for a `Perlin` node, or a new one if the configuration was not valid when it was added
+    fn generator(&self, index: usize, config: &PerlinConfig) -> std::borrow::Cow<'_, PerlinNoise2D> {
+        match self.generators.get(index) {
+            Some(Some(perlin)) => std::borrow::Cow::Borrowed(perlin),
+            _ => std::borrow::Cow::Owned(config.build()),
+        }
+    }
+
+    fn cache_lock(&self) -> std::sync::MutexGuard<'_, Vec<Option<CachedGrid>>> {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn cached_grid(
+        &self,
+        index: usize,
+        region: Region,
+        resolution: (usize, usize),
+        band: Range<usize>,
+    ) -> Option<Vec<f64>> {
+        if !self.is_cached(NodeId(index)) {
+            return None;
+        }
+        match self.cache_lock().get(index) {
+            Some(Some(grid)) if grid.region == region && grid.resolution == resolution => {
+                Some(grid.values[band].to_vec())
+            }
+            _ => None,
+        }
+    }
+
+    fn store_grid(&self, index: usize, region: Region, resolution: (usize, usize), values: &[f64]) {
+        let mut cache = self.cache_lock();
+        if cache.len() <= index {
+            cache.resize(index + 1, None);
+        }
+        cache[index] = Some(CachedGrid {
+            region,
+            resolution,
+            values: values.to_vec(),
+        });
+    }
+
+    fn invalidate_from(&mut self, id: NodeId) {
+        // only later nodes can depend on this one
+        let cache = self.cache.get_mut().unwrap_or_else(|e| e.into_inner());
+        cache.iter_mut().skip(id.0).for_each(|slot| *slot = None);
+    }
+}
+
+impl NoiseSource for Graph {
+    fn get_noise(&self, x: f64, y: f64) -> f64 {
+        self.evaluate(self.output.expect("graph has no output node"), x, y)
+    }
+
+    fn get_range(&self) -> (f64, f64) {
+        self.get_node_range(self.output.expect("graph has no output node"))
+    }
+
+    fn fill_region(&self, region: Region, resolution: (usize, usize), out: &mut [f64]) {
+        assert_eq!(
+            out.len(),
+            resolution.0 * resolution.1,
+            "output does not match the resolution"
+        );
+        let map = self.evaluate_region(self.output.expect("graph has no output node"), region, resolution);
+        out.copy_from_slice(map.get_values());
+    }
+
+    fn fill_rows(&self, region: Region, resolution: (usize, usize), first_row: usize, out: &mut [f64]) {
+        let width = resolution.0;
+        if width == 0 {
+            return;
+        }
+        assert!(
+            out.len().is_multiple_of(width) && first_row + out.len() / width <= resolution.1,
+            "output does not match the rows of the resolution"
+        );
+        let output = self.output.expect("graph has no output node");
+        let values = self.evaluate_rows(output, region, resolution, first_row, out.len() / width);
+        out.copy_from_slice(&values);
+    }
+}
+
+fn mask_weight(threshold: Option<(f64, f64, f64)>, range: impl FnOnce() -> (f64, f64), value: f64) -> f64 {
+    match threshold {
+        Some((lower, upper, falloff)) => threshold_weight(value, lower, upper, falloff),
+        None => {
+            let (min, max) = range();
+            if max > min {
+                ((value - min) / (max - min)).clamp(0.0, 1.0)
+            } else {
+                1.0
+            }
+        }
+    }
+}
+
+fn check_node(id: NodeId, node: &Node) -> Result<(), GraphError> {
+    if let Some(input) = node.inputs().into_iter().find(|input| input.0 >= id.0) {
+        return Err(GraphError::InvalidInput { node: id, input });
+    }
+    if let Node::Clamp { min, max, .. } = *node {
+        // written so that NaN bounds fail as well
+        let ordered = min <= max;
+        if !ordered {
+            return Err(GraphError::InvalidBounds { node: id, min, max });
+        }
+    }
+    if let Node::Perlin(config) = node {
+        config
+            .validate()
+            .map_err(|error| GraphError::InvalidConfig { node: id, error })?;
+    }
+    Ok(())
+}
+
+fn build_generator(node: &Node) -> Option<PerlinNoise2D> {
+    match node {
+        Node::Perlin(config) if config.validate().is_ok() => Some(config.build()),
+        _ => None,
+    }
+}
@@ -0,0 +1,126 @@
+//! Histogram matching of sampled values.
+//!
+//! Noise values follow a bell shaped distribution that depends on the generator settings, so a fixed sea level
+//! floods a different share of every world. [`match_histogram`] warps the values of a grid so that their distribution
+//! follows a [`TargetHistogram`] instead, keeping their order. Designers can then ask for "30% water, 50% plains, 20%
+//! mountains" and get exactly those shares from any seed, while the shape of the terrain still comes from the noise.
+//!
+//! ```
+//! use perlin2d::histogram::TargetHistogram;
+//! use perlin2d::{NoiseSource, PerlinNoise2D, Region};
+//!
+//! // water below 0, plains up to 0.3 and mountains up to 1
+//! let target = TargetHistogram::new(vec![-1.0, 0.0, 0.3, 1.0], vec![0.3, 0.5, 0.2]);
+//!
+//! for seed in 0..3 {
+//!     let perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (64.0, 64.0), 0.0, seed);
+//!     let map = perlin.generate_map(Region::new((0.0, 0.0), (200.0, 200.0)), (100, 100));
+//!     let matched = map.match_histogram(&target);
+//!
+//!     let count = |lo: f64, hi: f64| matched.get_values().iter().filter(|&&v| v >= lo && v < hi).count();
+//!     assert_eq!(count(-1.0, 0.0), 3000);
+//!     assert_eq!(count(0.0, 0.3), 5000);
+//!     assert_eq!(count(0.3, 1.1), 2000);
+//! }
+//! ```
+
+use crate::NoiseMap;
+
+/// Distribution that [`match_histogram`] gives to the values of a grid
+///
+/// Member variables:
+///
+/// * `edges` - The ascending bin edges, one more than there are bins. Bin `k` covers `edges[k]..edges[k + 1]`.
+/// * `weights` - The share of values in every bin, normalized to sum to `1`. Values are spread evenly within a bin.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TargetHistogram {
+    edges: Vec<f64>,
+    weights: Vec<f64>,
+}
+
+impl TargetHistogram {
+    /// Create and return a new TargetHistogram object from bin edges and the relative weight of every bin
+    ///
+    /// Weights are relative, so `[3.0, 5.0, 2.0]` and `[0.3, 0.5, 0.2]` describe the same histogram.
+    ///
+    /// Panics if there is not exactly one more edge than weights, if the edges are not finite and ascending, or if a
+    /// weight is negative or not finite or all weights are zero.
+    pub fn new(edges: Vec<f64>, weights: Vec<f64>) -> Self {
+        assert!(!weights.is_empty(), "histogram needs at least one bin");
+        assert_eq!(
+            edges.len(),
+            weights.len() + 1,
+            "histogram needs one more edge than bins"
+        );
+        assert!(edges.iter().all(|e| e.is_finite()), "histogram edges must be finite");
+        assert!(
+            edges.windows(2).all(|e| e[0] <= e[1]),
+            "histogram edges must be ascending"
+        );
+        assert!(
+            weights.iter().all(|&w| w >= 0.0 && w.is_finite()),
+            "histogram weights must be non-negative and finite"
+        );
+        let sum: f64 = weights.iter().sum();
+        assert!(sum > 0.0 && sum.is_finite(), "histogram weights must not all be zero");
+        let weights = weights.iter().map(|w| w / sum).collect();
+        Self { edges, weights }
+    }
+
+    /// Create and return a new TargetHistogram object spreading values evenly from `min` to `max`
+    ///
+    /// Panics if `min` or `max` is not finite or `min > max`.
+    pub fn uniform(min: f64, max: f64) -> Self {
+        Self::new(vec![min, max], vec![1.0])
+    }
+
+    /// Getter function for edges
+    pub fn get_edges(&self) -> &[f64] {
+        &self.edges
+    }
+    /// Getter function for weights
+    pub fn get_weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    /// Value below which the share `q` of the target distribution lies, the inverse of its cumulative distribution
+    ///
+    /// `q` is clamped to `[0, 1]`.
+    pub fn quantile(&self, q: f64) -> f64 {
+        let q = q.clamp(0.0, 1.0);
+        let mut below = 0.0;
+        for (k, &weight) in self.weights.iter().enumerate() {
+            let last = k + 1 == self.weights.len();
+            if weight > 0.0 && (q <= below + weight || last) {
+                let t = ((q - below) / weight).clamp(0.0, 1.0);
+                return self.edges[k] + t * (self.edges[k + 1] - self.edges[k]);
+            }
+            below += weight;
+        }
+        self.edges[self.edges.len() - 1]
+    }
+}
+
+/// Warps values in place so that their distribution follows `target`, keeping their order
+///
+/// Every value is replaced by the target [`TargetHistogram::quantile`] at the middle of its rank, so the share of
+/// values in every bin matches the target up to rounding to whole values. Equal values are ranked by their index,
+/// which makes the result deterministic. NaNs are left unchanged and do not count towards the shares.
+pub fn match_histogram(values: &mut [f64], target: &TargetHistogram) {
+    let mut order: Vec<usize> = (0..values.len()).filter(|&i| !values[i].is_nan()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]).then(a.cmp(&b)));
+    let n = order.len() as f64;
+    for (rank, &index) in order.iter().enumerate() {
+        values[index] = target.quantile((rank as f64 + 0.5) / n);
+    }
+}
+
+impl NoiseMap {
+    /// Returns a copy of the map warped with [`match_histogram`]
+    pub fn match_histogram(&self, target: &TargetHistogram) -> NoiseMap {
+        let mut map = self.clone();
+        match_histogram(map.get_values_mut(), target);
+        map
+    }
+}
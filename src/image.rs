@@ -0,0 +1,73 @@
+//! PNG export of noise maps, enabled by the `image` feature.
+//!
+//! ```no_run
+//! use perlin2d::gradient::Gradient;
+//! use perlin2d::{image, PerlinNoise2D, Region};
+//!
+//! let perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (200.0, 200.0), 0.0, 101);
+//! let region = Region::new((0.0, 0.0), (1024.0, 1024.0));
+//! image::export_colored_png(&perlin, "world.png", region, (512, 512), &Gradient::terrain()).unwrap();
+//! ```
+
+use crate::gradient::Gradient;
+use crate::{NoiseMap, NoiseSource, Region};
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+/// Error returned when an image can not be written
+#[derive(Clone, Debug, PartialEq)]
+pub enum ImageError {
+    /// The file could not be created or written.
+    Io(String),
+    /// The image could not be encoded, for example because it is empty or too large.
+    Encode(String),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::Io(msg) => write!(f, "failed to write image: {}", msg),
+            ImageError::Encode(msg) => write!(f, "failed to encode image: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+/// Samples a source over a region and saves it as an RGB PNG colored by `gradient`
+///
+/// Row `0` of the sampled map is the top row of the image.
+pub fn export_colored_png<S: NoiseSource + ?Sized, P: AsRef<Path>>(
+    source: &S,
+    path: P,
+    region: Region,
+    resolution: (usize, usize),
+    gradient: &Gradient,
+) -> Result<(), ImageError> {
+    save_colored_png(&source.generate_map(region, resolution), path, gradient)
+}
+
+/// Saves a map as an RGB PNG colored by `gradient`
+pub fn save_colored_png<P: AsRef<Path>>(map: &NoiseMap, path: P, gradient: &Gradient) -> Result<(), ImageError> {
+    let (w, h) = map.get_resolution();
+    let width = u32::try_from(w).map_err(|e| ImageError::Encode(e.to_string()))?;
+    let height = u32::try_from(h).map_err(|e| ImageError::Encode(e.to_string()))?;
+    let file = File::create(path).map_err(|e| ImageError::Io(e.to_string()))?;
+
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(encode_error)?;
+    writer.write_image_data(&gradient.colorize(map)).map_err(encode_error)?;
+    writer.finish().map_err(encode_error)
+}
+
+fn encode_error(error: png::EncodingError) -> ImageError {
+    match error {
+        png::EncodingError::IoError(e) => ImageError::Io(e.to_string()),
+        e => ImageError::Encode(e.to_string()),
+    }
+}
@@ -0,0 +1,113 @@
+//! Ken Perlin's improved gradient noise, used by [`crate::AlgorithmVersion::ImprovedGradient`].
+
+use crate::hash::mix_seed;
+#[cfg(not(feature = "std"))]
+use crate::math::Float;
+use crate::Interpolation;
+
+/// Salt of the lattice keys derived from the seed
+const KEY_SALT: u64 = 0x2002_0000_5eed_0001;
+
+/// The permutation from Ken Perlin's 2002 reference implementation
+const PERMUTATION: [u8; 256] = [
+    151, 160, 137, 91, 90, 15, 131, 13, 201, 95, 96, 53, 194, 233, 7, 225, 140, 36, 103, 30, 69, 142, 8, 99, 37, 240,
+    21, 10, 23, 190, 6, 148, 247, 120, 234, 75, 0, 26, 197, 62, 94, 252, 219, 203, 117, 35, 11, 32, 57, 177, 33, 88,
+    237, 149, 56, 87, 174, 20, 125, 136, 171, 168, 68, 175, 74, 165, 71, 134, 139, 48, 27, 166, 77, 146, 158, 231, 83,
+    111, 229, 122, 60, 211, 133, 230, 220, 105, 92, 41, 55, 46, 245, 40, 244, 102, 143, 54, 65, 25, 63, 161, 1, 216,
+    80, 73, 209, 76, 132, 187, 208, 89, 18, 169, 200, 196, 135, 130, 116, 188, 159, 86, 164, 100, 109, 198, 173, 186,
+    3, 64, 52, 217, 226, 250, 124, 123, 5, 202, 38, 147, 118, 126, 255, 82, 85, 212, 207, 206, 59, 227, 47, 16, 58, 17,
+    182, 189, 28, 42, 223, 183, 170, 213, 119, 248, 152, 2, 44, 154, 163, 70, 221, 153, 101, 155, 167, 43, 172, 9, 129,
+    22, 39, 253, 19, 98, 108, 110, 79, 113, 224, 232, 178, 185, 112, 104, 218, 246, 97, 228, 251, 34, 242, 193, 238,
+    210, 144, 12, 191, 179, 162, 241, 81, 51, 145, 235, 249, 14, 239, 107, 49, 192, 214, 31, 181, 199, 106, 157, 184,
+    84, 204, 176, 115, 121, 50, 45, 127, 4, 150, 254, 138, 236, 205, 93, 222, 114, 67, 29, 24, 72, 243, 141, 128, 195,
+    78, 66, 215, 61, 156, 180,
+];
+
+#[inline]
+fn perm(i: i64) -> i64 {
+    PERMUTATION[(i & 255) as usize] as i64
+}
+
+/// Gradient of a lattice point, one of eight directions along the axes and diagonals
+#[inline]
+fn gradient(hash: i64) -> (f64, f64) {
+    match hash & 7 {
+        0 => (1.0, 1.0),
+        1 => (-1.0, 1.0),
+        2 => (1.0, -1.0),
+        3 => (-1.0, -1.0),
+        4 => (1.0, 0.0),
+        5 => (-1.0, 0.0),
+        6 => (0.0, 1.0),
+        _ => (0.0, -1.0),
+    }
+}
+
+/// Bytes XORed into the column, row and final permutation lookups, derived from the seed
+///
+/// The reference algorithm only has a single lattice, so seeding by an offset alone repeats every 256 seeds. Keying
+/// the lookups gives every seed one of `2^24` lattices.
+#[inline]
+fn keys(seed: i32) -> [i64; 3] {
+    let key = mix_seed(seed as i64 as u64, KEY_SALT);
+    [
+        (key & 255) as i64,
+        ((key >> 8) & 255) as i64,
+        ((key >> 16) & 255) as i64,
+    ]
+}
+
+/// Permutation hash of the lattice point `(i, j)`
+#[inline]
+fn lattice_hash(i: i64, j: i64, [ki, kj, kh]: [i64; 3]) -> i64 {
+    perm((perm(i ^ ki) + (j ^ kj)) ^ kh)
+}
+
+/// Gradients of the `[x0y0, x1y0, x0y1, x1y1]` corners of the cell containing `(x, y)`
+#[inline]
+fn corner_gradients(x: f64, y: f64, seed: i32) -> [(f64, f64); 4] {
+    let (i, j) = (f64::floor(x) as i64, f64::floor(y) as i64);
+    let keys = keys(seed);
+    [
+        gradient(lattice_hash(i, j, keys)),
+        gradient(lattice_hash(i.wrapping_add(1), j, keys)),
+        gradient(lattice_hash(i, j.wrapping_add(1), keys)),
+        gradient(lattice_hash(i.wrapping_add(1), j.wrapping_add(1), keys)),
+    ]
+}
+
+/// Gradient noise at `(x, y)` in `[-1, 1]` on the lattice of `seed`, with the cell blended by the given fade function
+pub(crate) fn value(x: f64, y: f64, seed: i32, interpolation: Interpolation) -> f64 {
+    value_and_derivative(x, y, seed, interpolation).0
+}
+
+/// Gradient noise at `(x, y)` together with its partial derivatives along `x` and `y`
+pub(crate) fn value_and_derivative(x: f64, y: f64, seed: i32, interpolation: Interpolation) -> (f64, f64, f64) {
+    let [g00, g10, g01, g11] = corner_gradients(x, y, seed);
+    let (u, v) = (x - f64::floor(x), y - f64::floor(y));
+    // contribution of each corner is the dot product of its gradient with the offset to the point
+    let d00 = g00.0 * u + g00.1 * v;
+    let d10 = g10.0 * (u - 1.0) + g10.1 * v;
+    let d01 = g01.0 * u + g01.1 * (v - 1.0);
+    let d11 = g11.0 * (u - 1.0) + g11.1 * (v - 1.0);
+
+    let (wu0, wu1) = (interpolation.fade(1.0 - u), interpolation.fade(u));
+    let (wv0, wv1) = (interpolation.fade(1.0 - v), interpolation.fade(v));
+    let (du0, du1) = (
+        -interpolation.fade_derivative(1.0 - u),
+        interpolation.fade_derivative(u),
+    );
+    let (dv0, dv1) = (
+        -interpolation.fade_derivative(1.0 - v),
+        interpolation.fade_derivative(v),
+    );
+
+    let value = wv0 * (wu0 * d00 + wu1 * d10) + wv1 * (wu0 * d01 + wu1 * d11);
+    let dx = wv0 * (du0 * d00 + du1 * d10 + wu0 * g00.0 + wu1 * g10.0)
+        + wv1 * (du0 * d01 + du1 * d11 + wu0 * g01.0 + wu1 * g11.0);
+    let dy = dv0 * (wu0 * d00 + wu1 * d10)
+        + dv1 * (wu0 * d01 + wu1 * d11)
+        + wv0 * (wu0 * g00.1 + wu1 * g10.1)
+        + wv1 * (wu0 * g01.1 + wu1 * g11.1);
+    (value, dx, dy)
+}
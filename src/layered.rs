@@ -0,0 +1,183 @@
+//! Octave caches for interactive parameter editing.
+
+use crate::{Affine, AlgorithmVersion, FractalMode, Interpolation, LatticeHasher, NoiseMap, PerlinNoise2D, Region};
+
+/// A map that keeps the raw values of every octave so parameter changes only recompute what they affect
+///
+/// Member variables:
+///
+/// * `perlin` - The generator the map currently shows.
+/// * `region` - The sampled area.
+/// * `resolution` - The `(width, height)` resolution of the map.
+/// * `layers` - The raw lattice values of every octave, with the frequency they were sampled at.
+/// * `sampling` - The parameters shared by all layers when they were sampled.
+/// * `map` - The combined output.
+///
+/// Changing `amplitude`, `bias`, `persistence` or `exponent`, or switching between `Fbm` and `HybridMultifractal`,
+/// only recombines the cached octaves. Adding octaves samples just the new ones, and changing `lacunarity` keeps the
+/// first octave. `SwissTurbulence` warps every octave by the ones before it, so it is always fully regenerated. The
+/// result is identical to generating the map from scratch.
+///
+/// Changes to the lattice hash are not detected, call [`LayeredMap::invalidate`] after one.
+///
+/// ```
+/// use perlin2d::layered::LayeredMap;
+/// use perlin2d::{NoiseSource, PerlinNoise2D, Region};
+///
+/// let mut perlin = PerlinNoise2D::new(8, 1.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 3);
+/// let region = Region::new((0.0, 0.0), (256.0, 256.0));
+/// let mut layered = LayeredMap::new(perlin, region, (128, 128));
+///
+/// perlin.set_persistence(0.6);
+/// assert_eq!(layered.update(perlin), 0);
+/// assert_eq!(layered.get_map(), &perlin.generate_map(region, (128, 128)));
+///
+/// perlin.set_octaves(10);
+/// assert_eq!(layered.update(perlin), 2);
+/// ```
+#[derive(Clone)]
+pub struct LayeredMap<H = crate::PrimeHasher> {
+    perlin: PerlinNoise2D<H>,
+    region: Region,
+    resolution: (usize, usize),
+    layers: Vec<((f64, f64), Vec<f64>)>,
+    sampling: Option<Sampling>,
+    map: NoiseMap,
+}
+
+/// Parameters that every octave layer depends on
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Sampling {
+    scale: (f64, f64),
+    transform: Option<Affine>,
+    seed: i32,
+    version: AlgorithmVersion,
+    interpolation: Interpolation,
+}
+
+impl<H: LatticeHasher + Clone> LayeredMap<H> {
+    /// Create and return a new LayeredMap object, sampling every octave of the generator
+    pub fn new(perlin: PerlinNoise2D<H>, region: Region, resolution: (usize, usize)) -> Self {
+        let mut layered = Self {
+            perlin: perlin.clone(),
+            region,
+            resolution,
+            layers: Vec::new(),
+            sampling: None,
+            map: NoiseMap::new(region, resolution),
+        };
+        layered.update(perlin);
+        layered
+    }
+
+    /// Getter function for the generator
+    pub fn get_perlin(&self) -> &PerlinNoise2D<H> {
+        &self.perlin
+    }
+    /// Getter function for region
+    pub fn get_region(&self) -> Region {
+        self.region
+    }
+    /// Getter function for resolution
+    pub fn get_resolution(&self) -> (usize, usize) {
+        self.resolution
+    }
+    /// Getter function for the combined map
+    pub fn get_map(&self) -> &NoiseMap {
+        &self.map
+    }
+
+    /// Drop every cached octave, so the next update samples all of them again
+    pub fn invalidate(&mut self) {
+        self.layers.clear();
+        self.sampling = None;
+    }
+
+    /// Switch to a new set of parameters and return the number of octaves that had to be sampled
+    pub fn update(&mut self, perlin: PerlinNoise2D<H>) -> usize {
+        self.perlin = perlin;
+        let octaves = self.perlin.octaves.max(0) as usize;
+
+        if let FractalMode::SwissTurbulence { .. } = self.perlin.mode {
+            self.invalidate();
+            self.map = crate::NoiseSource::generate_map(&self.perlin, self.region, self.resolution);
+            return octaves;
+        }
+
+        let perlin = &self.perlin;
+
+        let sampling = Sampling {
+            scale: perlin.scale,
+            transform: perlin.transform,
+            seed: perlin.seed,
+            version: perlin.version,
+            interpolation: perlin.interpolation,
+        };
+        if self.sampling != Some(sampling) {
+            self.layers.clear();
+            self.sampling = Some(sampling);
+        }
+
+        // unit space positions of every sample, shared by all octaves
+        let points: Vec<(f64, f64)> = (0..self.resolution.1)
+            .flat_map(|j| (0..self.resolution.0).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                let (x, y) = self.region.sample_point(self.resolution, i, j);
+                perlin.to_unit_space(x, y)
+            })
+            .collect();
+
+        let seed = perlin.seed as f64;
+        let mut sampled = 0;
+        let (mut freq_x, mut freq_y) = perlin.frequency;
+        self.layers.truncate(octaves);
+        for k in 0..octaves {
+            let fresh = match self.layers.get(k) {
+                Some(&(frequency, _)) => frequency != (freq_x, freq_y),
+                None => true,
+            };
+            if fresh {
+                let values = points
+                    .iter()
+                    .map(|&(x, y)| perlin.get_value(y * freq_y + seed, x * freq_x + seed, seed))
+                    .collect();
+                let layer = ((freq_x, freq_y), values);
+                if k < self.layers.len() {
+                    self.layers[k] = layer;
+                } else {
+                    self.layers.push(layer);
+                }
+                sampled += 1;
+            }
+            freq_x *= perlin.lacunarity;
+            freq_y *= perlin.lacunarity;
+        }
+
+        self.combine();
+        sampled
+    }
+
+    /// Combines the cached octaves in the same order and with the same arithmetic as the generator
+    fn combine(&mut self) {
+        let perlin = &self.perlin;
+        let layers = &self.layers;
+        for (index, out) in self.map.get_values_mut().iter_mut().enumerate() {
+            let mut t = 0.0;
+            let mut amp = 1.0;
+            let mut weight = 1.0;
+            for (_, values) in layers.iter() {
+                let value = values[index];
+                match perlin.mode {
+                    FractalMode::HybridMultifractal { offset } => {
+                        let signal = (value + offset) * amp;
+                        t += weight * signal;
+                        weight = f64::clamp(weight * signal, 0.0, 1.0);
+                    }
+                    _ => t += value * amp,
+                }
+                amp *= perlin.persistence;
+            }
+            *out = perlin.bias + perlin.get_effective_amplitude() * perlin.shape(t);
+        }
+    }
+}
@@ -11,8 +11,291 @@
 //!
 //! let noise = perlin_obj.get_noise(5.0, 10.0);
 //! ```
+//!
+//! The generator itself, its lattice hashes and transforms, and the `fixed` module also build without the standard
+//! library. Maps, sources, combinators and the other modules that allocate or use threads and files need the default
+//! `std` feature. Building with `default-features = false` gives a `no_std` crate, and adding the `fixed` feature
+//! gives integer only generation for targets without a floating point unit.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
+pub mod analysis;
+#[cfg(feature = "std")]
+pub mod arena;
+#[cfg(feature = "std")]
+pub mod chunk;
+#[cfg(feature = "std")]
+pub mod climate;
+#[cfg(feature = "std")]
+pub mod combine;
+#[cfg(feature = "std")]
+pub mod config;
+#[cfg(feature = "std")]
+pub mod coords;
+#[cfg(feature = "std")]
+pub mod dungeon;
+#[cfg(feature = "std")]
+pub mod export;
+#[cfg(feature = "std")]
+pub mod expr;
+#[cfg(feature = "std")]
+pub mod filter;
+#[cfg(feature = "fixed")]
+pub mod fixed;
+#[cfg(feature = "std")]
+pub mod flow;
+#[cfg(all(feature = "std", feature = "godot"))]
+pub mod godot;
+#[cfg(feature = "std")]
+pub mod gradient;
+#[cfg(feature = "std")]
+pub mod graph;
+pub mod hash;
+#[cfg(feature = "std")]
+pub mod histogram;
+#[cfg(all(feature = "std", feature = "image"))]
+pub mod image;
+mod improved;
+#[cfg(feature = "std")]
+pub mod layered;
+#[cfg(feature = "std")]
+pub mod map;
+#[cfg(not(feature = "std"))]
+mod math;
+#[cfg(feature = "std")]
+pub mod mips;
+#[cfg(all(feature = "std", feature = "rayon"))]
+pub mod parallel;
+#[cfg(feature = "std")]
+pub mod paths;
+#[cfg(feature = "std")]
+pub mod patterns;
+#[cfg(feature = "std")]
+pub mod physics;
+#[cfg(feature = "std")]
+pub mod placement;
+#[cfg(feature = "std")]
+pub mod point;
+#[cfg(feature = "std")]
+pub mod preview;
+#[cfg(feature = "std")]
+pub mod quadtree;
+#[cfg(all(feature = "std", feature = "rand"))]
+pub mod rng;
+#[cfg(feature = "std")]
+pub mod sampler;
+pub mod simd;
+#[cfg(feature = "std")]
+pub mod source;
+#[cfg(feature = "std")]
+pub mod stream;
+#[cfg(feature = "std")]
+pub mod terrain;
+#[cfg(feature = "std")]
+pub mod tiles;
+pub mod transform;
+#[cfg(feature = "std")]
+pub mod verify;
+#[cfg(feature = "std")]
+pub mod viewport;
+#[cfg(feature = "std")]
+pub mod voronoi;
+
+#[cfg(feature = "std")]
+pub use arena::GridArena;
+#[cfg(feature = "std")]
+pub use climate::{LatitudeGradient, LatitudeProfile};
+#[cfg(feature = "std")]
+pub use combine::{BlendedNoise, Bounded, DetailBlend, Masked};
+#[cfg(feature = "std")]
+pub use config::{ConfigError, PerlinConfig};
+#[cfg(feature = "std")]
+pub use coords::{CoordMap, RowOrder};
+pub use hash::{HashBackend, LatticeHasher, PrimeHasher};
+#[cfg(feature = "std")]
+pub use map::{BoundaryMode, NoiseMap, Region};
+#[cfg(feature = "std")]
+pub use point::NoisePoint;
+pub use simd::SimdBackend;
+#[cfg(feature = "std")]
+pub use source::NoiseSource;
+pub use transform::Affine;
+#[cfg(feature = "std")]
+pub use verify::VerifyError;
+#[cfg(feature = "std")]
+pub use viewport::Viewport;
+
+#[cfg(not(feature = "std"))]
+use math::Float;
+
+/// Version of the noise algorithm used by a generator
+///
+/// Generated values for a given seed and set of parameters are only guaranteed to stay identical across crate
+/// releases for the same version. Changes to the underlying math are introduced as new versions, so existing worlds
+/// keep their terrain until they opt in with [`PerlinNoise2D::set_version`].
+///
+/// * `V1` - The original algorithm. Lattice coordinates are 32 bit, so sampling further than about `2^31` lattice
+///   cells from the origin saturates and produces flat or repeating noise.
+/// * `V2` - Lattice coordinates are 64 bit. Output is identical to `V1` within the 32 bit range and does not
+///   saturate further out. The default [`PrimeHasher`] only depends on the low 31 bits of the lattice coordinates, so
+///   with it the noise repeats every `2^31` lattice cells along each axis, the same distance at which `V1` breaks
+///   down. Large worlds need [`HashBackend::Wy`] or [`HashBackend::Xx`], which mix all 64 bits and stay unique up to
+///   about `2^52` lattice cells from the origin, beyond which `f64` inputs can no longer resolve positions inside a
+///   cell.
+/// * `V3` - Same as `V2`, but lattice cells are found with `floor` instead of truncation toward zero, which matches
+///   how the position inside a cell is computed. Earlier versions mirror the lattice around `x = 0` and `y = 0`,
+///   producing a visible seam there; `V3` is continuous across the origin.
+/// * `ImprovedGradient` - Ken Perlin's 2002 improved noise: gradient noise from the classic permutation table, with
+///   lattice cells found as in `V3`. Its output is zero at every lattice point and it has the look users of other
+///   engines expect. The classic algorithm pairs it with `Interpolation::Quintic`. The lattice hasher is not used and
+///   the pattern repeats every 256 lattice cells. The seed keys the permutation lookups, so every seed draws one of
+///   `2^24` lattices rather than shifting the single reference lattice.
+///
+/// ```
+/// use perlin2d::{AlgorithmVersion, PerlinNoise2D};
+///
+/// let gradient = |seed| {
+///     let mut perlin = PerlinNoise2D::new(4, 1.0, 1.0, 0.5, 2.0, (10.0, 10.0), 0.0, seed);
+///     perlin.set_version(AlgorithmVersion::ImprovedGradient);
+///     perlin
+/// };
+/// // seeds 256 apart would only shift the reference lattice by whole periods
+/// let (a, b) = (gradient(0), gradient(256));
+/// assert!((0..100).any(|k| (a.get_noise(k as f64 * 1.37, 5.0) - b.get_noise(k as f64 * 1.37, 5.0)).abs() > 0.1));
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum AlgorithmVersion {
+    #[default]
+    V1,
+    V2,
+    V3,
+    ImprovedGradient,
+}
+
+impl AlgorithmVersion {
+    /// The most recent algorithm version
+    pub const LATEST: AlgorithmVersion = AlgorithmVersion::V3;
+}
+
+/// Fade function used to interpolate between lattice values
+///
+/// * `Cubic` - `3t^2 - 2t^3`, the original smoothstep curve. This is the default.
+/// * `Linear` - `t`, cheapest but shows the lattice grid.
+/// * `Cosine` - `(1 - cos(pi * t)) / 2`.
+/// * `Quintic` - `6t^5 - 15t^4 + 10t^3`, smoother at lattice points than `Cubic`.
+/// * `Custom` - A user supplied kernel. Values stay within the generator range as long as
+///   `f(0) = 0`, `f(1) = 1` and `f(t) + f(1 - t) = 1`.
+#[derive(Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Interpolation {
+    #[default]
+    Cubic,
+    Linear,
+    Cosine,
+    Quintic,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(fn(f64) -> f64),
+}
+
+impl Interpolation {
+    /// Returns the weight of the second value when interpolating at `t` in `[0, 1]`
+    #[inline]
+    pub fn fade(self, t: f64) -> f64 {
+        match self {
+            Interpolation::Cubic => {
+                let t_sqr: f64 = t * t;
+                3.0 * t_sqr - 2.0 * (t_sqr * t)
+            }
+            Interpolation::Linear => t,
+            Interpolation::Cosine => 0.5 * (1.0 - f64::cos(core::f64::consts::PI * t)),
+            Interpolation::Quintic => t * t * t * (t * (t * 6.0 - 15.0) + 10.0),
+            Interpolation::Custom(f) => f(t),
+        }
+    }
+
+    /// Returns the derivative of [`Interpolation::fade`] at `t`
+    ///
+    /// `Custom` kernels are differentiated numerically.
+    #[inline]
+    pub fn fade_derivative(self, t: f64) -> f64 {
+        match self {
+            Interpolation::Cubic => 6.0 * t * (1.0 - t),
+            Interpolation::Linear => 1.0,
+            Interpolation::Cosine => 0.5 * core::f64::consts::PI * f64::sin(core::f64::consts::PI * t),
+            Interpolation::Quintic => 30.0 * t * t * (t - 1.0) * (t - 1.0),
+            Interpolation::Custom(f) => {
+                const H: f64 = 1e-6;
+                (f(t + H) - f(t - H)) / (2.0 * H)
+            }
+        }
+    }
+}
+
+impl PartialEq for Interpolation {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Interpolation::Custom(a), Interpolation::Custom(b)) => *a as usize == *b as usize,
+            _ => core::mem::discriminant(self) == core::mem::discriminant(other),
+        }
+    }
+}
+
+/// How the octaves of a generator are combined
+///
+/// * `Fbm` - Fractional Brownian motion, the sum of all octaves scaled by `persistence^i`. This is the default.
+/// * `HybridMultifractal` - Musgrave's hybrid multifractal. Every octave is shifted by `offset` and weighted by the
+///   product of the octaves before it, clamped to `[0, 1]`, so low areas stay smooth while peaks keep gaining detail.
+///   An offset around `0.7` is a good starting point.
+/// * `SwissTurbulence` - Ridged octaves, `1 - |noise|`, whose sample positions are warped by `warp` times the summed
+///   derivatives of the octaves before them and whose gain is damped where the sum is low. Slopes become smooth and
+///   ridges sharp, which looks like eroded mountains without a separate erosion pass. A warp around `0.15` is a good
+///   starting point.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum FractalMode {
+    #[default]
+    Fbm,
+    HybridMultifractal {
+        offset: f64,
+    },
+    SwissTurbulence {
+        warp: f64,
+    },
+}
 
-use std::num::Wrapping;
+/// Parameters replaced for a single call of [`PerlinNoise2D::get_noise_with`]
+///
+/// * `amplitude` - Replaces the amplitude.
+/// * `bias` - Replaces the bias.
+/// * `frequency` - Replaces the frequency of the first octave along `x` and `y`.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NoiseOverrides {
+    pub amplitude: Option<f64>,
+    pub bias: Option<f64>,
+    pub frequency: Option<(f64, f64)>,
+}
+
+/// Arrangement of the values returned by [`PerlinNoise2D::generate_channels`]
+///
+/// * `Interleaved` - All channels of a point are stored next to each other, `[a0, b0, a1, b1, ...]`.
+/// * `Planar` - Each channel is stored as a complete row-major map, `[a0, a1, ..., b0, b1, ...]`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChannelLayout {
+    Interleaved,
+    Planar,
+}
+
+/// Lattice distance between the seeds of consecutive channels
+const CHANNEL_SEED_STRIDE: i32 = 7919;
+/// Lattice offsets of the hashes behind [`PerlinNoise2D::jitter`], far from the cells the octaves read near the origin
+const JITTER_X_OFFSET: i64 = 3_000_017;
+const JITTER_Y_OFFSET: i64 = 4_000_037;
 
 /// Perlin Noise struct
 ///
@@ -20,30 +303,62 @@ use std::num::Wrapping;
 ///
 /// * `octaves` - The amount of detail in Perlin noise.
 /// * `amplitude` - The maximum absolute value that the Perlin noise can output.
-/// * `frequeny` - The number of cycles per unit length that the Perlin noise outputs.
+/// * `frequeny` - The number of cycles per unit length that the Perlin noise outputs. Can be set per axis.
 /// * `persistence` - A multiplier that determines how quickly the amplitudes diminish for each successive octave in a Perlin-noise function.
 /// * `lacunarity` - A multiplier that determines how quickly the frequency increases for each successive octave in a Perlin-noise function.
 /// * `scale` - A Tuple. A number that determines at what distance to view the noisemap.
 /// * `seed` -  A value that changes the output of a coherent-noise function.
 /// * `bias` - Amount of change in Perlin noise. Used , for example, to make all Perlin noise values positive.
+/// * `version` - The algorithm version used to generate values. Defaults to `AlgorithmVersion::V1`.
+/// * `interpolation` - The fade function used between lattice values. Defaults to `Interpolation::Cubic`.
+/// * `mode` - How octaves are combined. Defaults to `FractalMode::Fbm`.
+/// * `exponent` - Power applied to the magnitude of the normalized octave sum, keeping its sign. Values above `1.0`
+///   sharpen peaks and flatten lowlands, values below `1.0` do the opposite. Defaults to `1.0`.
+/// * `transform` - Optional affine transform of input coordinates. When set it replaces `scale`.
+/// * `normalized` - Whether the amplitude is divided by the sum of the octave gains, so that the overall magnitude of
+///   the output stays the same when the octave count or persistence change. Defaults to `false`.
+/// * `hasher` - The hash that assigns a value to each lattice point. Defaults to [`PrimeHasher`].
 ///
 /// Additional Info:
 /// http://libnoise.sourceforge.net/glossary/
 #[derive(Copy, Clone)]
-pub struct PerlinNoise2D {
+pub struct PerlinNoise2D<H = PrimeHasher> {
     octaves: i32,
     amplitude: f64,
-    frequency: f64,
+    frequency: (f64, f64),
     persistence: f64,
     lacunarity: f64,
     scale: (f64, f64),
     bias: f64,
     seed: i32,
+    version: AlgorithmVersion,
+    interpolation: Interpolation,
+    mode: FractalMode,
+    exponent: f64,
+    transform: Option<Affine>,
+    normalized: bool,
+    hasher: H,
 }
 
 impl PerlinNoise2D {
     /// Create and return a new PerlinNoise2D object
-    pub fn new(
+    ///
+    /// `new` and the setters of every plain parameter are `const fn`, so a generator with fixed settings can be built
+    /// at compile time and kept in a `static`.
+    ///
+    /// ```
+    /// use perlin2d::{AlgorithmVersion, PerlinNoise2D};
+    ///
+    /// static TERRAIN: PerlinNoise2D = {
+    ///     let mut perlin = PerlinNoise2D::new(6, 10.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 101);
+    ///     perlin.set_version(AlgorithmVersion::V3);
+    ///     perlin
+    /// };
+    ///
+    /// let noise = TERRAIN.get_noise(5.0, 10.0);
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub const fn new(
         octaves: i32,
         amplitude: f64,
         frequency: f64,
@@ -54,6 +369,51 @@ impl PerlinNoise2D {
         seed: i32,
     ) -> Self {
         Self {
+            octaves,
+            amplitude,
+            frequency: (frequency, frequency),
+            persistence,
+            lacunarity,
+            scale,
+            bias,
+            seed,
+            version: AlgorithmVersion::V1,
+            interpolation: Interpolation::Cubic,
+            mode: FractalMode::Fbm,
+            exponent: 1.0,
+            transform: None,
+            normalized: false,
+            hasher: PrimeHasher,
+        }
+    }
+
+    /// Create a fractional Brownian motion generator from its Hurst exponent instead of a persistence
+    ///
+    /// The amplitude of every octave is `lacunarity^-hurst` times the previous one, so `hurst` in `(0, 1)` sets the
+    /// roughness as in the fractal terrain literature: `0.5` gives Brownian surfaces, values near `1.0` smooth ones
+    /// and values near `0.0` rough ones. The surface has a fractal dimension of `3 - hurst` and its power spectrum
+    /// falls off as `frequency^-(2 * hurst + 2)`.
+    ///
+    /// ```
+    /// use perlin2d::PerlinNoise2D;
+    ///
+    /// let perlin = PerlinNoise2D::from_hurst(6, 1.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 7);
+    /// assert!((perlin.get_persistence() - f64::powf(2.0, -0.5)).abs() < 1e-12);
+    /// assert!((perlin.get_hurst() - 0.5).abs() < 1e-12);
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_hurst(
+        octaves: i32,
+        amplitude: f64,
+        frequency: f64,
+        hurst: f64,
+        lacunarity: f64,
+        scale: (f64, f64),
+        bias: f64,
+        seed: i32,
+    ) -> Self {
+        let persistence = lacunarity.powf(-hurst);
+        Self::new(
             octaves,
             amplitude,
             frequency,
@@ -62,137 +422,806 @@ impl PerlinNoise2D {
             scale,
             bias,
             seed,
+        )
+    }
+}
+
+impl<H> PerlinNoise2D<H> {
+    /// Returns a copy of this generator that uses a different lattice hash
+    pub fn with_hasher<G: LatticeHasher>(self, hasher: G) -> PerlinNoise2D<G> {
+        PerlinNoise2D {
+            octaves: self.octaves,
+            amplitude: self.amplitude,
+            frequency: self.frequency,
+            persistence: self.persistence,
+            lacunarity: self.lacunarity,
+            scale: self.scale,
+            bias: self.bias,
+            seed: self.seed,
+            version: self.version,
+            interpolation: self.interpolation,
+            mode: self.mode,
+            exponent: self.exponent,
+            transform: self.transform,
+            normalized: self.normalized,
+            hasher,
         }
     }
 
     /// Getter function for octaves
-    pub fn get_octaves(&self) -> i32 {
+    pub const fn get_octaves(&self) -> i32 {
         self.octaves
     }
     //// Getter function for amplitude
-    pub fn get_amplitude(&self) -> f64 {
+    pub const fn get_amplitude(&self) -> f64 {
         self.amplitude
     }
-    /// Getter function for frequency
-    pub fn get_frequency(&self) -> f64 {
+    /// Getter function for frequency, returns the x axis frequency if the axes differ
+    pub const fn get_frequency(&self) -> f64 {
+        self.frequency.0
+    }
+    /// Getter function for the `(x, y)` per-axis frequency
+    pub const fn get_axis_frequency(&self) -> (f64, f64) {
         self.frequency
     }
     /// Getter function for persistence
-    pub fn get_persistence(&self) -> f64 {
+    pub const fn get_persistence(&self) -> f64 {
         self.persistence
     }
+    /// Hurst exponent matching the persistence and lacunarity, see [`PerlinNoise2D::from_hurst`]
+    pub fn get_hurst(&self) -> f64 {
+        -self.persistence.ln() / self.lacunarity.ln()
+    }
     /// Getter function for lacunarity
-    pub fn get_lacunarity(&self) -> f64 {
+    pub const fn get_lacunarity(&self) -> f64 {
         self.lacunarity
     }
     /// Getter function for scale
-    pub fn get_scale(&self) -> (f64, f64) {
+    pub const fn get_scale(&self) -> (f64, f64) {
         self.scale
     }
     /// Getter function for bias
-    pub fn get_bias(&self) -> f64 {
+    pub const fn get_bias(&self) -> f64 {
         self.bias
     }
     /// Getter function for seed
-    pub fn get_seed(&self) -> i32 {
+    pub const fn get_seed(&self) -> i32 {
         self.seed
     }
+    /// Getter function for version
+    pub const fn get_version(&self) -> AlgorithmVersion {
+        self.version
+    }
+    /// Getter function for interpolation
+    pub const fn get_interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
+    /// Getter function for mode
+    pub const fn get_mode(&self) -> FractalMode {
+        self.mode
+    }
+    /// Getter function for exponent
+    pub const fn get_exponent(&self) -> f64 {
+        self.exponent
+    }
+    /// Getter function for normalized
+    pub const fn get_normalized(&self) -> bool {
+        self.normalized
+    }
+    /// Getter function for transform
+    pub const fn get_transform(&self) -> Option<Affine> {
+        self.transform
+    }
+    /// Getter function for hasher
+    pub const fn get_hasher(&self) -> &H {
+        &self.hasher
+    }
 
     /// Setter function for octaves
-    pub fn set_octaves(&mut self, octaves: i32) {
+    pub const fn set_octaves(&mut self, octaves: i32) {
         self.octaves = octaves;
     }
     /// Setter function for amplitude
-    pub fn set_amplitude(&mut self, amplitude: f64) {
+    pub const fn set_amplitude(&mut self, amplitude: f64) {
         self.amplitude = amplitude;
     }
-    /// Setter function for frequency
-    pub fn set_frequency(&mut self, frequency: f64) {
+    /// Setter function for frequency, sets the same frequency on both axes
+    pub const fn set_frequency(&mut self, frequency: f64) {
+        self.frequency = (frequency, frequency);
+    }
+    /// Setter function for the `(x, y)` per-axis frequency
+    pub const fn set_axis_frequency(&mut self, frequency: (f64, f64)) {
         self.frequency = frequency;
     }
     /// Setter function for persistence
-    pub fn set_persistence(&mut self, persistence: f64) {
+    pub const fn set_persistence(&mut self, persistence: f64) {
         self.persistence = persistence;
     }
     /// Setter function for lacunarity
-    pub fn set_lacunarity(&mut self, lacunarity: f64) {
+    pub const fn set_lacunarity(&mut self, lacunarity: f64) {
         self.lacunarity = lacunarity;
     }
     /// Setter function for scale
-    pub fn set_scale(&mut self, scale: (f64, f64)) {
+    pub const fn set_scale(&mut self, scale: (f64, f64)) {
         self.scale = scale;
     }
     /// Setter function for bias
-    pub fn set_bias(&mut self, bias: f64) {
+    pub const fn set_bias(&mut self, bias: f64) {
         self.bias = bias;
     }
     /// Setter function for seed
-    pub fn set_seed(&mut self, seed: i32) {
+    pub const fn set_seed(&mut self, seed: i32) {
         self.seed = seed;
     }
+    /// Setter function for version
+    pub const fn set_version(&mut self, version: AlgorithmVersion) {
+        self.version = version;
+    }
+    /// Setter function for interpolation
+    pub const fn set_interpolation(&mut self, interpolation: Interpolation) {
+        self.interpolation = interpolation;
+    }
+    /// Setter function for mode
+    pub const fn set_mode(&mut self, mode: FractalMode) {
+        self.mode = mode;
+    }
+    /// Setter function for exponent
+    pub const fn set_exponent(&mut self, exponent: f64) {
+        self.exponent = exponent;
+    }
+    /// Setter function for normalized
+    ///
+    /// With normalization on, the amplitude is divided by the sum of the absolute octave gains
+    /// `sum(|persistence|^i)`. The octave sum of `Fbm` noise then stays within `±amplitude` whatever the number of
+    /// octaves and the persistence, so exploring those parameters no longer changes the overall magnitude.
+    ///
+    /// ```
+    /// use perlin2d::{NoiseSource, PerlinNoise2D};
+    ///
+    /// let mut coarse = PerlinNoise2D::new(2, 10.0, 0.5, 0.8, 2.0, (100.0, 100.0), 0.0, 101);
+    /// let mut fine = PerlinNoise2D::new(8, 10.0, 0.5, 0.8, 2.0, (100.0, 100.0), 0.0, 101);
+    /// assert!(fine.get_range().1 > 2.0 * coarse.get_range().1);
+    ///
+    /// coarse.set_normalized(true);
+    /// fine.set_normalized(true);
+    /// assert!((coarse.get_range().1 - 10.0).abs() < 1e-12);
+    /// assert!((fine.get_range().1 - 10.0).abs() < 1e-12);
+    /// ```
+    pub const fn set_normalized(&mut self, normalized: bool) {
+        self.normalized = normalized;
+    }
+    /// Setter function for transform, `None` goes back to dividing by `scale`
+    pub const fn set_transform(&mut self, transform: Option<Affine>) {
+        self.transform = transform;
+    }
+    /// Setter function for hasher
+    pub fn set_hasher(&mut self, hasher: H) {
+        self.hasher = hasher;
+    }
+}
 
+impl<H: Clone> PerlinNoise2D<H> {
+    /// Returns a copy of this generator with a sub-seed derived from its seed and `salt`
+    ///
+    /// Every salt gives an unrelated but reproducible seed, so one master generator can spawn independent generators
+    /// for separate features. Derivation can be chained to build a hierarchy.
+    ///
+    /// Derived seeds span the full 32 bit range, so two salts share a seed with a probability of `2^-32`. Among `n`
+    /// salts some pair collides with a probability of about `n^2 / 2^33`: one in ten thousand for a thousand salts,
+    /// and even odds only around 77,000 salts. Seeds far from zero shift `V1` lattice coordinates close to their 32
+    /// bit limit, so derived generators should use `V2` or later.
+    ///
+    /// ```
+    /// use perlin2d::PerlinNoise2D;
+    ///
+    /// const RIVERS: u64 = 1;
+    /// const ORE: u64 = 2;
+    ///
+    /// let world = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 1234);
+    /// let rivers = world.derive(RIVERS);
+    /// let ore = world.derive(ORE);
+    ///
+    /// assert_ne!(rivers.get_seed(), ore.get_seed());
+    /// assert_eq!(rivers.get_seed(), world.derive(RIVERS).get_seed());
+    /// assert_eq!(rivers.get_octaves(), world.get_octaves());
+    /// ```
+    pub fn derive(&self, salt: u64) -> Self {
+        let mut child = self.clone();
+        child.seed = (hash::mix_seed(self.seed as i64 as u64, salt) >> 32) as u32 as i32;
+        child
+    }
+}
+
+impl<H: LatticeHasher> PerlinNoise2D<H> {
     /// generates and returns 2D perlin noise
     pub fn get_noise(&self, x: f64, y: f64) -> f64 {
-        self.bias + self.amplitude * self.total(x / self.scale.0, y / self.scale.1)
+        let (x, y) = self.to_unit_space(x, y);
+        self.bias + self.get_effective_amplitude() * self.shape(self.total(x, y))
+    }
+
+    /// Generates noise at `(x, y)` with some parameters replaced for this call only
+    ///
+    /// Overrides left as `None` use the value of the generator, and the generator itself is not modified, so a
+    /// generator shared between threads can be evaluated with different settings without cloning it. With no
+    /// overrides the result is identical to [`PerlinNoise2D::get_noise`].
+    ///
+    /// ```
+    /// use perlin2d::{NoiseOverrides, PerlinNoise2D};
+    ///
+    /// let perlin = PerlinNoise2D::new(6, 10.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 101);
+    /// let overrides = NoiseOverrides {
+    ///     amplitude: Some(20.0),
+    ///     bias: Some(5.0),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let raw = perlin.get_noise(5.0, 10.0) / 10.0;
+    /// assert!((perlin.get_noise_with(5.0, 10.0, overrides) - (5.0 + 20.0 * raw)).abs() < 1e-12);
+    /// assert_eq!(perlin.get_noise_with(5.0, 10.0, NoiseOverrides::default()), perlin.get_noise(5.0, 10.0));
+    /// ```
+    pub fn get_noise_with(&self, x: f64, y: f64, overrides: NoiseOverrides) -> f64 {
+        let (x, y) = self.to_unit_space(x, y);
+        let frequency = overrides.frequency.unwrap_or(self.frequency);
+        let total = self.total_at_frequency(x, y, self.seed as f64, frequency);
+        let amplitude = overrides.amplitude.unwrap_or(self.amplitude) * self.amplitude_scale();
+        overrides.bias.unwrap_or(self.bias) + amplitude * self.shape(total)
+    }
+
+    /// Returns a stable pseudo random offset for the integer cell `(ix, iy)`
+    ///
+    /// Both components lie in `[-magnitude, magnitude]` and are drawn from the lattice hash of the generator, so they
+    /// only depend on the cell, the seed and the hasher. Offsetting one feature per cell by its jitter places trees,
+    /// villages and similar features with natural variation, identically on every run and every machine.
+    ///
+    /// ```
+    /// use perlin2d::PerlinNoise2D;
+    ///
+    /// let perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 101);
+    /// let cell_size = 32.0;
+    /// let (dx, dy) = perlin.jitter(4, -7, 0.4 * cell_size);
+    /// let village = (4.5 * cell_size + dx, -6.5 * cell_size + dy);
+    ///
+    /// assert!(dx.abs() <= 0.4 * cell_size && dy.abs() <= 0.4 * cell_size);
+    /// assert_eq!(perlin.jitter(4, -7, 0.4 * cell_size), (dx, dy));
+    /// assert_ne!(perlin.jitter(5, -7, 0.4 * cell_size), (dx, dy));
+    /// ```
+    pub fn jitter(&self, ix: i64, iy: i64, magnitude: f64) -> (f64, f64) {
+        let seed = self.seed as i64;
+        let (i, j) = (ix.wrapping_add(seed), iy.wrapping_add(seed));
+        (
+            magnitude * self.hasher.hash(i.wrapping_add(JITTER_X_OFFSET), j),
+            magnitude * self.hasher.hash(i, j.wrapping_add(JITTER_Y_OFFSET)),
+        )
+    }
+
+    /// Returns the raw octave sum at `(x, y)`
+    ///
+    /// Coordinates are used as given, without dividing by the scale or applying the transform, and the exponent,
+    /// amplitude and bias are skipped. Wrappers doing their own normalization can use this instead of undoing the
+    /// post-processing of [`PerlinNoise2D::get_noise`]. In `Fbm` mode the result lies within
+    /// `±sum(|persistence|^i)` over the octaves.
+    ///
+    /// ```
+    /// use perlin2d::PerlinNoise2D;
+    ///
+    /// let perlin = PerlinNoise2D::new(6, 10.0, 0.5, 0.5, 2.0, (100.0, 100.0), 3.0, 101);
+    /// let raw = perlin.get_noise_raw(5.0 / 100.0, 10.0 / 100.0);
+    /// assert_eq!(perlin.get_noise(5.0, 10.0), 3.0 + 10.0 * raw);
+    /// ```
+    pub fn get_noise_raw(&self, x: f64, y: f64) -> f64 {
+        self.total(x, y)
+    }
+
+    /// The amplitude applied to the shaped octave sum, divided by the sum of the octave gains when normalized
+    pub fn get_effective_amplitude(&self) -> f64 {
+        self.amplitude * self.amplitude_scale()
+    }
+
+    /// Factor applied to the amplitude, `1` unless normalized
+    #[inline]
+    fn amplitude_scale(&self) -> f64 {
+        if !self.normalized {
+            return 1.0;
+        }
+        let mut gain: f64 = 1.0;
+        let mut sum = 0.0;
+        for _ in 0..self.octaves {
+            sum += gain.abs();
+            gain *= self.persistence;
+        }
+        if sum > 0.0 {
+            1.0 / sum
+        } else {
+            1.0
+        }
+    }
+
+    /// Applies the exponent to an octave sum normalized by the largest sum the octaves can reach
+    #[inline]
+    fn shape(&self, t: f64) -> f64 {
+        if self.exponent == 1.0 {
+            return t;
+        }
+        let (lo, hi) = self.octave_range();
+        let norm = f64::max(lo.abs(), hi.abs());
+        if norm == 0.0 {
+            return t;
+        }
+        f64::copysign(f64::abs(t / norm).powf(self.exponent), t) * norm
+    }
+
+    /// Derivative of [`PerlinNoise2D::shape`] with respect to the octave sum
+    #[inline]
+    fn shape_derivative(&self, t: f64) -> f64 {
+        if self.exponent == 1.0 {
+            return 1.0;
+        }
+        let (lo, hi) = self.octave_range();
+        let norm = f64::max(lo.abs(), hi.abs());
+        if norm == 0.0 {
+            return 1.0;
+        }
+        self.exponent * f64::abs(t / norm).powf(self.exponent - 1.0)
+    }
+
+    /// Bounds of the octave sum before amplitude and bias are applied
+    pub(crate) fn octave_range(&self) -> (f64, f64) {
+        let mut gain: f64 = 1.0;
+        let (mut lo, mut hi) = (0.0, 0.0);
+        for i in 0..self.octaves {
+            match self.mode {
+                FractalMode::Fbm => {
+                    // every octave contributes a lattice value in [-1, 1] scaled by the octave gain
+                    lo -= gain.abs();
+                    hi += gain.abs();
+                }
+                FractalMode::HybridMultifractal { offset } => {
+                    let (a, b) = (gain * (offset - 1.0), gain * (offset + 1.0));
+                    let (a, b) = (f64::min(a, b), f64::max(a, b));
+                    // the first octave has a weight of one, later weights lie in [0, 1]
+                    if i == 0 {
+                        lo += a;
+                        hi += b;
+                    } else {
+                        lo += f64::min(a, 0.0);
+                        hi += f64::max(b, 0.0);
+                    }
+                }
+                FractalMode::SwissTurbulence { .. } => {
+                    // ridged octaves lie in [0, 1], the damping keeps the magnitude of the gain at most `gain`
+                    if i == 0 {
+                        hi += 1.0;
+                    } else {
+                        hi += gain.abs();
+                        if self.persistence < 0.0 {
+                            lo -= gain.abs();
+                        }
+                    }
+                }
+            }
+            gain *= self.persistence;
+        }
+        (lo, hi)
+    }
+
+    /// Maps input coordinates with the transform, or divides them by the scale when there is none
+    #[inline]
+    fn to_unit_space(&self, x: f64, y: f64) -> (f64, f64) {
+        match self.transform {
+            Some(transform) => transform.apply(x, y),
+            None => (x / self.scale.0, y / self.scale.1),
+        }
+    }
+
+    /// Samples the region once for every seed in `seeds` and returns one map per seed, in the same order
+    ///
+    /// The sample positions and, in `Fbm` mode, the scaled coordinates of every octave are computed once per point and
+    /// shared by all seeds, instead of once per seed as with separate passes. Each map is identical to the one generated after
+    /// setting that seed.
+    ///
+    /// ```
+    /// use perlin2d::{NoiseSource, PerlinNoise2D, Region};
+    ///
+    /// let mut perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (16.0, 16.0), 0.0, 0);
+    /// let region = Region::new((0.0, 0.0), (64.0, 64.0));
+    /// let maps = perlin.generate_ensemble(region, (32, 32), &[1, 2, 3]);
+    ///
+    /// perlin.set_seed(2);
+    /// assert_eq!(maps[1], perlin.generate_map(region, (32, 32)));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn generate_ensemble(&self, region: Region, resolution: (usize, usize), seeds: &[i32]) -> Vec<NoiseMap> {
+        let mut maps: Vec<NoiseMap> = seeds.iter().map(|_| NoiseMap::new(region, resolution)).collect();
+        self.sample_seeds(region, resolution, seeds, |i, j, values| {
+            for (map, &value) in maps.iter_mut().zip(values) {
+                map.set_value(i, j, value);
+            }
+        });
+        maps
+    }
+
+    /// Returns the seed of channel `channel` of [`PerlinNoise2D::generate_channels`]
+    ///
+    /// Channel `0` uses the seed of the generator, later channels are offset far enough apart on the lattice that they
+    /// share no cells near the origin.
+    pub fn get_channel_seed(&self, channel: usize) -> i32 {
+        self.seed
+            .wrapping_add((channel as i32).wrapping_mul(CHANNEL_SEED_STRIDE))
+    }
+
+    /// Samples `channels` decorrelated channels of the region in one pass, for example height, moisture and temperature
+    ///
+    /// Channel `k` is the noise generated with [`PerlinNoise2D::get_channel_seed`]`(k)`, so channel `0` matches
+    /// [`NoiseSource::generate_map`]. The work per point is shared as in [`PerlinNoise2D::generate_ensemble`]. The
+    /// returned buffer holds `width * height * channels` values arranged according to `layout`.
+    ///
+    /// ```
+    /// use perlin2d::{ChannelLayout, NoiseSource, PerlinNoise2D, Region};
+    ///
+    /// let mut perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (16.0, 16.0), 0.0, 5);
+    /// let region = Region::new((0.0, 0.0), (64.0, 64.0));
+    /// let interleaved = perlin.generate_channels(region, (32, 32), 3, ChannelLayout::Interleaved);
+    /// let planar = perlin.generate_channels(region, (32, 32), 3, ChannelLayout::Planar);
+    ///
+    /// // the value of channel 2 at column 4, row 7
+    /// let index = 7 * 32 + 4;
+    /// assert_eq!(interleaved[index * 3 + 2], planar[2 * 32 * 32 + index]);
+    ///
+    /// perlin.set_seed(perlin.get_channel_seed(2));
+    /// assert_eq!(perlin.generate_map(region, (32, 32)).get_value(4, 7), planar[2 * 32 * 32 + index]);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn generate_channels(
+        &self,
+        region: Region,
+        resolution: (usize, usize),
+        channels: usize,
+        layout: ChannelLayout,
+    ) -> Vec<f64> {
+        let seeds: Vec<i32> = (0..channels).map(|k| self.get_channel_seed(k)).collect();
+        let (width, height) = resolution;
+        let mut out = vec![0.0; width * height * channels];
+        self.sample_seeds(region, resolution, &seeds, |i, j, values| {
+            let index = j * width + i;
+            match layout {
+                ChannelLayout::Interleaved => {
+                    out[index * channels..(index + 1) * channels].copy_from_slice(values);
+                }
+                ChannelLayout::Planar => {
+                    for (k, &value) in values.iter().enumerate() {
+                        out[k * width * height + index] = value;
+                    }
+                }
+            }
+        });
+        out
+    }
+
+    /// Returns the noise at `(x, y)` together with its partial derivatives along `x` and `y`
+    ///
+    /// The value is identical to [`PerlinNoise2D::get_noise`]. In `Fbm` and `HybridMultifractal` mode the derivatives
+    /// are analytic and computed alongside the value from the same lattice corners. `SwissTurbulence` warps every
+    /// octave by the derivatives of the ones before it, so its derivatives are estimated with central differences.
+    ///
+    /// ```
+    /// use perlin2d::PerlinNoise2D;
+    ///
+    /// let perlin = PerlinNoise2D::new(6, 10.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 101);
+    /// let (h, dx, dy) = perlin.get_noise_and_gradient(5.0, 10.0);
+    /// assert_eq!(h, perlin.get_noise(5.0, 10.0));
+    ///
+    /// let e = 1e-4;
+    /// let estimate = (perlin.get_noise(5.0 + e, 10.0) - perlin.get_noise(5.0 - e, 10.0)) / (2.0 * e);
+    /// assert!((dx - estimate).abs() < 1e-5);
+    /// ```
+    pub fn get_noise_and_gradient(&self, x: f64, y: f64) -> (f64, f64, f64) {
+        let (u, v) = self.to_unit_space(x, y);
+        let (t, dt_du, dt_dv) = self.total_and_derivative(u, v);
+        let slope = self.get_effective_amplitude() * self.shape_derivative(t);
+        // chain rule through the mapping into unit space
+        let m = match self.transform {
+            Some(transform) => transform.matrix,
+            None => [[1.0 / self.scale.0, 0.0], [0.0, 1.0 / self.scale.1]],
+        };
+        (
+            self.bias + self.get_effective_amplitude() * self.shape(t),
+            slope * (dt_du * m[0][0] + dt_dv * m[1][0]),
+            slope * (dt_du * m[0][1] + dt_dv * m[1][1]),
+        )
+    }
+
+    /// Samples the height and its partial derivatives along `x` and `y` at every point of the region in one pass
+    ///
+    /// Every output is laid out like [`NoiseSource::fill_region`], the values of `out_h` are identical to it.
+    ///
+    /// Panics if an output does not match the resolution.
+    ///
+    /// ```
+    /// use perlin2d::{NoiseSource, PerlinNoise2D, Region};
+    ///
+    /// let perlin = PerlinNoise2D::new(6, 10.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 101);
+    /// let region = Region::new((0.0, 0.0), (256.0, 256.0));
+    /// let (mut h, mut dx, mut dy) = (vec![0.0; 64 * 64], vec![0.0; 64 * 64], vec![0.0; 64 * 64]);
+    /// perlin.fill_noise_and_gradient(region, (64, 64), &mut h, &mut dx, &mut dy);
+    ///
+    /// assert_eq!(h, perlin.generate_map(region, (64, 64)).get_values());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn fill_noise_and_gradient(
+        &self,
+        region: Region,
+        resolution: (usize, usize),
+        out_h: &mut [f64],
+        out_dx: &mut [f64],
+        out_dy: &mut [f64],
+    ) {
+        let len = resolution.0 * resolution.1;
+        assert_eq!(out_h.len(), len, "height output does not match the resolution");
+        assert_eq!(out_dx.len(), len, "x derivative output does not match the resolution");
+        assert_eq!(out_dy.len(), len, "y derivative output does not match the resolution");
+        for j in 0..resolution.1 {
+            for i in 0..resolution.0 {
+                let (x, y) = region.sample_point(resolution, i, j);
+                let index = j * resolution.0 + i;
+                let (h, dx, dy) = self.get_noise_and_gradient(x, y);
+                out_h[index] = h;
+                out_dx[index] = dx;
+                out_dy[index] = dy;
+            }
+        }
+    }
+
+    /// Calls `emit` with the column, row and the value for every seed at each sample point of the region, row by row
+    #[cfg(feature = "std")]
+    fn sample_seeds<F: FnMut(usize, usize, &[f64])>(
+        &self,
+        region: Region,
+        resolution: (usize, usize),
+        seeds: &[i32],
+        mut emit: F,
+    ) {
+        let mut totals = vec![0.0; seeds.len()];
+        for j in 0..resolution.1 {
+            for i in 0..resolution.0 {
+                let (x, y) = region.sample_point(resolution, i, j);
+                let (x, y) = self.to_unit_space(x, y);
+                if self.mode != FractalMode::Fbm {
+                    for (t, &seed) in totals.iter_mut().zip(seeds) {
+                        *t = self.total_seeded(x, y, seed as f64);
+                    }
+                } else {
+                    let mut amp = 1.0;
+                    let (mut freq_x, mut freq_y) = self.frequency;
+                    totals.iter_mut().for_each(|t| *t = 0.0);
+
+                    for _ in 0..self.octaves {
+                        let (sx, sy) = (x * freq_x, y * freq_y);
+                        for (t, &seed) in totals.iter_mut().zip(seeds) {
+                            *t += self.get_value(sy + seed as f64, sx + seed as f64, seed as f64) * amp;
+                        }
+                        amp *= self.persistence;
+                        freq_x *= self.lacunarity;
+                        freq_y *= self.lacunarity;
+                    }
+                }
+                for t in totals.iter_mut() {
+                    *t = self.bias + self.get_effective_amplitude() * self.shape(*t);
+                }
+                emit(i, j, &totals);
+            }
+        }
     }
 
     fn total(&self, x: f64, y: f64) -> f64 {
+        self.total_seeded(x, y, self.seed as f64)
+    }
+
+    fn total_seeded(&self, x: f64, y: f64, seed: f64) -> f64 {
+        self.total_at_frequency(x, y, seed, self.frequency)
+    }
+
+    #[inline]
+    fn total_at_frequency(&self, x: f64, y: f64, seed: f64, frequency: (f64, f64)) -> f64 {
         let mut t = 0.0;
         let mut amp = 1.0;
-        let mut freq = self.frequency;
+        let (mut freq_x, mut freq_y) = frequency;
 
-        for _ in 0..self.octaves {
-            t += self.get_value(y * freq + self.seed as f64, x * freq + self.seed as f64) * amp;
-            amp *= self.persistence;
-            freq *= self.lacunarity;
+        match self.mode {
+            FractalMode::Fbm => {
+                for _ in 0..self.octaves {
+                    t += self.get_value(y * freq_y + seed, x * freq_x + seed, seed) * amp;
+                    amp *= self.persistence;
+                    freq_x *= self.lacunarity;
+                    freq_y *= self.lacunarity;
+                }
+            }
+            FractalMode::HybridMultifractal { offset } => {
+                let mut weight = 1.0;
+                for _ in 0..self.octaves {
+                    let signal = (self.get_value(y * freq_y + seed, x * freq_x + seed, seed) + offset) * amp;
+                    t += weight * signal;
+                    weight = f64::clamp(weight * signal, 0.0, 1.0);
+                    amp *= self.persistence;
+                    freq_x *= self.lacunarity;
+                    freq_y *= self.lacunarity;
+                }
+            }
+            FractalMode::SwissTurbulence { warp } => {
+                let (mut dx, mut dy) = (0.0, 0.0);
+                for _ in 0..self.octaves {
+                    let (wx, wy) = (x + warp * dx, y + warp * dy);
+                    // the lattice is sampled with swapped axes, so its derivatives come back as (d/dy, d/dx)
+                    let (n, n_dy, n_dx) = self.get_value_and_derivative(wy * freq_y + seed, wx * freq_x + seed, seed);
+                    t += amp * (1.0 - n.abs());
+                    dx -= amp * n_dx * n;
+                    dy -= amp * n_dy * n;
+                    amp *= self.persistence * f64::clamp(t, 0.0, 1.0);
+                    freq_x *= self.lacunarity;
+                    freq_y *= self.lacunarity;
+                }
+            }
         }
         t
     }
 
+    /// The octave sum at `(x, y)` in unit space together with its partial derivatives along `x` and `y`
+    fn total_and_derivative(&self, x: f64, y: f64) -> (f64, f64, f64) {
+        let seed = self.seed as f64;
+        let mut t = 0.0;
+        let (mut dt_dx, mut dt_dy) = (0.0, 0.0);
+        let mut amp = 1.0;
+        let (mut freq_x, mut freq_y) = self.frequency;
+
+        match self.mode {
+            FractalMode::Fbm => {
+                for _ in 0..self.octaves {
+                    // the lattice is sampled with swapped axes, so its derivatives come back as (d/dy, d/dx)
+                    let (n, n_dy, n_dx) = self.get_value_and_derivative(y * freq_y + seed, x * freq_x + seed, seed);
+                    t += n * amp;
+                    dt_dx += n_dx * freq_x * amp;
+                    dt_dy += n_dy * freq_y * amp;
+                    amp *= self.persistence;
+                    freq_x *= self.lacunarity;
+                    freq_y *= self.lacunarity;
+                }
+            }
+            FractalMode::HybridMultifractal { offset } => {
+                let mut weight = 1.0;
+                let (mut weight_dx, mut weight_dy) = (0.0, 0.0);
+                for _ in 0..self.octaves {
+                    let (n, n_dy, n_dx) = self.get_value_and_derivative(y * freq_y + seed, x * freq_x + seed, seed);
+                    let signal = (n + offset) * amp;
+                    let (signal_dx, signal_dy) = (n_dx * freq_x * amp, n_dy * freq_y * amp);
+                    t += weight * signal;
+                    dt_dx += weight_dx * signal + weight * signal_dx;
+                    dt_dy += weight_dy * signal + weight * signal_dy;
+                    let next = weight * signal;
+                    if next > 0.0 && next < 1.0 {
+                        weight_dx = weight_dx * signal + weight * signal_dx;
+                        weight_dy = weight_dy * signal + weight * signal_dy;
+                    } else {
+                        weight_dx = 0.0;
+                        weight_dy = 0.0;
+                    }
+                    weight = f64::clamp(next, 0.0, 1.0);
+                    amp *= self.persistence;
+                    freq_x *= self.lacunarity;
+                    freq_y *= self.lacunarity;
+                }
+            }
+            FractalMode::SwissTurbulence { .. } => {
+                t = self.total(x, y);
+                // a step well below the cell size of the finest octave
+                let finest = self.lacunarity.abs().max(1.0).powi(self.octaves.max(1) - 1);
+                let (hx, hy) = (
+                    1e-6 / (self.frequency.0.abs() * finest).max(1e-12),
+                    1e-6 / (self.frequency.1.abs() * finest).max(1e-12),
+                );
+                dt_dx = (self.total(x + hx, y) - self.total(x - hx, y)) / (2.0 * hx);
+                dt_dy = (self.total(x, y + hy) - self.total(x, y - hy)) / (2.0 * hy);
+            }
+        }
+        (t, dt_dx, dt_dy)
+    }
+
     fn interpolate(&self, x: f64, y: f64, a: f64) -> f64 {
-        let neg_a: f64 = 1.0 - a;
-        let neg_a_sqr: f64 = neg_a * neg_a;
-        let fac1: f64 = 3.0 * (neg_a_sqr) - 2.0 * (neg_a_sqr * neg_a);
-        let a_sqr: f64 = a * a;
-        let fac2: f64 = 3.0 * a_sqr - 2.0 * (a_sqr * a);
+        let fac1: f64 = self.interpolation.fade(1.0 - a);
+        let fac2: f64 = self.interpolation.fade(a);
 
         x * fac1 + y * fac2 // add the weighted factors
     }
 
-    fn noise(&self, x: i32, y: i32) -> f64 {
-        let mut n: i64 = x as i64 + y as i64 * 57;
-        n = (n << 13) ^ n;
-        let t = Wrapping(n) * Wrapping(n) * Wrapping(n * 15731 + 789221) + Wrapping(1376312589);
-        let t = t.0 & 0x7fffffff;
-        1.0 - (t as f64) * 0.931322574615478515625e-9
+    /// Lattice coordinates of the four cells around `v` along one axis, `[v - 1, v, v + 1, v + 2]`
+    #[inline]
+    fn lattice(&self, v: f64) -> [i64; 4] {
+        match self.version {
+            AlgorithmVersion::V1 => {
+                let i = v as i32;
+                [
+                    i.wrapping_sub(1) as i64,
+                    i as i64,
+                    i.wrapping_add(1) as i64,
+                    i.wrapping_add(2) as i64,
+                ]
+            }
+            AlgorithmVersion::V2 => {
+                let i = v as i64;
+                [i.wrapping_sub(1), i, i.wrapping_add(1), i.wrapping_add(2)]
+            }
+            AlgorithmVersion::V3 | AlgorithmVersion::ImprovedGradient => {
+                let i = f64::floor(v) as i64;
+                [i.wrapping_sub(1), i, i.wrapping_add(1), i.wrapping_add(2)]
+            }
+        }
     }
 
-    fn get_value(&self, x: f64, y: f64) -> f64 {
-        let x_int: i32 = x as i32;
-        let y_int: i32 = y as i32;
+    /// Lattice value at `(x, y)`, which already includes the seed offset; `seed` only keys the improved gradient lattice
+    fn get_value(&self, x: f64, y: f64, seed: f64) -> f64 {
+        if self.version == AlgorithmVersion::ImprovedGradient {
+            return improved::value(x, y, seed as i32, self.interpolation);
+        }
         let x_frac: f64 = x - f64::floor(x);
         let y_frac: f64 = y - f64::floor(y);
+        self.interpolate_corners(self.corners(x, y), x_frac, y_frac)
+    }
 
-        // noise values
-        let n01: f64 = self.noise(x_int - 1, y_int - 1);
-        let n02: f64 = self.noise(x_int + 1, y_int - 1);
-        let n03: f64 = self.noise(x_int - 1, y_int + 1);
-        let n04: f64 = self.noise(x_int + 1, y_int + 1);
-        let n05: f64 = self.noise(x_int - 1, y_int);
-        let n06: f64 = self.noise(x_int + 1, y_int);
-        let n07: f64 = self.noise(x_int, y_int - 1);
-        let n08: f64 = self.noise(x_int, y_int + 1);
-        let n09: f64 = self.noise(x_int, y_int);
+    /// Interpolates the `[x0y0, x1y0, x0y1, x1y1]` corner values at the given fractions of a cell
+    #[inline]
+    fn interpolate_corners(&self, corners: [f64; 4], x_frac: f64, y_frac: f64) -> f64 {
+        let [x0y0, x1y0, x0y1, x1y1] = corners;
 
-        let n12: f64 = self.noise(x_int + 2, y_int - 1);
-        let n14: f64 = self.noise(x_int + 2, y_int + 1);
-        let n16: f64 = self.noise(x_int + 2, y_int);
+        // interpolate between those values according to the x and y fractions
+        let v1: f64 = self.interpolate(x0y0, x1y0, x_frac); // interpolate in x
+                                                            // direction (y)
+        let v2: f64 = self.interpolate(x0y1, x1y1, x_frac); // interpolate in x
+                                                            // direction (y+1)
+        let fin: f64 = self.interpolate(v1, v2, y_frac); // interpolate in y direction
+
+        fin
+    }
+
+    /// Returns the lattice value at `(x, y)` together with its partial derivatives along `x` and `y`
+    fn get_value_and_derivative(&self, x: f64, y: f64, seed: f64) -> (f64, f64, f64) {
+        if self.version == AlgorithmVersion::ImprovedGradient {
+            return improved::value_and_derivative(x, y, seed as i32, self.interpolation);
+        }
+        let x_frac: f64 = x - f64::floor(x);
+        let y_frac: f64 = y - f64::floor(y);
+        let [x0y0, x1y0, x0y1, x1y1] = self.corners(x, y);
+        let slope = |a: f64, b: f64, t: f64| {
+            -a * self.interpolation.fade_derivative(1.0 - t) + b * self.interpolation.fade_derivative(t)
+        };
+
+        let v1 = self.interpolate(x0y0, x1y0, x_frac);
+        let v2 = self.interpolate(x0y1, x1y1, x_frac);
+        let dx = self.interpolate(slope(x0y0, x1y0, x_frac), slope(x0y1, x1y1, x_frac), y_frac);
+        (self.interpolate(v1, v2, y_frac), dx, slope(v1, v2, y_frac))
+    }
 
-        let n23: f64 = self.noise(x_int - 1, y_int + 2);
-        let n24: f64 = self.noise(x_int + 1, y_int + 2);
-        let n28: f64 = self.noise(x_int, y_int + 2);
+    /// Smoothed values of the four lattice corners around `(x, y)`: `[x0y0, x1y0, x0y1, x1y1]`
+    #[inline]
+    fn corners(&self, x: f64, y: f64) -> [f64; 4] {
+        // noise values of the 4x4 lattice neighborhood, block[j][i] is at offset (i - 1, j - 1)
+        let block = self.hasher.hash_block(self.lattice(x), self.lattice(y));
+        let n01: f64 = block[0][0];
+        let n02: f64 = block[0][2];
+        let n03: f64 = block[2][0];
+        let n04: f64 = block[2][2];
+        let n05: f64 = block[1][0];
+        let n06: f64 = block[1][2];
+        let n07: f64 = block[0][1];
+        let n08: f64 = block[2][1];
+        let n09: f64 = block[1][1];
 
-        let n34: f64 = self.noise(x_int + 2, y_int + 2);
+        let n12: f64 = block[0][3];
+        let n14: f64 = block[2][3];
+        let n16: f64 = block[1][3];
+
+        let n23: f64 = block[3][0];
+        let n24: f64 = block[3][2];
+        let n28: f64 = block[3][1];
+
+        let n34: f64 = block[3][3];
 
         // find the noise values of the four corners
         let x0y0: f64 = 0.0625 * (n01 + n02 + n03 + n04) + 0.125 * (n05 + n06 + n07 + n08) + 0.25 * (n09);
@@ -200,13 +1229,6 @@ impl PerlinNoise2D {
         let x0y1: f64 = 0.0625 * (n05 + n06 + n23 + n24) + 0.125 * (n03 + n04 + n09 + n28) + 0.25 * (n08);
         let x1y1: f64 = 0.0625 * (n09 + n16 + n28 + n34) + 0.125 * (n08 + n14 + n06 + n24) + 0.25 * (n04);
 
-        // interpolate between those values according to the x and y fractions
-        let v1: f64 = self.interpolate(x0y0, x1y0, x_frac); // interpolate in x
-                                                            // direction (y)
-        let v2: f64 = self.interpolate(x0y1, x1y1, x_frac); // interpolate in x
-                                                            // direction (y+1)
-        let fin: f64 = self.interpolate(v1, v2, y_frac); // interpolate in y direction
-
-        return fin;
+        [x0y0, x1y0, x0y1, x1y1]
     }
 }
@@ -0,0 +1,298 @@
+//! Rectangular regions of the noise plane and grids of values sampled over them.
+
+use crate::CoordMap;
+
+/// An axis aligned rectangle of the noise plane
+///
+/// A region sampled at a resolution of `(width, height)` is split into `width * height` equally sized cells and
+/// sampled at the minimum corner of each cell. The maximum edge is excluded, so neighboring regions that share an
+/// edge tile without duplicated samples.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Region {
+    pub min: (f64, f64),
+    pub max: (f64, f64),
+}
+
+impl Region {
+    /// Create and return a new Region object spanning `min` to `max`
+    pub fn new(min: (f64, f64), max: (f64, f64)) -> Self {
+        Self { min, max }
+    }
+
+    /// Width of the region along the x axis
+    pub fn width(&self) -> f64 {
+        self.max.0 - self.min.0
+    }
+    /// Height of the region along the y axis
+    pub fn height(&self) -> f64 {
+        self.max.1 - self.min.1
+    }
+
+    /// Distance between neighboring samples along each axis at the given resolution
+    pub fn step(&self, resolution: (usize, usize)) -> (f64, f64) {
+        (self.width() / resolution.0 as f64, self.height() / resolution.1 as f64)
+    }
+
+    /// Brings a point outside the region back inside it according to `mode`
+    ///
+    /// Points inside the region are returned unchanged.
+    pub fn apply_boundary(&self, mode: BoundaryMode, x: f64, y: f64) -> (f64, f64) {
+        (
+            mode.fold(x, self.min.0, self.width()),
+            mode.fold(y, self.min.1, self.height()),
+        )
+    }
+
+    /// Position of the sample in column `i` and row `j` at the given resolution
+    pub fn sample_point(&self, resolution: (usize, usize), i: usize, j: usize) -> (f64, f64) {
+        self.coord_map(resolution).grid_to_world(i as f64, j as f64)
+    }
+
+    /// Layout of the samples of the region at the given resolution
+    pub fn coord_map(&self, resolution: (usize, usize)) -> CoordMap {
+        CoordMap::from_region(*self, resolution)
+    }
+}
+
+/// How positions outside a region are brought back inside it
+///
+/// * `Repeat` - The region tiles the plane, so the output repeats with a period of its width and height.
+/// * `Mirror` - The region is reflected at every edge, so neighboring copies meet seamlessly.
+/// * `Clamp` - Positions are moved to the nearest edge, extending the border values outwards.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BoundaryMode {
+    Repeat,
+    Mirror,
+    Clamp,
+}
+
+impl BoundaryMode {
+    /// Maps `t` into `[min, min + length]`
+    fn fold(self, t: f64, min: f64, length: f64) -> f64 {
+        if length == 0.0 || !length.is_finite() {
+            return min;
+        }
+        let (lo, length) = if length < 0.0 {
+            (min + length, -length)
+        } else {
+            (min, length)
+        };
+        let offset = t - lo;
+        let folded = match self {
+            BoundaryMode::Repeat => offset.rem_euclid(length),
+            BoundaryMode::Mirror => {
+                let r = offset.rem_euclid(2.0 * length);
+                if r > length {
+                    2.0 * length - r
+                } else {
+                    r
+                }
+            }
+            BoundaryMode::Clamp => offset.max(0.0).min(length),
+        };
+        lo + folded
+    }
+
+    /// Maps the index `i` into `0..len`, returning `None` when `len` is zero
+    pub(crate) fn fold_index(self, i: isize, len: usize) -> Option<usize> {
+        if len == 0 {
+            return None;
+        }
+        let n = len as isize;
+        let i = match self {
+            BoundaryMode::Repeat => i.rem_euclid(n),
+            BoundaryMode::Mirror => {
+                // the edge sample is repeated, as in a mirrored texture
+                let r = i.rem_euclid(2 * n);
+                if r >= n {
+                    2 * n - 1 - r
+                } else {
+                    r
+                }
+            }
+            BoundaryMode::Clamp => i.max(0).min(n - 1),
+        };
+        Some(i as usize)
+    }
+}
+
+/// A grid of noise values sampled over a [`Region`]
+///
+/// Values are stored row-major: the value in column `i` and row `j` is at index `j * width + i`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NoiseMap {
+    region: Region,
+    width: usize,
+    height: usize,
+    values: Vec<f64>,
+}
+
+impl NoiseMap {
+    /// Create and return a new NoiseMap object filled with zeros
+    pub fn new(region: Region, resolution: (usize, usize)) -> Self {
+        Self::from_values(region, resolution, vec![0.0; resolution.0 * resolution.1])
+    }
+
+    /// Create a NoiseMap from row-major values
+    ///
+    /// Panics if `values` does not hold exactly `resolution.0 * resolution.1` elements.
+    pub fn from_values(region: Region, resolution: (usize, usize), values: Vec<f64>) -> Self {
+        assert_eq!(
+            values.len(),
+            resolution.0 * resolution.1,
+            "value count does not match the resolution"
+        );
+        Self {
+            region,
+            width: resolution.0,
+            height: resolution.1,
+            values,
+        }
+    }
+
+    /// Getter function for region
+    pub fn get_region(&self) -> Region {
+        self.region
+    }
+    /// Getter function for width
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+    /// Getter function for height
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+    /// Getter function for the `(width, height)` resolution
+    pub fn get_resolution(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+    /// Getter function for the row-major values
+    pub fn get_values(&self) -> &[f64] {
+        &self.values
+    }
+    /// Mutable access to the row-major values
+    pub fn get_values_mut(&mut self) -> &mut [f64] {
+        &mut self.values
+    }
+    /// Consume the map and return the row-major values
+    pub fn into_values(self) -> Vec<f64> {
+        self.values
+    }
+
+    /// Returns the value in column `i` and row `j`
+    pub fn get_value(&self, i: usize, j: usize) -> f64 {
+        self.values[j * self.width + i]
+    }
+    /// Returns the value in column `i` and row `j`, bringing indices outside the map back inside according to `mode`
+    ///
+    /// Panics if the map is empty.
+    pub fn get_value_bounded(&self, i: isize, j: isize, mode: BoundaryMode) -> f64 {
+        let i = mode.fold_index(i, self.width).expect("map is empty");
+        let j = mode.fold_index(j, self.height).expect("map is empty");
+        self.get_value(i, j)
+    }
+    /// Sets the value in column `i` and row `j`
+    pub fn set_value(&mut self, i: usize, j: usize, value: f64) {
+        self.values[j * self.width + i] = value;
+    }
+
+    /// Position in the noise plane of the value in column `i` and row `j`
+    pub fn get_position(&self, i: usize, j: usize) -> (f64, f64) {
+        self.region.sample_point(self.get_resolution(), i, j)
+    }
+    /// Returns the layout of the samples of this map
+    pub fn get_coord_map(&self) -> CoordMap {
+        self.region.coord_map(self.get_resolution())
+    }
+
+    /// Whether `other` covers the same region at the same resolution, so its values line up with the ones of this map
+    pub fn same_extent(&self, other: &NoiseMap) -> bool {
+        self.region == other.region && self.get_resolution() == other.get_resolution()
+    }
+
+    /// Returns the map between this one at `t = 0` and `other` at `t = 1`, interpolated linearly value by value
+    ///
+    /// `t` is not clamped, values outside `[0, 1]` extrapolate. Morphing from the map of one parameter set to the map
+    /// of another over time gives smooth terrain transitions.
+    ///
+    /// Panics if the maps do not have the same extent, see [`NoiseMap::same_extent`].
+    ///
+    /// ```
+    /// use perlin2d::{NoiseSource, PerlinNoise2D, Region};
+    ///
+    /// let region = Region::new((0.0, 0.0), (128.0, 128.0));
+    /// let calm = PerlinNoise2D::new(6, 10.0, 0.5, 0.4, 2.0, (64.0, 64.0), 0.0, 5).generate_map(region, (64, 64));
+    /// let rough = PerlinNoise2D::new(6, 30.0, 0.5, 0.6, 2.0, (64.0, 64.0), 0.0, 5).generate_map(region, (64, 64));
+    ///
+    /// assert_eq!(calm.lerp(&rough, 0.0), calm);
+    /// assert_eq!(calm.lerp(&rough, 1.0), rough);
+    ///
+    /// let change = calm.diff(&rough);
+    /// let halfway = calm.lerp(&rough, 0.5);
+    /// assert!((halfway.get_value(3, 4) - (calm.get_value(3, 4) + 0.5 * change.get_value(3, 4))).abs() < 1e-9);
+    /// ```
+    pub fn lerp(&self, other: &NoiseMap, t: f64) -> NoiseMap {
+        self.zip_with(other, |a, b| {
+            // exact at both ends
+            if t == 1.0 {
+                b
+            } else {
+                a + (b - a) * t
+            }
+        })
+    }
+
+    /// Returns `other - self` value by value, the change from this map to `other`
+    ///
+    /// Panics if the maps do not have the same extent, see [`NoiseMap::same_extent`].
+    pub fn diff(&self, other: &NoiseMap) -> NoiseMap {
+        self.zip_with(other, |a, b| b - a)
+    }
+
+    fn zip_with<F: Fn(f64, f64) -> f64>(&self, other: &NoiseMap, f: F) -> NoiseMap {
+        assert!(
+            self.same_extent(other),
+            "maps do not cover the same region at the same resolution"
+        );
+        let values = self
+            .values
+            .iter()
+            .zip(other.values.iter())
+            .map(|(&a, &b)| f(a, b))
+            .collect();
+        NoiseMap::from_values(self.region, self.get_resolution(), values)
+    }
+
+    /// Returns a stable 64 bit checksum of the resolution and values
+    ///
+    /// The digest is FNV-1a over the little-endian bits of every value, so it is the same on every platform and
+    /// changes whenever any value changes. `-0.0` is hashed as `0.0` and all NaNs alike.
+    pub fn digest(&self) -> u64 {
+        let mut hash = FNV_OFFSET;
+        let mut feed = |bits: u64| {
+            for byte in bits.to_le_bytes().iter() {
+                hash ^= u64::from(*byte);
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        };
+        feed(self.width as u64);
+        feed(self.height as u64);
+        for &value in self.values.iter() {
+            let value = if value == 0.0 {
+                0.0
+            } else if value.is_nan() {
+                f64::NAN
+            } else {
+                value
+            };
+            feed(value.to_bits());
+        }
+        hash
+    }
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
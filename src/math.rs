@@ -0,0 +1,163 @@
+//! Floating point functions for builds without the standard library.
+//!
+//! `core` does not provide `floor`, `powf`, `sin` and the other functions that need a math library. Without the `std`
+//! feature, [`Float`] supplies portable implementations under the same names as the methods of `f64`, so the generator
+//! reads the same in both builds. They are accurate to a few units in the last place, so `no_std` output can differ
+//! from `std` output in the last bits.
+
+use core::f64::consts::{FRAC_PI_2, LN_2, SQRT_2};
+
+/// The math library functions of `f64` that the generator uses
+pub(crate) trait Float: Sized {
+    fn floor(self) -> Self;
+    fn powi(self, n: i32) -> Self;
+    fn powf(self, n: Self) -> Self;
+    fn ln(self) -> Self;
+    fn exp(self) -> Self;
+    fn sin_cos(self) -> (Self, Self);
+    fn sin(self) -> Self {
+        self.sin_cos().0
+    }
+    fn cos(self) -> Self {
+        self.sin_cos().1
+    }
+}
+
+impl Float for f64 {
+    fn floor(self) -> f64 {
+        // every f64 of at least 2^52 in magnitude is already an integer, written so that NaN is returned as well
+        let fraction = self.abs() < 4_503_599_627_370_496.0;
+        if !fraction {
+            return self;
+        }
+        let t = self as i64 as f64;
+        if t > self {
+            t - 1.0
+        } else {
+            t
+        }
+    }
+
+    fn powi(self, n: i32) -> f64 {
+        let mut base = if n < 0 { 1.0 / self } else { self };
+        let mut n = n.unsigned_abs();
+        let mut result = 1.0;
+        while n > 0 {
+            if n & 1 == 1 {
+                result *= base;
+            }
+            base *= base;
+            n >>= 1;
+        }
+        result
+    }
+
+    fn powf(self, n: f64) -> f64 {
+        if n == 0.0 || self == 1.0 {
+            return 1.0;
+        }
+        if self.is_nan() || n.is_nan() {
+            return f64::NAN;
+        }
+        let integer = n.floor() == n;
+        if self < 0.0 {
+            if !integer {
+                return f64::NAN;
+            }
+            let magnitude = (-self).powf(n);
+            let odd = (n * 0.5).floor() != n * 0.5;
+            return if odd { -magnitude } else { magnitude };
+        }
+        if self == 0.0 {
+            return if n > 0.0 { 0.0 } else { f64::INFINITY };
+        }
+        (n * self.ln()).exp()
+    }
+
+    fn ln(self) -> f64 {
+        if self.is_nan() || self < 0.0 {
+            return f64::NAN;
+        }
+        if self == 0.0 {
+            return f64::NEG_INFINITY;
+        }
+        if self.is_infinite() {
+            return self;
+        }
+        // split into m * 2^e with m in [sqrt(1/2), sqrt(2))
+        let (mut m, mut e) = (self, 0i32);
+        if m < f64::MIN_POSITIVE {
+            m *= 18_014_398_509_481_984.0;
+            e -= 54;
+        }
+        let bits = m.to_bits();
+        e += ((bits >> 52) & 0x7ff) as i32 - 1023;
+        m = f64::from_bits((bits & 0x000f_ffff_ffff_ffff) | 0x3ff0_0000_0000_0000);
+        if m > SQRT_2 {
+            m *= 0.5;
+            e += 1;
+        }
+        // ln(m) = 2 atanh(s) with s = (m - 1) / (m + 1), |s| < 0.172
+        let s = (m - 1.0) / (m + 1.0);
+        let s2 = s * s;
+        let mut term = s;
+        let mut sum = 0.0;
+        let mut k = 1.0;
+        while k < 40.0 {
+            sum += term / k;
+            term *= s2;
+            k += 2.0;
+        }
+        2.0 * sum + e as f64 * LN_2
+    }
+
+    fn exp(self) -> f64 {
+        if self.is_nan() {
+            return self;
+        }
+        if self > 709.8 {
+            return f64::INFINITY;
+        }
+        if self < -745.2 {
+            return 0.0;
+        }
+        // exp(x) = 2^k * exp(r) with |r| <= ln(2) / 2
+        let k = (self / LN_2 + 0.5).floor();
+        let r = self - k * LN_2;
+        let mut term = 1.0;
+        let mut sum = 1.0;
+        for i in 1..20 {
+            term *= r / i as f64;
+            sum += term;
+        }
+        // apply 2^k in two steps so that neither factor overflows
+        let k = k as i32;
+        let half = k / 2;
+        sum * 2.0f64.powi(half) * 2.0f64.powi(k - half)
+    }
+
+    fn sin_cos(self) -> (f64, f64) {
+        if !self.is_finite() {
+            return (f64::NAN, f64::NAN);
+        }
+        // reduce to r in [-pi / 4, pi / 4] and the quadrant of the angle
+        let quadrant = (self / FRAC_PI_2 + 0.5).floor();
+        let r = self - quadrant * FRAC_PI_2;
+        let r2 = r * r;
+        let (mut sin, mut cos) = (0.0, 0.0);
+        let (mut s, mut c) = (r, 1.0);
+        for i in 0..12 {
+            sin += s;
+            cos += c;
+            let n = 2.0 * i as f64;
+            s *= -r2 / ((n + 2.0) * (n + 3.0));
+            c *= -r2 / ((n + 1.0) * (n + 2.0));
+        }
+        match ((quadrant % 4.0 + 4.0) % 4.0) as u8 {
+            0 => (sin, cos),
+            1 => (cos, -sin),
+            2 => (-sin, -cos),
+            _ => (-cos, sin),
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! Downsampled copies of noise maps for level of detail.
+//!
+//! ```
+//! use perlin2d::mips::MipFilter;
+//! use perlin2d::{NoiseSource, PerlinNoise2D, Region};
+//!
+//! let perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (50.0, 50.0), 0.0, 3);
+//! let map = perlin.generate_map(Region::new((0.0, 0.0), (256.0, 256.0)), (256, 256));
+//! let mips = map.build_mips(MipFilter::Box);
+//!
+//! assert_eq!(mips.len(), 9);
+//! assert_eq!(mips.get_level(3).get_resolution(), (32, 32));
+//!
+//! // level 0 reproduces the map at its sample positions, higher levels are smoother
+//! assert_eq!(mips.sample_lod(10.0, 20.0, 0.0), map.get_value(10, 20));
+//!
+//! // every level reproduces its values at the centers of the blocks of samples they cover
+//! let level = mips.get_level(2);
+//! for (i, j) in [(0, 0), (2, 5), (17, 40)] {
+//!     let (x, y) = (4.0 * i as f64 + 1.5, 4.0 * j as f64 + 1.5);
+//!     assert_eq!(mips.sample_lod(x, y, 2.0), level.get_value(i, j));
+//! }
+//!
+//! // fractional levels blend the two levels around them
+//! let blurry = mips.sample_lod(10.0, 20.0, 2.5);
+//! let (a, b) = (mips.sample_lod(10.0, 20.0, 2.0), mips.sample_lod(10.0, 20.0, 3.0));
+//! assert!(blurry >= f64::min(a, b) && blurry <= f64::max(a, b));
+//! assert!((blurry - 0.5 * (a + b)).abs() < 1e-12);
+//! ```
+
+use crate::{NoiseMap, Region};
+
+/// The filter used to downsample each level of a [`MipChain`]
+///
+/// * `Box` - Averages each 2x2 block. Fastest, but lets some high frequencies through.
+/// * `Gaussian` - A 4x4 binomial kernel centered on each 2x2 block. Smoother levels with less aliasing.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MipFilter {
+    Box,
+    Gaussian,
+}
+
+/// A chain of successively halved copies of a map
+///
+/// Member variables:
+///
+/// * `levels` - Level `0` is the original map, every following level has half the resolution of the previous one,
+///   rounded up, down to `1x1`. All levels cover the same region.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MipChain {
+    levels: Vec<NoiseMap>,
+}
+
+impl NoiseMap {
+    /// Builds the mip chain of the map with the given filter
+    pub fn build_mips(&self, filter: MipFilter) -> MipChain {
+        let mut levels = vec![self.clone()];
+        loop {
+            let last = &levels[levels.len() - 1];
+            let (w, h) = last.get_resolution();
+            if w <= 1 && h <= 1 {
+                break;
+            }
+            let next = downsample(last, filter);
+            levels.push(next);
+        }
+        MipChain { levels }
+    }
+}
+
+impl MipChain {
+    /// Getter function for levels
+    pub fn get_levels(&self) -> &[NoiseMap] {
+        &self.levels
+    }
+    /// Returns the map of one level, panics if the level does not exist
+    pub fn get_level(&self, level: usize) -> &NoiseMap {
+        &self.levels[level]
+    }
+    /// Number of levels including the original map
+    pub fn len(&self) -> usize {
+        self.levels.len()
+    }
+    /// Returns true if the chain holds no levels, which never happens as level `0` is always present
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+    /// Getter function for the region covered by every level
+    pub fn get_region(&self) -> Region {
+        self.levels[0].get_region()
+    }
+
+    /// Returns the filtered value at a point of the noise plane and a fractional level of detail
+    ///
+    /// Both neighboring levels are interpolated bilinearly and blended by the fraction of `lod`, which is clamped to
+    /// the levels of the chain. Each value of a level is placed at the center of the samples of level `0` it covers,
+    /// and positions outside the map use the nearest edge. Returns `0.0` for a chain of an empty map.
+    pub fn sample_lod(&self, x: f64, y: f64, lod: f64) -> f64 {
+        let (w, h) = self.levels[0].get_resolution();
+        if w == 0 || h == 0 {
+            return 0.0;
+        }
+        let region = self.get_region();
+        let (dx, dy) = region.step((w, h));
+        // position in samples of level 0
+        let u = if dx != 0.0 { (x - region.min.0) / dx } else { 0.0 };
+        let v = if dy != 0.0 { (y - region.min.1) / dy } else { 0.0 };
+
+        let lod = lod.max(0.0).min((self.levels.len() - 1) as f64);
+        let lower = lod.floor() as usize;
+        let a = bilinear(&self.levels[lower], lower, u, v);
+        let t = lod - lower as f64;
+        if t == 0.0 {
+            return a;
+        }
+        let b = bilinear(&self.levels[lower + 1], lower + 1, u, v);
+        a + (b - a) * t
+    }
+}
+
+/// Interpolates a level at the position `(u, v)` given in samples of level `0`
+fn bilinear(map: &NoiseMap, level: usize, u: f64, v: f64) -> f64 {
+    let size = (1u64 << level.min(63)) as f64;
+    let offset = 0.5 * (size - 1.0);
+    let (w, h) = map.get_resolution();
+    let u = ((u - offset) / size).max(0.0).min((w - 1) as f64);
+    let v = ((v - offset) / size).max(0.0).min((h - 1) as f64);
+    let (i, j) = (u.floor() as usize, v.floor() as usize);
+    let (i1, j1) = ((i + 1).min(w - 1), (j + 1).min(h - 1));
+    let (tx, ty) = (u - i as f64, v - j as f64);
+    let top = map.get_value(i, j) + (map.get_value(i1, j) - map.get_value(i, j)) * tx;
+    let bottom = map.get_value(i, j1) + (map.get_value(i1, j1) - map.get_value(i, j1)) * tx;
+    top + (bottom - top) * ty
+}
+
+fn downsample(map: &NoiseMap, filter: MipFilter) -> NoiseMap {
+    let (w, h) = map.get_resolution();
+    let (nw, nh) = (w.div_ceil(2), h.div_ceil(2));
+    let at = |i: isize, j: isize| {
+        let i = i.max(0).min(w as isize - 1) as usize;
+        let j = j.max(0).min(h as isize - 1) as usize;
+        map.get_value(i, j)
+    };
+    let mut values = Vec::with_capacity(nw * nh);
+    for j in 0..nh as isize {
+        for i in 0..nw as isize {
+            let value = match filter {
+                MipFilter::Box => {
+                    0.25 * (at(2 * i, 2 * j) + at(2 * i + 1, 2 * j) + at(2 * i, 2 * j + 1) + at(2 * i + 1, 2 * j + 1))
+                }
+                MipFilter::Gaussian => {
+                    const WEIGHTS: [f64; 4] = [0.125, 0.375, 0.375, 0.125];
+                    let mut sum = 0.0;
+                    for (b, wy) in WEIGHTS.iter().enumerate() {
+                        for (a, wx) in WEIGHTS.iter().enumerate() {
+                            sum += wx * wy * at(2 * i + a as isize - 1, 2 * j + b as isize - 1);
+                        }
+                    }
+                    sum
+                }
+            };
+            values.push(value);
+        }
+    }
+    NoiseMap::from_values(map.get_region(), (nw, nh), values)
+}
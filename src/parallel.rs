@@ -0,0 +1,113 @@
+//! Adapters for sampling noise inside rayon parallel iterators, enabled by the `rayon` feature.
+//!
+//! ```
+//! use perlin2d::parallel::ParallelNoiseExt;
+//! use perlin2d::PerlinNoise2D;
+//! use rayon::prelude::*;
+//!
+//! let perlin = PerlinNoise2D::new(6, 10.0, 0.5, 1.0, 2.0, (100.0, 100.0), 0.5, 101);
+//! let points = vec![(5.0, 10.0), (20.0, -3.5), (7.25, 7.25)];
+//!
+//! let values: Vec<f64> = points.par_iter().map_noise(&perlin).collect();
+//! ```
+
+use crate::{NoiseSource, Region};
+use rayon::iter::plumbing::{Consumer, ProducerCallback, UnindexedConsumer};
+use rayon::prelude::*;
+use std::borrow::Borrow;
+
+/// Extension trait that samples a noise source at every point of a parallel iterator
+pub trait ParallelNoiseExt: ParallelIterator + Sized
+where
+    Self::Item: Borrow<(f64, f64)>,
+{
+    /// Replace every `(x, y)` point with the value of `source` at that point
+    ///
+    /// The result is indexed whenever the input is, so it can be zipped, enumerated or collected in order.
+    fn map_noise<S: NoiseSource + Sync>(self, source: &S) -> MapNoise<'_, Self, S> {
+        MapNoise { base: self, source }
+    }
+}
+
+impl<I> ParallelNoiseExt for I
+where
+    I: ParallelIterator,
+    I::Item: Borrow<(f64, f64)>,
+{
+}
+
+/// Parallel iterator returned by [`ParallelNoiseExt::map_noise`]
+#[derive(Clone, Debug)]
+pub struct MapNoise<'a, I, S> {
+    base: I,
+    source: &'a S,
+}
+
+impl<I, S> ParallelIterator for MapNoise<'_, I, S>
+where
+    I: ParallelIterator,
+    I::Item: Borrow<(f64, f64)>,
+    S: NoiseSource + Sync,
+{
+    type Item = f64;
+
+    fn drive_unindexed<C: UnindexedConsumer<f64>>(self, consumer: C) -> C::Result {
+        let source = self.source;
+        self.base
+            .map(move |point| {
+                let (x, y) = *point.borrow();
+                source.get_noise(x, y)
+            })
+            .drive_unindexed(consumer)
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        self.base.opt_len()
+    }
+}
+
+impl<I, S> IndexedParallelIterator for MapNoise<'_, I, S>
+where
+    I: IndexedParallelIterator,
+    I::Item: Borrow<(f64, f64)>,
+    S: NoiseSource + Sync,
+{
+    fn len(&self) -> usize {
+        self.base.len()
+    }
+
+    fn drive<C: Consumer<f64>>(self, consumer: C) -> C::Result {
+        let source = self.source;
+        self.base
+            .map(move |point| {
+                let (x, y) = *point.borrow();
+                source.get_noise(x, y)
+            })
+            .drive(consumer)
+    }
+
+    fn with_producer<CB: ProducerCallback<f64>>(self, callback: CB) -> CB::Output {
+        let source = self.source;
+        self.base
+            .map(move |point| {
+                let (x, y) = *point.borrow();
+                source.get_noise(x, y)
+            })
+            .with_producer(callback)
+    }
+}
+
+/// Returns an indexed parallel iterator over the values of `source` in a region, in row-major order
+///
+/// Sample positions match [`NoiseSource::fill_region`] at the same resolution.
+pub fn par_region<S: NoiseSource + Sync>(
+    source: &S,
+    region: Region,
+    resolution: (usize, usize),
+) -> impl IndexedParallelIterator<Item = f64> + '_ {
+    let width = resolution.0;
+    (0..resolution.0 * resolution.1).into_par_iter().map(move |index| {
+        let (x, y) = region.sample_point(resolution, index % width, index / width);
+        source.get_noise(x, y)
+    })
+}
@@ -0,0 +1,310 @@
+//! Least-cost paths over heightfields for roads and trails.
+//!
+//! [`find_path`] searches the samples of a [`NoiseMap`] for the cheapest route between two points, where every step
+//! costs its length scaled up by the slope it climbs and the height it reaches, and returns the route as a smoothed
+//! polyline. Steps can be ruled out entirely above a maximum slope or outside a height range, for example to keep
+//! roads out of the sea.
+//!
+//! ```
+//! use perlin2d::paths::{find_path, PathCost};
+//! use perlin2d::{NoiseSource, PerlinNoise2D, Region};
+//!
+//! let terrain = PerlinNoise2D::new(6, 40.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 12);
+//! let map = terrain.generate_map(Region::new((0.0, 0.0), (256.0, 256.0)), (128, 128));
+//!
+//! let mut cost = PathCost::new();
+//! cost.set_slope_weight(20.0);
+//! let road = find_path(&map, (10.0, 10.0), (240.0, 200.0), &cost).unwrap();
+//!
+//! let points = road.get_points();
+//! assert_eq!(points[0], (10.0, 10.0));
+//! assert_eq!(points[points.len() - 1], (240.0, 200.0));
+//! // a road around the hills is longer than the straight line
+//! assert!(road.length() >= f64::hypot(230.0, 190.0));
+//! ```
+
+use crate::NoiseMap;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// How expensive a step over the terrain is
+///
+/// Member variables:
+///
+/// * `slope_weight` - Extra cost per unit of distance for every squared unit of slope. Defaults to `10.0`.
+/// * `height_weight` - Extra cost per unit of distance for every unit of height above the lowest value of the map.
+///   Defaults to `0.0`.
+/// * `max_slope` - Optional slope above which steps are impassable.
+/// * `height_range` - Optional `(min, max)` range of heights outside which samples are impassable.
+/// * `smoothing` - Rounds of corner cutting applied to the route. Defaults to `3`.
+///
+/// Slopes are height change per unit of distance. A step of length `d` costs
+/// `d * (1 + slope_weight * slope^2 + height_weight * height)`, measured at the sample it enters.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PathCost {
+    slope_weight: f64,
+    height_weight: f64,
+    max_slope: Option<f64>,
+    height_range: Option<(f64, f64)>,
+    smoothing: u32,
+}
+
+impl Default for PathCost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PathCost {
+    /// Create and return a new PathCost object with the default weights
+    pub fn new() -> Self {
+        Self {
+            slope_weight: 10.0,
+            height_weight: 0.0,
+            max_slope: None,
+            height_range: None,
+            smoothing: 3,
+        }
+    }
+
+    /// Getter function for slope_weight
+    pub fn get_slope_weight(&self) -> f64 {
+        self.slope_weight
+    }
+    /// Getter function for height_weight
+    pub fn get_height_weight(&self) -> f64 {
+        self.height_weight
+    }
+    /// Getter function for max_slope
+    pub fn get_max_slope(&self) -> Option<f64> {
+        self.max_slope
+    }
+    /// Getter function for the passable height range
+    pub fn get_height_range(&self) -> Option<(f64, f64)> {
+        self.height_range
+    }
+    /// Getter function for smoothing
+    pub fn get_smoothing(&self) -> u32 {
+        self.smoothing
+    }
+
+    /// Setter function for slope_weight, negative weights are treated as zero
+    pub fn set_slope_weight(&mut self, weight: f64) {
+        self.slope_weight = weight.max(0.0);
+    }
+    /// Setter function for height_weight, negative weights are treated as zero
+    pub fn set_height_weight(&mut self, weight: f64) {
+        self.height_weight = weight.max(0.0);
+    }
+    /// Setter function for max_slope, `None` allows every slope
+    pub fn set_max_slope(&mut self, max_slope: Option<f64>) {
+        self.max_slope = max_slope;
+    }
+    /// Setter function for the passable height range, `None` allows every height
+    pub fn set_height_range(&mut self, range: Option<(f64, f64)>) {
+        self.height_range = range;
+    }
+    /// Setter function for smoothing
+    pub fn set_smoothing(&mut self, smoothing: u32) {
+        self.smoothing = smoothing;
+    }
+}
+
+/// A route found by [`find_path`]
+///
+/// Member variables:
+///
+/// * `points` - The smoothed polyline from the start to the goal.
+/// * `cost` - The total cost of the route over the map samples, before smoothing.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Path {
+    points: Vec<(f64, f64)>,
+    cost: f64,
+}
+
+impl Path {
+    /// Getter function for points
+    pub fn get_points(&self) -> &[(f64, f64)] {
+        &self.points
+    }
+    /// Getter function for cost
+    pub fn get_cost(&self) -> f64 {
+        self.cost
+    }
+
+    /// Length of the polyline
+    pub fn length(&self) -> f64 {
+        self.points
+            .windows(2)
+            .map(|w| f64::hypot(w[1].0 - w[0].0, w[1].1 - w[0].1))
+            .sum()
+    }
+}
+
+/// An open node of the search, ordered so the heap pops the lowest estimate first
+#[derive(Copy, Clone, PartialEq)]
+struct Open {
+    estimate: f64,
+    spent: f64,
+    index: usize,
+}
+
+impl Eq for Open {}
+
+impl Ord for Open {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .total_cmp(&self.estimate)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+impl PartialOrd for Open {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Finds the cheapest route from `start` to `goal` over the samples of a heightfield
+///
+/// Both points are snapped to their nearest sample for the search, which moves between the eight neighbors of every
+/// sample, and the polyline then starts and ends at the exact points given. Returns `None` when either point lies
+/// outside the map region or no passable route connects them.
+pub fn find_path(map: &NoiseMap, start: (f64, f64), goal: (f64, f64), cost: &PathCost) -> Option<Path> {
+    let (w, h) = map.get_resolution();
+    let from = nearest_sample(map, start)?;
+    let to = nearest_sample(map, goal)?;
+    let (step_x, step_y) = map.get_region().step((w, h));
+    let (step_x, step_y) = (step_x.abs(), step_y.abs());
+    let values = map.get_values();
+    let lowest = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let passable = |index: usize| match cost.height_range {
+        Some((lo, hi)) => values[index] >= lo && values[index] <= hi,
+        None => true,
+    };
+    if !passable(from) || !passable(to) {
+        return None;
+    }
+    let position = |index: usize| ((index % w) as f64 * step_x, (index / w) as f64 * step_y);
+    let goal_position = position(to);
+    // every unit of distance costs at least one, so the straight line never overestimates
+    let heuristic = |index: usize| {
+        let (x, y) = position(index);
+        f64::hypot(goal_position.0 - x, goal_position.1 - y)
+    };
+
+    let mut best = vec![f64::INFINITY; w * h];
+    let mut came_from = vec![usize::MAX; w * h];
+    let mut open = BinaryHeap::new();
+    best[from] = 0.0;
+    open.push(Open {
+        estimate: heuristic(from),
+        spent: 0.0,
+        index: from,
+    });
+    while let Some(Open { spent, index, .. }) = open.pop() {
+        if index == to {
+            break;
+        }
+        if spent > best[index] {
+            // a cheaper route to this sample was found after it was queued
+            continue;
+        }
+        let (i, j) = ((index % w) as isize, (index / w) as isize);
+        for (di, dj) in NEIGHBORS.iter() {
+            let (ni, nj) = (i + di, j + dj);
+            if ni < 0 || nj < 0 || ni >= w as isize || nj >= h as isize {
+                continue;
+            }
+            let next = nj as usize * w + ni as usize;
+            if !passable(next) {
+                continue;
+            }
+            let distance = f64::hypot(*di as f64 * step_x, *dj as f64 * step_y);
+            let slope = (values[next] - values[index]).abs() / distance;
+            if let Some(max_slope) = cost.max_slope {
+                if slope > max_slope {
+                    continue;
+                }
+            }
+            let step =
+                distance * (1.0 + cost.slope_weight * slope * slope + cost.height_weight * (values[next] - lowest));
+            if spent + step < best[next] {
+                best[next] = spent + step;
+                came_from[next] = index;
+                open.push(Open {
+                    estimate: spent + step + heuristic(next),
+                    spent: spent + step,
+                    index: next,
+                });
+            }
+        }
+    }
+    if !best[to].is_finite() {
+        return None;
+    }
+
+    let mut samples = vec![to];
+    while let Some(&last) = samples.last() {
+        if last == from {
+            break;
+        }
+        samples.push(came_from[last]);
+    }
+    samples.reverse();
+    let mut points: Vec<(f64, f64)> = samples
+        .iter()
+        .map(|&index| map.get_position(index % w, index / w))
+        .collect();
+    // the exact end points replace the samples they were snapped to
+    points[0] = start;
+    let last = points.len() - 1;
+    points[last] = goal;
+    if points.len() == 1 {
+        points.push(goal);
+    }
+    Some(Path {
+        points: smooth_polyline(&points, cost.smoothing),
+        cost: best[to],
+    })
+}
+
+/// Rounds the corners of a polyline with Chaikin's corner cutting, keeping its first and last point
+///
+/// Every iteration replaces each segment by points at a quarter and three quarters of its length, which converges to
+/// a smooth curve inside the original polyline.
+pub fn smooth_polyline(points: &[(f64, f64)], iterations: u32) -> Vec<(f64, f64)> {
+    let mut points = points.to_vec();
+    for _ in 0..iterations {
+        if points.len() < 3 {
+            break;
+        }
+        let mut next = Vec::with_capacity(2 * points.len());
+        next.push(points[0]);
+        for w in points.windows(2) {
+            let (a, b) = (w[0], w[1]);
+            next.push((0.75 * a.0 + 0.25 * b.0, 0.75 * a.1 + 0.25 * b.1));
+            next.push((0.25 * a.0 + 0.75 * b.0, 0.25 * a.1 + 0.75 * b.1));
+        }
+        next.push(points[points.len() - 1]);
+        points = next;
+    }
+    points
+}
+
+const NEIGHBORS: [(isize, isize); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+
+/// Index of the sample nearest to `point`, `None` outside the region
+fn nearest_sample(map: &NoiseMap, point: (f64, f64)) -> Option<usize> {
+    let (w, h) = map.get_resolution();
+    let (u, v) = map.get_coord_map().world_to_grid(point.0, point.1);
+    if !(u >= 0.0 && v >= 0.0 && u <= w as f64 && v <= h as f64) || w == 0 || h == 0 {
+        return None;
+    }
+    let i = usize::min(u.round() as usize, w - 1);
+    let j = usize::min(v.round() as usize, h - 1);
+    Some(j * w + i)
+}
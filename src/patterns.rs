@@ -0,0 +1,263 @@
+//! Classic texture synthesis patterns built on noise sources.
+//!
+//! These follow the formulas from Ken Perlin's original texturing work:
+//!
+//! * [`Turbulence`] - `sum(|noise(p * lacunarity^k)| * gain^k)`, a billowy sum of absolute values.
+//! * [`Marble`] - `sin(stripes . p + distortion * noise(p))`, veins bent by a distortion source.
+//! * [`Wood`] - `fract(rings * |p - center| + distortion * noise(p))`, concentric growth rings.
+//!
+//! Every pattern is a [`NoiseSource`], so they can be sampled into maps and combined like any other generator.
+//!
+//! ```
+//! use perlin2d::patterns::{Marble, Turbulence, Wood};
+//! use perlin2d::{NoiseSource, PerlinNoise2D};
+//!
+//! let perlin = PerlinNoise2D::new(1, 1.0, 1.0, 0.5, 2.0, (16.0, 16.0), 0.0, 3);
+//! let turbulence = Turbulence::new(perlin, 5);
+//! let marble = Marble::new(turbulence.clone(), (0.05, 0.0), 4.0);
+//! let wood = Wood::new(perlin, (0.0, 0.0), 0.2, 0.3);
+//!
+//! let (lo, hi) = turbulence.get_range();
+//! for k in 0..100 {
+//!     let (x, y) = (k as f64 * 3.7, k as f64 * 1.3);
+//!     let t = turbulence.get_noise(x, y);
+//!     assert!(t >= 0.0 && t >= lo && t <= hi);
+//!
+//!     let vein = marble.get_noise(x, y);
+//!     assert!((-1.0..=1.0).contains(&vein));
+//!
+//!     let ring = wood.get_noise(x, y);
+//!     assert!((0.0..1.0).contains(&ring));
+//! }
+//! ```
+
+use crate::NoiseSource;
+
+/// Sum of the absolute values of a source over several octaves
+///
+/// The output at `p` is `sum(|source(p * lacunarity^k)| * gain^k)` for `k` in `0..octaves`. The source is usually
+/// a single octave generator, since it supplies the base frequency of each octave.
+///
+/// Member variables:
+///
+/// * `source` - The noise sampled at every octave.
+/// * `octaves` - The number of octaves summed.
+/// * `lacunarity` - Frequency multiplier between octaves. Defaults to `2.0`.
+/// * `gain` - Amplitude multiplier between octaves. Defaults to `0.5`.
+#[derive(Copy, Clone, Debug)]
+pub struct Turbulence<S> {
+    source: S,
+    octaves: u32,
+    lacunarity: f64,
+    gain: f64,
+}
+
+impl<S: NoiseSource> Turbulence<S> {
+    /// Create and return a new Turbulence object with a lacunarity of `2.0` and a gain of `0.5`
+    pub fn new(source: S, octaves: u32) -> Self {
+        Self {
+            source,
+            octaves,
+            lacunarity: 2.0,
+            gain: 0.5,
+        }
+    }
+
+    /// Getter function for source
+    pub fn get_source(&self) -> &S {
+        &self.source
+    }
+    /// Getter function for octaves
+    pub fn get_octaves(&self) -> u32 {
+        self.octaves
+    }
+    /// Getter function for lacunarity
+    pub fn get_lacunarity(&self) -> f64 {
+        self.lacunarity
+    }
+    /// Getter function for gain
+    pub fn get_gain(&self) -> f64 {
+        self.gain
+    }
+
+    /// Setter function for octaves
+    pub fn set_octaves(&mut self, octaves: u32) {
+        self.octaves = octaves;
+    }
+    /// Setter function for lacunarity
+    pub fn set_lacunarity(&mut self, lacunarity: f64) {
+        self.lacunarity = lacunarity;
+    }
+    /// Setter function for gain
+    pub fn set_gain(&mut self, gain: f64) {
+        self.gain = gain;
+    }
+}
+
+impl<S: NoiseSource> NoiseSource for Turbulence<S> {
+    fn get_noise(&self, x: f64, y: f64) -> f64 {
+        let mut t = 0.0;
+        let mut amp = 1.0;
+        let mut freq = 1.0;
+        for _ in 0..self.octaves {
+            t += f64::abs(self.source.get_noise(x * freq, y * freq)) * amp;
+            amp *= self.gain;
+            freq *= self.lacunarity;
+        }
+        t
+    }
+
+    fn get_range(&self) -> (f64, f64) {
+        let (lo, hi) = self.source.get_range();
+        let peak = f64::max(lo.abs(), hi.abs());
+        // the absolute value never gets closer to zero than the source range allows
+        let floor = if lo > 0.0 || hi < 0.0 {
+            f64::min(lo.abs(), hi.abs())
+        } else {
+            0.0
+        };
+        let mut gain = 1.0;
+        let (mut min, mut max) = (0.0, 0.0);
+        for _ in 0..self.octaves {
+            let (a, b) = (floor * gain, peak * gain);
+            min += f64::min(a, b);
+            max += f64::max(a, b);
+            gain *= self.gain;
+        }
+        (min, max)
+    }
+}
+
+/// Marble veins: a sine wave across the plane distorted by a noise source
+///
+/// The output at `p` is `sin(stripes.0 * x + stripes.1 * y + distortion * source(p))`, in `[-1, 1]`. The stripe
+/// vector sets the direction and spacing of the veins, and a [`Turbulence`] source gives the classic look.
+///
+/// Member variables:
+///
+/// * `source` - The noise that bends the veins.
+/// * `stripes` - Angular frequency of the veins along the x and y axes.
+/// * `distortion` - How strongly the source bends the veins.
+#[derive(Copy, Clone, Debug)]
+pub struct Marble<S> {
+    source: S,
+    stripes: (f64, f64),
+    distortion: f64,
+}
+
+impl<S: NoiseSource> Marble<S> {
+    /// Create and return a new Marble object
+    pub fn new(source: S, stripes: (f64, f64), distortion: f64) -> Self {
+        Self {
+            source,
+            stripes,
+            distortion,
+        }
+    }
+
+    /// Getter function for source
+    pub fn get_source(&self) -> &S {
+        &self.source
+    }
+    /// Getter function for stripes
+    pub fn get_stripes(&self) -> (f64, f64) {
+        self.stripes
+    }
+    /// Getter function for distortion
+    pub fn get_distortion(&self) -> f64 {
+        self.distortion
+    }
+
+    /// Setter function for stripes
+    pub fn set_stripes(&mut self, stripes: (f64, f64)) {
+        self.stripes = stripes;
+    }
+    /// Setter function for distortion
+    pub fn set_distortion(&mut self, distortion: f64) {
+        self.distortion = distortion;
+    }
+}
+
+impl<S: NoiseSource> NoiseSource for Marble<S> {
+    fn get_noise(&self, x: f64, y: f64) -> f64 {
+        let phase = self.stripes.0 * x + self.stripes.1 * y;
+        f64::sin(phase + self.distortion * self.source.get_noise(x, y))
+    }
+
+    fn get_range(&self) -> (f64, f64) {
+        (-1.0, 1.0)
+    }
+}
+
+/// Wood grain: concentric rings around a center distorted by a noise source
+///
+/// The output at `p` is the fractional part of `rings * |p - center| + distortion * source(p)`, in `[0, 1)`. Each
+/// ring ramps up from `0` to `1` and then drops back, which is usually remapped through a color gradient.
+///
+/// Member variables:
+///
+/// * `source` - The noise that warps the rings.
+/// * `center` - The center of the rings in the noise plane.
+/// * `rings` - Number of rings per unit of distance from the center.
+/// * `distortion` - How strongly the source warps the rings.
+#[derive(Copy, Clone, Debug)]
+pub struct Wood<S> {
+    source: S,
+    center: (f64, f64),
+    rings: f64,
+    distortion: f64,
+}
+
+impl<S: NoiseSource> Wood<S> {
+    /// Create and return a new Wood object
+    pub fn new(source: S, center: (f64, f64), rings: f64, distortion: f64) -> Self {
+        Self {
+            source,
+            center,
+            rings,
+            distortion,
+        }
+    }
+
+    /// Getter function for source
+    pub fn get_source(&self) -> &S {
+        &self.source
+    }
+    /// Getter function for center
+    pub fn get_center(&self) -> (f64, f64) {
+        self.center
+    }
+    /// Getter function for rings
+    pub fn get_rings(&self) -> f64 {
+        self.rings
+    }
+    /// Getter function for distortion
+    pub fn get_distortion(&self) -> f64 {
+        self.distortion
+    }
+
+    /// Setter function for center
+    pub fn set_center(&mut self, center: (f64, f64)) {
+        self.center = center;
+    }
+    /// Setter function for rings
+    pub fn set_rings(&mut self, rings: f64) {
+        self.rings = rings;
+    }
+    /// Setter function for distortion
+    pub fn set_distortion(&mut self, distortion: f64) {
+        self.distortion = distortion;
+    }
+}
+
+impl<S: NoiseSource> NoiseSource for Wood<S> {
+    fn get_noise(&self, x: f64, y: f64) -> f64 {
+        let distance = f64::hypot(x - self.center.0, y - self.center.1);
+        let g = self.rings * distance + self.distortion * self.source.get_noise(x, y);
+        g - g.floor()
+    }
+
+    fn get_range(&self) -> (f64, f64) {
+        (0.0, 1.0)
+    }
+}
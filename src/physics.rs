@@ -0,0 +1,126 @@
+//! Heightfield data laid out for physics engines.
+//!
+//! Physics heightfields are usually centered on the origin with the height along `y`, rows advancing along `z` and
+//! columns along `x`, stored column-major and covering both edges of the area. [`HeightfieldData`] samples a source
+//! in exactly that layout, mapping the noise `x` axis to `x` and the noise `y` axis to `z`, and keeps the scale and
+//! translation that place the collider over the sampled region. With the `parry` feature it converts directly into a
+//! `parry3d` (and therefore `rapier3d`) heightfield.
+//!
+//! ```
+//! use perlin2d::physics::HeightfieldData;
+//! use perlin2d::{NoiseSource, PerlinNoise2D, Region};
+//!
+//! let terrain = PerlinNoise2D::new(6, 20.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 1);
+//! let field = HeightfieldData::sample(&terrain, Region::new((0.0, 0.0), (64.0, 32.0)), (65, 33));
+//!
+//! assert_eq!((field.get_nrows(), field.get_ncols()), (33, 65));
+//! assert_eq!(field.get_scale(), (64.0, 1.0, 32.0));
+//! assert_eq!(field.get_translation(), (32.0, 0.0, 16.0));
+//! // the last column lies on the maximum x edge of the region
+//! assert_eq!(field.get_height(10, 64), terrain.get_noise(64.0, 10.0));
+//! ```
+
+use crate::{NoiseSource, Region};
+
+/// Heights sampled on a grid that includes both edges of a region, stored column-major
+///
+/// Member variables:
+///
+/// * `heights` - Column-major heights, the height of row `i` and column `j` is at `i + j * nrows`.
+/// * `nrows` - The number of samples along `z`, the noise `y` axis.
+/// * `ncols` - The number of samples along `x`, the noise `x` axis.
+/// * `scale` - The `(x, y, z)` size of the collider, the region size with a height scale of `1`.
+/// * `translation` - The `(x, y, z)` position of the collider center, the center of the region.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeightfieldData {
+    heights: Vec<f64>,
+    nrows: usize,
+    ncols: usize,
+    scale: (f64, f64, f64),
+    translation: (f64, f64, f64),
+}
+
+impl HeightfieldData {
+    /// Samples a source over the region with `(columns, rows)` samples, including the maximum edges
+    ///
+    /// Panics if there are fewer than two columns or rows, which physics heightfields require.
+    pub fn sample<S: NoiseSource + ?Sized>(source: &S, region: Region, resolution: (usize, usize)) -> Self {
+        let (ncols, nrows) = resolution;
+        assert!(
+            ncols > 1 && nrows > 1,
+            "a heightfield needs at least two rows and columns"
+        );
+        let (dx, dy) = (
+            region.width() / (ncols - 1) as f64,
+            region.height() / (nrows - 1) as f64,
+        );
+        let mut heights = Vec::with_capacity(ncols * nrows);
+        for j in 0..ncols {
+            // the last sample is placed on the edge exactly
+            let x = if j == ncols - 1 {
+                region.max.0
+            } else {
+                region.min.0 + j as f64 * dx
+            };
+            for i in 0..nrows {
+                let y = if i == nrows - 1 {
+                    region.max.1
+                } else {
+                    region.min.1 + i as f64 * dy
+                };
+                heights.push(source.get_noise(x, y));
+            }
+        }
+        Self {
+            heights,
+            nrows,
+            ncols,
+            scale: (region.width(), 1.0, region.height()),
+            translation: (
+                0.5 * (region.min.0 + region.max.0),
+                0.0,
+                0.5 * (region.min.1 + region.max.1),
+            ),
+        }
+    }
+
+    /// Getter function for the column-major heights
+    pub fn get_heights(&self) -> &[f64] {
+        &self.heights
+    }
+    /// Getter function for nrows
+    pub fn get_nrows(&self) -> usize {
+        self.nrows
+    }
+    /// Getter function for ncols
+    pub fn get_ncols(&self) -> usize {
+        self.ncols
+    }
+    /// Getter function for scale
+    pub fn get_scale(&self) -> (f64, f64, f64) {
+        self.scale
+    }
+    /// Getter function for translation
+    pub fn get_translation(&self) -> (f64, f64, f64) {
+        self.translation
+    }
+
+    /// Setter function for the height scale, applied by the physics engine on top of the sampled values
+    pub fn set_height_scale(&mut self, scale: f64) {
+        self.scale.1 = scale;
+    }
+
+    /// Returns the height of row `i` and column `j`
+    pub fn get_height(&self, i: usize, j: usize) -> f64 {
+        self.heights[i + j * self.nrows]
+    }
+
+    /// Creates a `parry3d` heightfield, to be placed at [`HeightfieldData::get_translation`]
+    #[cfg(feature = "parry")]
+    pub fn to_parry(&self) -> parry3d::shape::HeightField {
+        let heights = self.heights.iter().map(|&h| h as f32).collect();
+        let scale = parry3d::math::Vector::new(self.scale.0 as f32, self.scale.1 as f32, self.scale.2 as f32);
+        parry3d::shape::HeightField::new(parry3d::utils::Array2::new(self.nrows, self.ncols, heights), scale)
+    }
+}
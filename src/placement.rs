@@ -0,0 +1,271 @@
+//! Deterministic object placement driven by noise layers.
+//!
+//! Every [`PlacementRule`] scatters candidates over an infinite jittered grid whose spacing is the minimum distance
+//! between its objects. A candidate is kept when it passes the rule's height, slope, biome and density filters and
+//! no valid candidate of the same rule with a higher priority lies closer than the spacing. Candidates, priorities
+//! and filters only depend on the seed and the position, so neighboring regions agree along their borders and a
+//! region always yields the same placements however the world is split into chunks.
+//!
+//! ```
+//! use perlin2d::placement::{place, Placement, PlacementRule};
+//! use perlin2d::{NoiseSource, PerlinNoise2D, Region};
+//!
+//! let terrain = PerlinNoise2D::new(6, 50.0, 0.5, 0.5, 2.0, (200.0, 200.0), 0.0, 4);
+//!
+//! let mut trees = PlacementRule::new(6.0);
+//! trees.set_height_range(Some((0.0, 30.0)));
+//! trees.set_slope_range(Some((0.0, 0.6)));
+//! let mut rocks = PlacementRule::new(15.0);
+//! rocks.set_density(0.3);
+//!
+//! let region = Region::new((0.0, 0.0), (256.0, 256.0));
+//! let rules = [trees, rocks];
+//! let placements = place(&terrain, |_, _| 0, &rules, region, 99);
+//!
+//! // chunks produce the same objects as the whole region
+//! let left = place(&terrain, |_, _| 0, &rules, Region::new((0.0, 0.0), (128.0, 256.0)), 99);
+//! let right = place(&terrain, |_, _| 0, &rules, Region::new((128.0, 0.0), (256.0, 256.0)), 99);
+//! assert_eq!(left.len() + right.len(), placements.len());
+//!
+//! let trees: Vec<&Placement> = placements.iter().filter(|p| p.rule == 0).collect();
+//! assert!(trees.iter().all(|p| p.height >= 0.0 && p.height <= 30.0));
+//! for (k, a) in trees.iter().enumerate() {
+//!     for b in &trees[k + 1..] {
+//!         let d = f64::hypot(a.position.0 - b.position.0, a.position.1 - b.position.1);
+//!         assert!(d >= 6.0);
+//!     }
+//! }
+//! ```
+
+use crate::hash::mix_seed;
+use crate::{NoiseSource, Region};
+
+/// Conditions an object needs to be placed
+///
+/// Member variables:
+///
+/// * `spacing` - The minimum distance between two objects of this rule.
+/// * `density` - The fraction of valid candidates that are kept, in `[0, 1]`. Defaults to `1.0`.
+/// * `height` - Optional `(min, max)` range of terrain heights.
+/// * `slope` - Optional `(min, max)` range of slopes, as height change per unit of distance.
+/// * `biomes` - Biome IDs the object can be placed in. Empty allows every biome.
+/// * `salt` - Distinguishes the candidates of rules with equal spacing. Defaults to the index of the rule.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlacementRule {
+    spacing: f64,
+    density: f64,
+    height: Option<(f64, f64)>,
+    slope: Option<(f64, f64)>,
+    biomes: Vec<u32>,
+    salt: Option<u64>,
+}
+
+/// An object placed by [`place`]
+///
+/// * `rule` - The index of the rule that placed the object.
+/// * `id` - A stable identifier of the object, derived from the seed, the rule and its grid cell.
+/// * `position` - The position of the object.
+/// * `height` - The terrain height at the position.
+/// * `slope` - The terrain slope at the position.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Placement {
+    pub rule: usize,
+    pub id: u64,
+    pub position: (f64, f64),
+    pub height: f64,
+    pub slope: f64,
+}
+
+impl PlacementRule {
+    /// Create and return a new PlacementRule object that accepts every candidate at the given spacing
+    ///
+    /// Panics if `spacing` is not a positive finite number.
+    pub fn new(spacing: f64) -> Self {
+        assert!(
+            spacing > 0.0 && spacing.is_finite(),
+            "spacing must be positive and finite"
+        );
+        Self {
+            spacing,
+            density: 1.0,
+            height: None,
+            slope: None,
+            biomes: Vec::new(),
+            salt: None,
+        }
+    }
+
+    /// Getter function for spacing
+    pub fn get_spacing(&self) -> f64 {
+        self.spacing
+    }
+    /// Getter function for density
+    pub fn get_density(&self) -> f64 {
+        self.density
+    }
+    /// Getter function for the height range
+    pub fn get_height_range(&self) -> Option<(f64, f64)> {
+        self.height
+    }
+    /// Getter function for the slope range
+    pub fn get_slope_range(&self) -> Option<(f64, f64)> {
+        self.slope
+    }
+    /// Getter function for biomes
+    pub fn get_biomes(&self) -> &[u32] {
+        &self.biomes
+    }
+    /// Getter function for salt
+    pub fn get_salt(&self) -> Option<u64> {
+        self.salt
+    }
+
+    /// Setter function for density, clamped to `[0, 1]`
+    pub fn set_density(&mut self, density: f64) {
+        self.density = density.clamp(0.0, 1.0);
+    }
+    /// Setter function for the height range, `None` accepts every height
+    pub fn set_height_range(&mut self, range: Option<(f64, f64)>) {
+        self.height = range;
+    }
+    /// Setter function for the slope range, `None` accepts every slope
+    pub fn set_slope_range(&mut self, range: Option<(f64, f64)>) {
+        self.slope = range;
+    }
+    /// Setter function for biomes, empty accepts every biome
+    pub fn set_biomes(&mut self, biomes: Vec<u32>) {
+        self.biomes = biomes;
+    }
+    /// Setter function for salt, `None` uses the index of the rule
+    pub fn set_salt(&mut self, salt: Option<u64>) {
+        self.salt = salt;
+    }
+}
+
+/// A candidate that passed the filters of its rule
+struct Candidate {
+    id: u64,
+    priority: u64,
+    position: (f64, f64),
+    height: f64,
+    slope: f64,
+}
+
+/// Places the objects of every rule inside a region
+///
+/// `height` gives the terrain height and `biome` the biome ID at a point. Placements are ordered by rule, then row
+/// by row through the grid of the rule. Spacing is only enforced between objects of the same rule.
+pub fn place<S: NoiseSource + ?Sized, B: Fn(f64, f64) -> u32>(
+    height: &S,
+    biome: B,
+    rules: &[PlacementRule],
+    region: Region,
+    seed: u64,
+) -> Vec<Placement> {
+    let (x0, x1) = (
+        f64::min(region.min.0, region.max.0),
+        f64::max(region.min.0, region.max.0),
+    );
+    let (y0, y1) = (
+        f64::min(region.min.1, region.max.1),
+        f64::max(region.min.1, region.max.1),
+    );
+    let mut placements = Vec::new();
+
+    for (index, rule) in rules.iter().enumerate() {
+        let salt = mix_seed(seed, rule.salt.unwrap_or(index as u64));
+        let candidate = |i: i64, j: i64| evaluate(height, &biome, rule, salt, i, j);
+        let s = rule.spacing;
+        let (i0, i1) = (f64::floor(x0 / s) as i64, f64::floor(x1 / s) as i64);
+        let (j0, j1) = (f64::floor(y0 / s) as i64, f64::floor(y1 / s) as i64);
+
+        for j in j0..=j1 {
+            for i in i0..=i1 {
+                let c = match candidate(i, j) {
+                    Some(c) => c,
+                    None => continue,
+                };
+                let (x, y) = c.position;
+                // the maximum edge is excluded like in the sampling of regions
+                if x < x0 || x >= x1 || y < y0 || y >= y1 {
+                    continue;
+                }
+                // candidates closer than the spacing lie at most one cell away
+                let suppressed = (-1..=1).any(|dj| {
+                    (-1..=1).any(|di| {
+                        if di == 0 && dj == 0 {
+                            return false;
+                        }
+                        match candidate(i + di, j + dj) {
+                            Some(other) => {
+                                let d = f64::hypot(other.position.0 - x, other.position.1 - y);
+                                d < s && (other.priority, other.id) > (c.priority, c.id)
+                            }
+                            None => false,
+                        }
+                    })
+                });
+                if !suppressed {
+                    placements.push(Placement {
+                        rule: index,
+                        id: c.id,
+                        position: c.position,
+                        height: c.height,
+                        slope: c.slope,
+                    });
+                }
+            }
+        }
+    }
+    placements
+}
+
+/// Returns the candidate of cell `(i, j)` if it passes the filters of the rule
+fn evaluate<S: NoiseSource + ?Sized, B: Fn(f64, f64) -> u32>(
+    height: &S,
+    biome: &B,
+    rule: &PlacementRule,
+    salt: u64,
+    i: i64,
+    j: i64,
+) -> Option<Candidate> {
+    let id = mix_seed(salt ^ (i as u64).rotate_left(32), j as u64);
+    let unit = |h: u64| (h >> 11) as f64 * (1.0 / (1u64 << 53) as f64);
+    let h1 = mix_seed(id, 1);
+    let h2 = mix_seed(id, 2);
+    if unit(mix_seed(id, 3)) >= rule.density {
+        return None;
+    }
+    let s = rule.spacing;
+    let position = ((i as f64 + unit(h1)) * s, (j as f64 + unit(h2)) * s);
+    let (x, y) = position;
+
+    let value = height.get_noise(x, y);
+    if let Some((lo, hi)) = rule.height {
+        if value < lo || value > hi {
+            return None;
+        }
+    }
+    // central differences over a small fraction of the spacing
+    let e = 0.05 * s;
+    let dx = (height.get_noise(x + e, y) - height.get_noise(x - e, y)) / (2.0 * e);
+    let dy = (height.get_noise(x, y + e) - height.get_noise(x, y - e)) / (2.0 * e);
+    let slope = f64::hypot(dx, dy);
+    if let Some((lo, hi)) = rule.slope {
+        if slope < lo || slope > hi {
+            return None;
+        }
+    }
+    if !rule.biomes.is_empty() && !rule.biomes.contains(&biome(x, y)) {
+        return None;
+    }
+    Some(Candidate {
+        id,
+        priority: mix_seed(id, 4),
+        position,
+        height: value,
+        slope,
+    })
+}
@@ -0,0 +1,79 @@
+//! Point types accepted by the vector based sampling methods.
+//!
+//! [`NoiseSource::get_noise_vec`](crate::NoiseSource::get_noise_vec) and
+//! [`NoiseSource::sample_vecs`](crate::NoiseSource::sample_vecs) take any [`NoisePoint`]. Besides tuples and arrays,
+//! the `glam` feature adds `Vec2` and `DVec2` and the `nalgebra` feature adds `Point2` and `Vector2`.
+//!
+//! ```
+//! use perlin2d::{NoiseSource, PerlinNoise2D};
+//!
+//! let perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 2);
+//! assert_eq!(perlin.get_noise_vec([5.0, 10.0]), perlin.get_noise(5.0, 10.0));
+//! assert_eq!(perlin.sample_vecs(&[(1.0, 2.0), (3.0, 4.0)])[1], perlin.get_noise(3.0, 4.0));
+//! ```
+
+/// A point of the noise plane
+pub trait NoisePoint {
+    /// Returns the `(x, y)` coordinates of the point
+    fn coords(&self) -> (f64, f64);
+}
+
+impl NoisePoint for (f64, f64) {
+    fn coords(&self) -> (f64, f64) {
+        *self
+    }
+}
+
+impl NoisePoint for [f64; 2] {
+    fn coords(&self) -> (f64, f64) {
+        (self[0], self[1])
+    }
+}
+
+impl<P: NoisePoint + ?Sized> NoisePoint for &P {
+    fn coords(&self) -> (f64, f64) {
+        (**self).coords()
+    }
+}
+
+#[cfg(feature = "glam")]
+impl NoisePoint for glam::Vec2 {
+    fn coords(&self) -> (f64, f64) {
+        (self.x as f64, self.y as f64)
+    }
+}
+
+#[cfg(feature = "glam")]
+impl NoisePoint for glam::DVec2 {
+    fn coords(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl NoisePoint for nalgebra::Point2<f64> {
+    fn coords(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl NoisePoint for nalgebra::Point2<f32> {
+    fn coords(&self) -> (f64, f64) {
+        (self.x as f64, self.y as f64)
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl NoisePoint for nalgebra::Vector2<f64> {
+    fn coords(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl NoisePoint for nalgebra::Vector2<f32> {
+    fn coords(&self) -> (f64, f64) {
+        (self.x as f64, self.y as f64)
+    }
+}
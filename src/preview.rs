@@ -0,0 +1,184 @@
+//! Double-buffered regeneration of a map for live previews in editors.
+//!
+//! A [`LivePreview`] owns the map currently on screen and a second buffer that new maps are generated into. Once a
+//! map is complete the two buffers swap, so the front map is never seen half regenerated. Regeneration can run on the
+//! calling thread with [`LivePreview::update`], or on a worker thread with [`LivePreview::request`] and
+//! [`LivePreview::poll`], which keeps a GUI responsive while the user drags a slider. Requests arriving while the
+//! worker is busy replace each other, so only the newest parameters are generated next.
+//!
+//! ```
+//! use perlin2d::preview::LivePreview;
+//! use perlin2d::{NoiseSource, PerlinNoise2D, Region};
+//!
+//! let region = Region::new((0.0, 0.0), (256.0, 256.0));
+//! let mut preview = LivePreview::new(region, (128, 128));
+//!
+//! // the user drags the persistence slider, every change requests a new map
+//! for step in 1..=5 {
+//!     let perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.1 * step as f64, 2.0, (64.0, 64.0), 0.0, 3);
+//!     preview.request(perlin);
+//!     // once per frame the GUI picks up finished maps and draws the front buffer
+//!     preview.poll();
+//!     assert_eq!(preview.get_front().get_resolution(), (128, 128));
+//! }
+//!
+//! preview.wait();
+//! let last = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (64.0, 64.0), 0.0, 3);
+//! assert_eq!(preview.get_front(), &last.generate_map(region, (128, 128)));
+//! assert!(!preview.is_busy());
+//! ```
+
+use crate::{NoiseMap, NoiseSource, Region};
+use std::thread::JoinHandle;
+
+/// Front and back map buffers with regeneration on the calling thread or a worker thread
+///
+/// Member variables:
+///
+/// * `region` - The region of the noise plane that new maps cover.
+/// * `resolution` - The `(width, height)` of new maps.
+/// * `front` - The most recently completed map. It covers the region at the resolution of the time it was requested,
+///   and starts out filled with zeros.
+/// * `back` - The buffer the next map is generated into, held by the worker while it runs.
+/// * `worker` - The running worker thread, if any.
+/// * `pending` - The newest source requested while the worker was busy.
+/// * `generation` - The number of times the front buffer has been replaced.
+pub struct LivePreview {
+    region: Region,
+    resolution: (usize, usize),
+    front: NoiseMap,
+    back: Option<NoiseMap>,
+    worker: Option<JoinHandle<NoiseMap>>,
+    pending: Option<Box<dyn NoiseSource + Send>>,
+    generation: u64,
+}
+
+impl LivePreview {
+    /// Create and return a new LivePreview object whose maps cover `region` at the given `(width, height)` resolution
+    pub fn new(region: Region, resolution: (usize, usize)) -> Self {
+        Self {
+            region,
+            resolution,
+            front: NoiseMap::new(region, resolution),
+            back: Some(NoiseMap::new(region, resolution)),
+            worker: None,
+            pending: None,
+            generation: 0,
+        }
+    }
+
+    /// Getter function for region
+    pub fn get_region(&self) -> Region {
+        self.region
+    }
+    /// Getter function for resolution
+    pub fn get_resolution(&self) -> (usize, usize) {
+        self.resolution
+    }
+    /// Getter function for the front map
+    pub fn get_front(&self) -> &NoiseMap {
+        &self.front
+    }
+    /// Getter function for generation, which changes whenever the front map does
+    pub fn get_generation(&self) -> u64 {
+        self.generation
+    }
+    /// Whether a worker is generating a map or a request is waiting for it
+    pub fn is_busy(&self) -> bool {
+        self.worker.is_some() || self.pending.is_some()
+    }
+
+    /// Setter function for region, used from the next regeneration on
+    pub fn set_region(&mut self, region: Region) {
+        self.region = region;
+    }
+    /// Setter function for resolution, used from the next regeneration on
+    pub fn set_resolution(&mut self, resolution: (usize, usize)) {
+        self.resolution = resolution;
+    }
+
+    /// Regenerates the map from `source` on the calling thread and swaps it to the front
+    ///
+    /// Work requested from the worker is dropped, since it holds older parameters. A map the worker is still generating
+    /// is waited for and discarded.
+    pub fn update<S: NoiseSource + ?Sized>(&mut self, source: &S) {
+        self.pending = None;
+        if let Some(worker) = self.worker.take() {
+            self.back = Some(join(worker));
+        }
+        let mut back = self.take_back();
+        source.fill_region(back.get_region(), back.get_resolution(), back.get_values_mut());
+        self.present(back);
+    }
+
+    /// Requests a map from `source` on a worker thread
+    ///
+    /// The worker starts right away when idle. Otherwise the source waits until the running map is picked up by
+    /// [`LivePreview::poll`], replacing any source requested before it.
+    pub fn request<S: NoiseSource + Send + 'static>(&mut self, source: S) {
+        self.pending = Some(Box::new(source));
+        self.poll();
+    }
+
+    /// Swaps a map the worker has completed to the front and starts the worker on a waiting request
+    ///
+    /// Never blocks. Returns whether the front map changed. Panics in the worker are resumed on the calling thread.
+    pub fn poll(&mut self) -> bool {
+        let finished = self.worker.as_ref().is_some_and(JoinHandle::is_finished);
+        if finished {
+            if let Some(worker) = self.worker.take() {
+                let map = join(worker);
+                self.present(map);
+            }
+        }
+        self.start_pending();
+        finished
+    }
+
+    /// Blocks until the worker has completed every request and swaps the last map to the front
+    ///
+    /// Returns whether the front map changed.
+    pub fn wait(&mut self) -> bool {
+        let mut changed = false;
+        while let Some(worker) = self.worker.take() {
+            let map = join(worker);
+            self.present(map);
+            changed = true;
+            self.start_pending();
+        }
+        changed
+    }
+
+    fn start_pending(&mut self) {
+        if self.worker.is_some() {
+            return;
+        }
+        if let Some(source) = self.pending.take() {
+            let mut back = self.take_back();
+            self.worker = Some(std::thread::spawn(move || {
+                source.fill_region(back.get_region(), back.get_resolution(), back.get_values_mut());
+                back
+            }));
+        }
+    }
+
+    /// The back buffer shaped for the current region and resolution, reusing its allocation
+    fn take_back(&mut self) -> NoiseMap {
+        let (w, h) = self.resolution;
+        let mut values = self.back.take().map(NoiseMap::into_values).unwrap_or_default();
+        values.resize(w * h, 0.0);
+        NoiseMap::from_values(self.region, self.resolution, values)
+    }
+
+    fn present(&mut self, map: NoiseMap) {
+        self.back = Some(std::mem::replace(&mut self.front, map));
+        self.generation += 1;
+    }
+}
+
+fn join(worker: JoinHandle<NoiseMap>) -> NoiseMap {
+    match worker.join() {
+        Ok(map) => map,
+        Err(panic) => std::panic::resume_unwind(panic),
+    }
+}
@@ -0,0 +1,221 @@
+//! Adaptive sampling into a quadtree.
+//!
+//! [`sample_adaptive`] refines a region only where the noise deviates from a bilinear fit of the samples taken so
+//! far, so flat areas stay coarse while detailed ones are sampled densely. The leaves of the resulting [`QuadTree`]
+//! map directly to mesh patches of varying size.
+//!
+//! ```
+//! use perlin2d::quadtree::sample_adaptive;
+//! use perlin2d::{NoiseSource, PerlinNoise2D, Region};
+//!
+//! let perlin = PerlinNoise2D::new(6, 10.0, 0.5, 0.5, 2.0, (200.0, 200.0), 0.0, 21);
+//! let region = Region::new((0.0, 0.0), (512.0, 512.0));
+//! let tree = sample_adaptive(&perlin, region, 0.05, (2, 9));
+//!
+//! // far fewer samples than the 513 x 513 grid of the finest level
+//! assert!(tree.get_sample_count() < 513 * 513);
+//!
+//! // leaves above the finest level passed the error check at their center
+//! for leaf in tree.leaves().iter().filter(|leaf| leaf.depth < 9) {
+//!     let (min, max) = (leaf.region.min, leaf.region.max);
+//!     let (x, y) = ((min.0 + max.0) * 0.5, (min.1 + max.1) * 0.5);
+//!     assert!((tree.get_value(x, y) - perlin.get_noise(x, y)).abs() <= 0.05);
+//! }
+//! ```
+
+use crate::{NoiseSource, Region};
+
+/// A leaf of a [`QuadTree`]
+///
+/// * `region` - The area covered by the leaf.
+/// * `depth` - The number of subdivisions from the root.
+/// * `corners` - The values at the `[min x min y, max x min y, min x max y, max x max y]` corners.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuadLeaf {
+    pub region: Region,
+    pub depth: u32,
+    pub corners: [f64; 4],
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum QuadNode {
+    Leaf([f64; 4]),
+    /// Children in the same order as the corners
+    Branch(Box<[QuadNode; 4]>),
+}
+
+/// Sparse samples of a region, refined where the field varies
+///
+/// Member variables:
+///
+/// * `region` - The sampled area, including its maximum edge.
+/// * `root` - The top level node.
+/// * `samples` - The number of source evaluations used to build the tree.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuadTree {
+    region: Region,
+    root: QuadNode,
+    samples: usize,
+}
+
+/// Samples a region into a quadtree whose leaves are accurate to `max_error`
+///
+/// Every cell is checked at its center and edge midpoints, and split into four when one of them differs from the
+/// bilinear interpolation of the corners by more than `max_error`. Cells are always split down to `depths.0` levels,
+/// which keeps features smaller than the root from slipping between samples, and never below `depths.1` levels.
+/// The error is only bounded at those check points, so features narrower than a leaf can still exceed it in between.
+pub fn sample_adaptive<S: NoiseSource + ?Sized>(
+    source: &S,
+    region: Region,
+    max_error: f64,
+    depths: (u32, u32),
+) -> QuadTree {
+    let (min, max) = (region.min, region.max);
+    let corners = [
+        source.get_noise(min.0, min.1),
+        source.get_noise(max.0, min.1),
+        source.get_noise(min.0, max.1),
+        source.get_noise(max.0, max.1),
+    ];
+    let mut samples = 4;
+    let root = refine(source, region, corners, 0, max_error, depths, &mut samples);
+    QuadTree { region, root, samples }
+}
+
+fn refine<S: NoiseSource + ?Sized>(
+    source: &S,
+    region: Region,
+    corners: [f64; 4],
+    depth: u32,
+    max_error: f64,
+    depths: (u32, u32),
+    samples: &mut usize,
+) -> QuadNode {
+    if depth >= depths.1 {
+        return QuadNode::Leaf(corners);
+    }
+    let (min, max) = (region.min, region.max);
+    let mid = ((min.0 + max.0) * 0.5, (min.1 + max.1) * 0.5);
+    let [c00, c10, c01, c11] = corners;
+    // edge midpoints and center
+    let bottom = source.get_noise(mid.0, min.1);
+    let top = source.get_noise(mid.0, max.1);
+    let left = source.get_noise(min.0, mid.1);
+    let right = source.get_noise(max.0, mid.1);
+    let center = source.get_noise(mid.0, mid.1);
+    *samples += 5;
+
+    let error = [
+        bottom - 0.5 * (c00 + c10),
+        top - 0.5 * (c01 + c11),
+        left - 0.5 * (c00 + c01),
+        right - 0.5 * (c10 + c11),
+        center - 0.25 * (c00 + c10 + c01 + c11),
+    ]
+    .iter()
+    .fold(0.0, |e: f64, d| e.max(d.abs()));
+    if depth >= depths.0 && error <= max_error {
+        return QuadNode::Leaf(corners);
+    }
+
+    let quadrants = [
+        (Region::new(min, mid), [c00, bottom, left, center]),
+        (
+            Region::new((mid.0, min.1), (max.0, mid.1)),
+            [bottom, c10, center, right],
+        ),
+        (Region::new((min.0, mid.1), (mid.0, max.1)), [left, center, c01, top]),
+        (Region::new(mid, max), [center, right, top, c11]),
+    ];
+    let mut child = |k: usize| {
+        let (region, corners) = quadrants[k];
+        refine(source, region, corners, depth + 1, max_error, depths, samples)
+    };
+    QuadNode::Branch(Box::new([child(0), child(1), child(2), child(3)]))
+}
+
+impl QuadTree {
+    /// Getter function for region
+    pub fn get_region(&self) -> Region {
+        self.region
+    }
+    /// Getter function for the number of source evaluations
+    pub fn get_sample_count(&self) -> usize {
+        self.samples
+    }
+
+    /// Returns every leaf of the tree, depth first in corner order
+    pub fn leaves(&self) -> Vec<QuadLeaf> {
+        let mut leaves = Vec::new();
+        let mut stack = vec![(&self.root, self.region, 0)];
+        while let Some((node, region, depth)) = stack.pop() {
+            match node {
+                QuadNode::Leaf(corners) => leaves.push(QuadLeaf {
+                    region,
+                    depth,
+                    corners: *corners,
+                }),
+                QuadNode::Branch(children) => {
+                    // pushed in reverse so the first quadrant is visited first
+                    for k in (0..4).rev() {
+                        stack.push((&children[k], quadrant(region, k), depth + 1));
+                    }
+                }
+            }
+        }
+        leaves
+    }
+
+    /// Interpolates the value at a point from the leaf containing it
+    ///
+    /// Points outside the region are clamped to its edges.
+    pub fn get_value(&self, x: f64, y: f64) -> f64 {
+        let mut node = &self.root;
+        let mut region = self.region;
+        let x = x
+            .max(f64::min(region.min.0, region.max.0))
+            .min(f64::max(region.min.0, region.max.0));
+        let y = y
+            .max(f64::min(region.min.1, region.max.1))
+            .min(f64::max(region.min.1, region.max.1));
+        loop {
+            let (tx, ty) = (
+                (x - region.min.0) / region.width(),
+                (y - region.min.1) / region.height(),
+            );
+            let (tx, ty) = (finite_or_zero(tx), finite_or_zero(ty));
+            match node {
+                QuadNode::Leaf([c00, c10, c01, c11]) => {
+                    let bottom = c00 + (c10 - c00) * tx;
+                    let top = c01 + (c11 - c01) * tx;
+                    return bottom + (top - bottom) * ty;
+                }
+                QuadNode::Branch(children) => {
+                    let k = (tx >= 0.5) as usize + 2 * (ty >= 0.5) as usize;
+                    node = &children[k];
+                    region = quadrant(region, k);
+                }
+            }
+        }
+    }
+}
+
+fn finite_or_zero(t: f64) -> f64 {
+    if t.is_finite() {
+        t
+    } else {
+        0.0
+    }
+}
+
+/// Quadrant `k` of a region, in corner order
+fn quadrant(region: Region, k: usize) -> Region {
+    let (min, max) = (region.min, region.max);
+    let mid = ((min.0 + max.0) * 0.5, (min.1 + max.1) * 0.5);
+    let (x0, x1) = if k & 1 == 0 { (min.0, mid.0) } else { (mid.0, max.0) };
+    let (y0, y1) = if k & 2 == 0 { (min.1, mid.1) } else { (mid.1, max.1) };
+    Region::new((x0, y0), (x1, y1))
+}
@@ -0,0 +1,147 @@
+//! Interoperability with the `rand` crate, enabled by the `rand` feature.
+//!
+//! ```
+//! use perlin2d::rng::NoiseDistribution;
+//! use perlin2d::{PerlinNoise2D, Region};
+//! use rand::distributions::Distribution;
+//! use rand::rngs::mock::StepRng;
+//!
+//! let mut rng = StepRng::new(7, 0x9e37_79b9_7f4a_7c15);
+//! let perlin = PerlinNoise2D::from_rng(6, 1.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, &mut rng);
+//!
+//! let dist = NoiseDistribution::new(perlin, Region::new((0.0, 0.0), (512.0, 512.0)), 8);
+//! let values: Vec<f64> = dist.sample_iter(&mut rng).take(100).collect();
+//! assert!(values.iter().all(|v| v.abs() <= 1.0));
+//! ```
+
+use crate::{NoiseSource, PerlinNoise2D, Region};
+use rand::distributions::Distribution;
+use rand::{Rng, RngCore};
+
+impl PerlinNoise2D {
+    /// Create a new PerlinNoise2D object whose seed is drawn from `rng`
+    ///
+    /// Seeds are drawn from the full 32 bit range, like the ones of [`PerlinNoise2D::derive`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_rng<R: RngCore + ?Sized>(
+        octaves: i32,
+        amplitude: f64,
+        frequency: f64,
+        persistence: f64,
+        lacunarity: f64,
+        scale: (f64, f64),
+        bias: f64,
+        rng: &mut R,
+    ) -> Self {
+        let seed = rng.next_u32() as i32;
+        Self::new(
+            octaves,
+            amplitude,
+            frequency,
+            persistence,
+            lacunarity,
+            scale,
+            bias,
+            seed,
+        )
+    }
+}
+
+/// A [`Distribution`] of noise values taken along a Hilbert curve through a region
+///
+/// Member variables:
+///
+/// * `source` - The sampled noise source.
+/// * `region` - The area covered by the curve.
+/// * `order` - The curve visits `4^order` cells of the region, at most `31`.
+///
+/// Every sample draws a position along the curve from the random number generator and returns the noise at the
+/// center of that cell. [`NoiseDistribution::path`] walks the whole curve in order instead, which gives a
+/// deterministic sequence whose neighbors are always close in space.
+#[derive(Clone, Debug)]
+pub struct NoiseDistribution<S> {
+    source: S,
+    region: Region,
+    order: u32,
+}
+
+impl<S: NoiseSource> NoiseDistribution<S> {
+    /// Create and return a new NoiseDistribution object, `order` is clamped to `31`
+    pub fn new(source: S, region: Region, order: u32) -> Self {
+        Self {
+            source,
+            region,
+            order: order.min(31),
+        }
+    }
+
+    /// Getter function for the source
+    pub fn get_source(&self) -> &S {
+        &self.source
+    }
+    /// Getter function for region
+    pub fn get_region(&self) -> Region {
+        self.region
+    }
+    /// Getter function for order
+    pub fn get_order(&self) -> u32 {
+        self.order
+    }
+
+    /// Number of cells visited by the curve
+    pub fn len(&self) -> u64 {
+        1 << (2 * self.order)
+    }
+    /// Returns true if the curve visits no cells, which never happens
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Position of the cell center at distance `index` along the curve, wrapping around after [`NoiseDistribution::len`]
+    pub fn path_point(&self, index: u64) -> (f64, f64) {
+        let side = 1u64 << self.order;
+        let (i, j) = hilbert_cell(self.order, index % self.len());
+        (
+            self.region.min.0 + (i as f64 + 0.5) / side as f64 * self.region.width(),
+            self.region.min.1 + (j as f64 + 0.5) / side as f64 * self.region.height(),
+        )
+    }
+
+    /// Returns the noise values of every cell in curve order
+    pub fn path(&self) -> impl Iterator<Item = f64> + '_ {
+        (0..self.len()).map(move |index| {
+            let (x, y) = self.path_point(index);
+            self.source.get_noise(x, y)
+        })
+    }
+}
+
+impl<S: NoiseSource> Distribution<f64> for NoiseDistribution<S> {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        let (x, y) = self.path_point(rng.next_u64());
+        self.source.get_noise(x, y)
+    }
+}
+
+/// Cell of a Hilbert curve of the given order at distance `d` from its start
+fn hilbert_cell(order: u32, d: u64) -> (u64, u64) {
+    let (mut x, mut y) = (0u64, 0u64);
+    let mut t = d;
+    let mut s = 1u64;
+    while s < 1u64 << order {
+        let rx = 1 & (t / 2);
+        let ry = 1 & (t ^ rx);
+        if ry == 0 {
+            if rx == 1 {
+                x = s - 1 - x;
+                y = s - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        x += s * rx;
+        y += s * ry;
+        t /= 4;
+        s *= 2;
+    }
+    (x, y)
+}
@@ -0,0 +1,265 @@
+//! Incremental evaluation of dense grids.
+
+use crate::{AlgorithmVersion, FractalMode, LatticeHasher, NoiseMap, NoiseSource, PerlinNoise2D, Region};
+
+/// Row-major sampler that reuses lattice work between neighboring samples
+///
+/// Dense grids sample every lattice cell many times: consecutive samples of a row usually fall into the same cell,
+/// and consecutive rows cross the same cells again. A `RowSampler` keeps the smoothed corner values of the cells
+/// visited in the current lattice row of every octave, so only the first sample in each cell hashes the lattice.
+/// Results are identical to [`PerlinNoise2D::get_noise`].
+///
+/// Samples should be taken row by row with increasing `x`, calling [`RowSampler::begin_row`] at the start of every
+/// row. Other orders still return correct values but reuse less work.
+///
+/// ```
+/// use perlin2d::sampler::RowSampler;
+/// use perlin2d::PerlinNoise2D;
+///
+/// let perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 9);
+/// let mut sampler = RowSampler::new(&perlin);
+/// for y in 0..64 {
+///     sampler.begin_row();
+///     for x in 0..64 {
+///         let (x, y) = (x as f64, y as f64);
+///         assert_eq!(sampler.sample(x, y), perlin.get_noise(x, y));
+///     }
+/// }
+/// ```
+pub struct RowSampler<'a, H> {
+    perlin: &'a PerlinNoise2D<H>,
+    octaves: Vec<OctaveCache>,
+}
+
+/// Corner values of the cells along one lattice row of an octave
+struct OctaveCache {
+    row: Option<i64>,
+    cells: Vec<(i64, [f64; 4])>,
+    cursor: usize,
+}
+
+impl<'a, H: LatticeHasher> RowSampler<'a, H> {
+    /// Create and return a new RowSampler object for a generator
+    pub fn new(perlin: &'a PerlinNoise2D<H>) -> Self {
+        let octaves = (0..perlin.octaves.max(0))
+            .map(|_| OctaveCache {
+                row: None,
+                cells: Vec::new(),
+                cursor: 0,
+            })
+            .collect();
+        Self { perlin, octaves }
+    }
+
+    /// Getter function for the generator
+    pub fn get_perlin(&self) -> &'a PerlinNoise2D<H> {
+        self.perlin
+    }
+
+    /// Start a new row of samples
+    pub fn begin_row(&mut self) {
+        for cache in self.octaves.iter_mut() {
+            cache.cursor = 0;
+        }
+    }
+
+    /// Returns the noise value at the given point
+    pub fn sample(&mut self, x: f64, y: f64) -> f64 {
+        let perlin = self.perlin;
+        if perlin.version == AlgorithmVersion::ImprovedGradient {
+            // gradient noise depends on the position inside the cell, so corner values can not be shared
+            return perlin.get_noise(x, y);
+        }
+        let offset = match perlin.mode {
+            FractalMode::Fbm => None,
+            FractalMode::HybridMultifractal { offset } => Some(offset),
+            // warped octaves jump between cells, so there is nothing to reuse
+            _ => return perlin.get_noise(x, y),
+        };
+        let (x, y) = perlin.to_unit_space(x, y);
+        let seed = perlin.seed as f64;
+        let mut t = 0.0;
+        let mut amp = 1.0;
+        let mut weight = 1.0;
+        let (mut freq_x, mut freq_y) = perlin.frequency;
+
+        for cache in self.octaves.iter_mut() {
+            // same argument order as the generator, which samples the lattice with swapped axes
+            let value = cache.value(perlin, y * freq_y + seed, x * freq_x + seed);
+            match offset {
+                None => t += value * amp,
+                Some(offset) => {
+                    let signal = (value + offset) * amp;
+                    t += weight * signal;
+                    weight = f64::clamp(weight * signal, 0.0, 1.0);
+                }
+            }
+            amp *= perlin.persistence;
+            freq_x *= perlin.lacunarity;
+            freq_y *= perlin.lacunarity;
+        }
+        perlin.bias + perlin.get_effective_amplitude() * perlin.shape(t)
+    }
+
+    /// Samples the region at the given `(width, height)` resolution into `out`, row by row
+    ///
+    /// Panics if `out` does not hold exactly `width * height` elements.
+    pub fn fill_region(&mut self, region: Region, resolution: (usize, usize), out: &mut [f64]) {
+        assert_eq!(
+            out.len(),
+            resolution.0 * resolution.1,
+            "output does not match the resolution"
+        );
+        self.fill_rows(region, resolution, 0, out);
+    }
+
+    /// Samples the rows of a region from `first_row` on into `out`, see [`NoiseSource::fill_rows`]
+    ///
+    /// Panics if `out` does not hold whole rows or holds rows past the last one of the resolution.
+    pub fn fill_rows(&mut self, region: Region, resolution: (usize, usize), first_row: usize, out: &mut [f64]) {
+        let width = resolution.0;
+        if width == 0 {
+            return;
+        }
+        assert!(
+            out.len().is_multiple_of(width) && first_row + out.len() / width <= resolution.1,
+            "output does not match the rows of the resolution"
+        );
+        for (k, row) in out.chunks_mut(width).enumerate() {
+            self.begin_row();
+            for (i, value) in row.iter_mut().enumerate() {
+                let (x, y) = region.sample_point(resolution, i, first_row + k);
+                *value = self.sample(x, y);
+            }
+        }
+    }
+}
+
+/// Answers queries inside a region from a precomputed grid and falls back to the source outside it
+///
+/// The region is divided into `(columns, rows)` cells whose corners are sampled once up front. Points inside the
+/// region, edges included, are interpolated bilinearly from the four corners of their cell, which costs about as much
+/// as a single octave lookup whatever the source. Points outside are passed to the source unchanged. The resolution is
+/// the speed/accuracy knob: cells a fraction of the wavelength of the finest octave that matters keep the error small,
+/// coarser cells smooth out detail.
+///
+/// ```
+/// use perlin2d::sampler::CachedSampler;
+/// use perlin2d::{NoiseSource, PerlinNoise2D, Region};
+///
+/// let perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 9);
+/// let sampler = CachedSampler::new(&perlin, Region::new((0.0, 0.0), (512.0, 512.0)), (256, 256));
+///
+/// // grid points are exact, points in between are close
+/// assert_eq!(sampler.sample(4.0, 6.0), perlin.get_noise(4.0, 6.0));
+/// assert!((sampler.sample(4.7, 6.3) - perlin.get_noise(4.7, 6.3)).abs() < 0.01);
+///
+/// // outside the region the source answers
+/// assert_eq!(sampler.sample(-3.0, 600.0), perlin.get_noise(-3.0, 600.0));
+/// ```
+#[derive(Clone, Debug)]
+pub struct CachedSampler<S> {
+    source: S,
+    region: Region,
+    cells: (usize, usize),
+    grid: NoiseMap,
+}
+
+impl<S: NoiseSource> CachedSampler<S> {
+    /// Create and return a new CachedSampler object, sampling the corners of `(columns, rows)` cells of the region
+    pub fn new(source: S, region: Region, cells: (usize, usize)) -> Self {
+        let (step_x, step_y) = region.step(cells);
+        // one more sample along each axis, so the last one lands on the maximum edge
+        let corners = Region::new(region.min, (region.max.0 + step_x, region.max.1 + step_y));
+        let grid = if cells.0 == 0 || cells.1 == 0 {
+            NoiseMap::new(region, (0, 0))
+        } else {
+            source.generate_map(corners, (cells.0 + 1, cells.1 + 1))
+        };
+        Self {
+            source,
+            region,
+            cells,
+            grid,
+        }
+    }
+
+    /// Getter function for the source
+    pub fn get_source(&self) -> &S {
+        &self.source
+    }
+    /// Getter function for region
+    pub fn get_region(&self) -> Region {
+        self.region
+    }
+    /// Getter function for the `(columns, rows)` of cells
+    pub fn get_cells(&self) -> (usize, usize) {
+        self.cells
+    }
+
+    /// Whether `(x, y)` is answered from the grid
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        let (min, max) = (self.region.min, self.region.max);
+        !self.grid.get_values().is_empty() && x >= min.0 && x <= max.0 && y >= min.1 && y <= max.1
+    }
+
+    /// Returns the interpolated value at `(x, y)` inside the region, or the exact value of the source outside it
+    pub fn sample(&self, x: f64, y: f64) -> f64 {
+        if !self.contains(x, y) {
+            return self.source.get_noise(x, y);
+        }
+        let (u, v) = self.region.coord_map(self.cells).world_to_grid(x, y);
+        // points on the maximum edge belong to the last cell
+        let i = usize::min(u as usize, self.cells.0 - 1);
+        let j = usize::min(v as usize, self.cells.1 - 1);
+        let (tx, ty) = (u - i as f64, v - j as f64);
+        let grid = &self.grid;
+        let bottom = grid.get_value(i, j) + (grid.get_value(i + 1, j) - grid.get_value(i, j)) * tx;
+        let top = grid.get_value(i, j + 1) + (grid.get_value(i + 1, j + 1) - grid.get_value(i, j + 1)) * tx;
+        bottom + (top - bottom) * ty
+    }
+}
+
+impl<S: NoiseSource> NoiseSource for CachedSampler<S> {
+    fn get_noise(&self, x: f64, y: f64) -> f64 {
+        self.sample(x, y)
+    }
+
+    fn get_range(&self) -> (f64, f64) {
+        // interpolation never leaves the range of the corner values
+        self.source.get_range()
+    }
+}
+
+impl OctaveCache {
+    #[inline]
+    fn value<H: LatticeHasher>(&mut self, perlin: &PerlinNoise2D<H>, a: f64, b: f64) -> f64 {
+        let row = perlin.lattice(a)[1];
+        let cell = perlin.lattice(b)[1];
+        if self.row != Some(row) {
+            self.row = Some(row);
+            self.cells.clear();
+            self.cursor = 0;
+        }
+
+        let corners = match self.cells.get(self.cursor) {
+            Some(&(key, corners)) if key == cell => corners,
+            _ => match self.cells.get(self.cursor + 1) {
+                Some(&(key, corners)) if key == cell => {
+                    self.cursor += 1;
+                    corners
+                }
+                _ => {
+                    let corners = perlin.corners(a, b);
+                    // keep the cells in visiting order, dropping any that were passed over
+                    let keep = usize::min(self.cursor + 1, self.cells.len());
+                    self.cells.truncate(keep);
+                    self.cells.push((cell, corners));
+                    self.cursor = self.cells.len() - 1;
+                    corners
+                }
+            },
+        };
+        perlin.interpolate_corners(corners, a - f64::floor(a), b - f64::floor(b))
+    }
+}
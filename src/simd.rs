@@ -0,0 +1,228 @@
+//! Vectorized versions of the built-in lattice hashes.
+//!
+//! The prime hash only keeps the lower 31 bits of its 64 bit result, and those only depend on the lower 32 bits of
+//! every intermediate value, so it can be computed in wrapping 32 bit lanes with identical results. The fastest
+//! [`SimdBackend`] supported by the running CPU is detected once and then used for every lattice block, both when
+//! sampling single points and when filling grids.
+//!
+//! Every backend produces bit-identical output:
+//!
+//! ```
+//! use perlin2d::simd::SimdBackend;
+//!
+//! let backends = [SimdBackend::Scalar, SimdBackend::Avx2, SimdBackend::Neon, SimdBackend::Simd128];
+//! for y in (-40_000i64..40_000).step_by(997) {
+//!     for x in (-40_000i64..40_000).step_by(991) {
+//!         let xs = [x - 1, x, x + 1, x + 2];
+//!         let ys = [y - 1, y, y + 1, y + 2];
+//!         let expected = SimdBackend::Scalar.prime_hash_block(xs, ys).unwrap();
+//!         for backend in backends.iter() {
+//!             if let Some(block) = backend.prime_hash_block(xs, ys) {
+//!                 assert_eq!(block, expected, "{:?} differs from the scalar backend", backend);
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::hash::scalar_block;
+use crate::PrimeHasher;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// Instruction set used to hash lattice blocks
+///
+/// * `Scalar` - Plain 64 bit arithmetic, available everywhere.
+/// * `Avx2` - 256 bit AVX2 instructions on x86_64, detected at runtime. Without the `std` feature it is only used
+///   when the `avx2` target feature is enabled at compile time.
+/// * `Neon` - 128 bit NEON instructions on aarch64, detected at runtime. Without the `std` feature it is only used
+///   when the `neon` target feature is enabled at compile time.
+/// * `Simd128` - 128 bit WebAssembly SIMD, used when compiling for wasm32 with the `simd128` target feature.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SimdBackend {
+    Scalar,
+    Avx2,
+    Neon,
+    Simd128,
+}
+
+static DETECTED: AtomicU8 = AtomicU8::new(u8::MAX);
+
+impl SimdBackend {
+    /// Returns the fastest backend supported by the running CPU
+    ///
+    /// Detection runs once, later calls return the cached result.
+    #[inline]
+    pub fn detect() -> SimdBackend {
+        match DETECTED.load(Ordering::Relaxed) {
+            0 => SimdBackend::Scalar,
+            1 => SimdBackend::Avx2,
+            2 => SimdBackend::Neon,
+            3 => SimdBackend::Simd128,
+            _ => {
+                let backend = Self::probe();
+                DETECTED.store(backend as u8, Ordering::Relaxed);
+                backend
+            }
+        }
+    }
+
+    fn probe() -> SimdBackend {
+        [SimdBackend::Simd128, SimdBackend::Avx2, SimdBackend::Neon]
+            .iter()
+            .cloned()
+            .find(|backend| backend.is_available())
+            .unwrap_or(SimdBackend::Scalar)
+    }
+
+    /// Returns true if the backend can run on this CPU
+    pub fn is_available(self) -> bool {
+        match self {
+            SimdBackend::Scalar => true,
+            #[cfg(all(target_arch = "x86_64", feature = "std"))]
+            SimdBackend::Avx2 => is_x86_feature_detected!("avx2"),
+            #[cfg(all(target_arch = "aarch64", feature = "std"))]
+            SimdBackend::Neon => std::arch::is_aarch64_feature_detected!("neon"),
+            // without the standard library only the target features enabled at compile time are used
+            #[cfg(all(target_arch = "x86_64", target_feature = "avx2", not(feature = "std")))]
+            SimdBackend::Avx2 => true,
+            #[cfg(all(target_arch = "aarch64", target_feature = "neon", not(feature = "std")))]
+            SimdBackend::Neon => true,
+            #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+            SimdBackend::Simd128 => true,
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+
+    /// Prime hash of the 4x4 block formed by `xs` and `ys` using this backend, `block[j][i]` is the value of
+    /// `(xs[i], ys[j])`
+    ///
+    /// Returns `None` if the backend is not available on this CPU.
+    pub fn prime_hash_block(self, xs: [i64; 4], ys: [i64; 4]) -> Option<[[f64; 4]; 4]> {
+        if self.is_available() {
+            Some(hash_block_with(self, xs, ys))
+        } else {
+            None
+        }
+    }
+}
+
+/// Prime hash of a lattice block with the detected backend
+#[inline]
+pub(crate) fn prime_hash_block(xs: [i64; 4], ys: [i64; 4]) -> [[f64; 4]; 4] {
+    hash_block_with(SimdBackend::detect(), xs, ys)
+}
+
+/// Callers must only pass backends that are available
+#[inline]
+fn hash_block_with(backend: SimdBackend, xs: [i64; 4], ys: [i64; 4]) -> [[f64; 4]; 4] {
+    match backend {
+        // SAFETY: the backend is only selected when the CPU supports AVX2
+        #[cfg(target_arch = "x86_64")]
+        SimdBackend::Avx2 => unsafe { avx2::prime_hash_block(xs, ys) },
+        // SAFETY: the backend is only selected when the CPU supports NEON
+        #[cfg(target_arch = "aarch64")]
+        SimdBackend::Neon => unsafe { neon::prime_hash_block(xs, ys) },
+        #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+        SimdBackend::Simd128 => wasm::prime_hash_block(xs, ys),
+        _ => scalar_block(&PrimeHasher, xs, ys),
+    }
+}
+
+const SCALE: f64 = 9.313_225_746_154_785e-10;
+
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use core::arch::x86_64::*;
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn prime_hash_block(xs: [i64; 4], ys: [i64; 4]) -> [[f64; 4]; 4] {
+        let scale = _mm256_set1_pd(super::SCALE);
+        let one = _mm256_set1_pd(1.0);
+        // truncating to 32 bits keeps exactly the bits the hash depends on
+        let (x0, x1, x2, x3) = (xs[0] as i32, xs[1] as i32, xs[2] as i32, xs[3] as i32);
+        let x = _mm256_setr_epi32(x0, x1, x2, x3, x0, x1, x2, x3);
+        let mut block = [[0.0; 4]; 4];
+        // two rows per vector, the lower half holds row j and the upper half row j + 1
+        for j in (0..4).step_by(2) {
+            let y0 = (ys[j] as i32).wrapping_mul(57);
+            let y1 = (ys[j + 1] as i32).wrapping_mul(57);
+            let n = _mm256_add_epi32(x, _mm256_setr_epi32(y0, y0, y0, y0, y1, y1, y1, y1));
+            let n = _mm256_xor_si256(_mm256_slli_epi32::<13>(n), n);
+            let t = _mm256_add_epi32(
+                _mm256_mullo_epi32(n, _mm256_set1_epi32(15731)),
+                _mm256_set1_epi32(789221),
+            );
+            let t = _mm256_add_epi32(
+                _mm256_mullo_epi32(_mm256_mullo_epi32(n, n), t),
+                _mm256_set1_epi32(1376312589),
+            );
+            let t = _mm256_and_si256(t, _mm256_set1_epi32(0x7fffffff));
+            let low = _mm256_cvtepi32_pd(_mm256_castsi256_si128(t));
+            let high = _mm256_cvtepi32_pd(_mm256_extracti128_si256::<1>(t));
+            let low = _mm256_sub_pd(one, _mm256_mul_pd(low, scale));
+            let high = _mm256_sub_pd(one, _mm256_mul_pd(high, scale));
+            _mm256_storeu_pd(block[j].as_mut_ptr(), low);
+            _mm256_storeu_pd(block[j + 1].as_mut_ptr(), high);
+        }
+        block
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use core::arch::aarch64::*;
+
+    #[target_feature(enable = "neon")]
+    pub(super) unsafe fn prime_hash_block(xs: [i64; 4], ys: [i64; 4]) -> [[f64; 4]; 4] {
+        let scale = vdupq_n_f64(super::SCALE);
+        let one = vdupq_n_f64(1.0);
+        // truncating to 32 bits keeps exactly the bits the hash depends on
+        let lanes = [xs[0] as i32, xs[1] as i32, xs[2] as i32, xs[3] as i32];
+        let x = vld1q_s32(lanes.as_ptr());
+        let mut block = [[0.0; 4]; 4];
+        for (row, &y) in block.iter_mut().zip(ys.iter()) {
+            let n = vaddq_s32(x, vdupq_n_s32((y as i32).wrapping_mul(57)));
+            let n = veorq_s32(vshlq_n_s32::<13>(n), n);
+            let t = vaddq_s32(vmulq_s32(n, vdupq_n_s32(15731)), vdupq_n_s32(789221));
+            let t = vaddq_s32(vmulq_s32(vmulq_s32(n, n), t), vdupq_n_s32(1376312589));
+            let t = vandq_s32(t, vdupq_n_s32(0x7fffffff));
+            let low = vcvtq_f64_s64(vmovl_s32(vget_low_s32(t)));
+            let high = vcvtq_f64_s64(vmovl_high_s32(t));
+            vst1q_f64(row.as_mut_ptr(), vsubq_f64(one, vmulq_f64(low, scale)));
+            vst1q_f64(row.as_mut_ptr().add(2), vsubq_f64(one, vmulq_f64(high, scale)));
+        }
+        block
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+mod wasm {
+    use core::arch::wasm32::*;
+
+    #[inline]
+    pub(super) fn prime_hash_block(xs: [i64; 4], ys: [i64; 4]) -> [[f64; 4]; 4] {
+        let scale = f64x2_splat(super::SCALE);
+        let one = f64x2_splat(1.0);
+        // truncating to 32 bits keeps exactly the bits the hash depends on
+        let x = i32x4(xs[0] as i32, xs[1] as i32, xs[2] as i32, xs[3] as i32);
+        let mut block = [[0.0; 4]; 4];
+        for (row, &y) in block.iter_mut().zip(ys.iter()) {
+            let n = i32x4_add(x, i32x4_splat((y as i32).wrapping_mul(57)));
+            let n = v128_xor(i32x4_shl(n, 13), n);
+            let t = i32x4_add(i32x4_mul(n, i32x4_splat(15731)), i32x4_splat(789221));
+            let t = i32x4_add(i32x4_mul(i32x4_mul(n, n), t), i32x4_splat(1376312589));
+            let t = v128_and(t, i32x4_splat(0x7fffffff));
+            let low = f64x2_sub(one, f64x2_mul(f64x2_convert_low_i32x4(t), scale));
+            let high = i32x4_shuffle::<2, 3, 2, 3>(t, t);
+            let high = f64x2_sub(one, f64x2_mul(f64x2_convert_low_i32x4(high), scale));
+            *row = [
+                f64x2_extract_lane::<0>(low),
+                f64x2_extract_lane::<1>(low),
+                f64x2_extract_lane::<0>(high),
+                f64x2_extract_lane::<1>(high),
+            ];
+        }
+        block
+    }
+}
@@ -0,0 +1,571 @@
+//! Common interface shared by every noise generator and combinator in the crate.
+
+use crate::sampler::RowSampler;
+use crate::{AlgorithmVersion, FractalMode, Interpolation, LatticeHasher, NoiseMap, NoisePoint, PerlinNoise2D, Region};
+
+/// A two dimensional noise function
+///
+/// Implemented by [`PerlinNoise2D`] and by the combinators built on top of it, so generators can be nested and mixed
+/// freely. Boxed trait objects and references are sources as well, which allows collections of different generator
+/// types.
+pub trait NoiseSource {
+    /// Returns the noise value at the given point
+    fn get_noise(&self, x: f64, y: f64) -> f64;
+
+    /// Returns the `(min, max)` bounds that [`NoiseSource::get_noise`] can output
+    ///
+    /// The bounds are conservative: values are guaranteed to lie inside them, but are not guaranteed to reach them.
+    fn get_range(&self) -> (f64, f64);
+
+    /// Returns a bound on how much the value can differ between two points of `region` that lie at most `step` apart
+    /// along each axis
+    ///
+    /// Used by [`NoiseSource::range_in_region`] to turn sampled extrema into guaranteed bounds. The default of
+    /// infinity means nothing is known about the source, which makes the region range fall back to
+    /// [`NoiseSource::get_range`].
+    fn get_variation_bound(&self, region: Region, step: (f64, f64)) -> f64 {
+        let _ = (region, step);
+        f64::INFINITY
+    }
+
+    /// Samples the region at the given `(width, height)` resolution into `out`, row by row
+    ///
+    /// Panics if `out` does not hold exactly `width * height` elements.
+    fn fill_region(&self, region: Region, resolution: (usize, usize), out: &mut [f64]) {
+        assert_eq!(
+            out.len(),
+            resolution.0 * resolution.1,
+            "output does not match the resolution"
+        );
+        if resolution.0 == 0 {
+            return;
+        }
+        for (j, row) in out.chunks_mut(resolution.0).enumerate() {
+            for (i, value) in row.iter_mut().enumerate() {
+                let (x, y) = region.sample_point(resolution, i, j);
+                *value = self.get_noise(x, y);
+            }
+        }
+    }
+
+    /// Samples the rows of a region from `first_row` on into `out`, which holds a whole number of rows
+    ///
+    /// The values are the ones [`NoiseSource::fill_region`] writes for those rows, so a region can be filled in bands.
+    ///
+    /// Panics if `out` does not hold whole rows or holds rows past the last one of the resolution.
+    fn fill_rows(&self, region: Region, resolution: (usize, usize), first_row: usize, out: &mut [f64]) {
+        let width = resolution.0;
+        if width == 0 {
+            return;
+        }
+        assert!(
+            out.len().is_multiple_of(width) && first_row + out.len() / width <= resolution.1,
+            "output does not match the rows of the resolution"
+        );
+        for (k, row) in out.chunks_mut(width).enumerate() {
+            for (i, value) in row.iter_mut().enumerate() {
+                let (x, y) = region.sample_point(resolution, i, first_row + k);
+                *value = self.get_noise(x, y);
+            }
+        }
+    }
+
+    /// Samples every point of `points` into the matching element of `out`
+    ///
+    /// Panics if `out` and `points` have different lengths.
+    fn sample_points_into(&self, points: &[(f64, f64)], out: &mut [f64]) {
+        assert_eq!(out.len(), points.len(), "output does not match the number of points");
+        for (value, &(x, y)) in out.iter_mut().zip(points) {
+            *value = self.get_noise(x, y);
+        }
+    }
+
+    /// Samples points given as separate coordinate slices, writing `out[i]` for the point `(xs[i], ys[i])`
+    ///
+    /// Panics if `xs`, `ys` and `out` do not all have the same length.
+    fn get_noise_soa(&self, xs: &[f64], ys: &[f64], out: &mut [f64]) {
+        assert!(
+            xs.len() == ys.len() && xs.len() == out.len(),
+            "coordinate and output slices must have the same length"
+        );
+        for ((value, &x), &y) in out.iter_mut().zip(xs).zip(ys) {
+            *value = self.get_noise(x, y);
+        }
+    }
+
+    /// Samples a batch of scattered points and returns their values in the same order
+    fn sample_points<I: IntoIterator<Item = (f64, f64)>>(&self, points: I) -> Vec<f64>
+    where
+        Self: Sized,
+    {
+        let points: Vec<(f64, f64)> = points.into_iter().collect();
+        let mut out = vec![0.0; points.len()];
+        self.sample_points_into(&points, &mut out);
+        out
+    }
+
+    /// Returns the noise value at a point given as any [`NoisePoint`], such as a `glam` or `nalgebra` vector
+    fn get_noise_vec<P: NoisePoint>(&self, p: P) -> f64
+    where
+        Self: Sized,
+    {
+        let (x, y) = p.coords();
+        self.get_noise(x, y)
+    }
+
+    /// Samples a batch of points given as any [`NoisePoint`] and returns their values in the same order
+    fn sample_vecs<P: NoisePoint>(&self, points: &[P]) -> Vec<f64>
+    where
+        Self: Sized,
+    {
+        let points: Vec<(f64, f64)> = points.iter().map(NoisePoint::coords).collect();
+        let mut out = vec![0.0; points.len()];
+        self.sample_points_into(&points, &mut out);
+        out
+    }
+
+    /// Samples the region at the given `(width, height)` resolution and returns the resulting map
+    fn generate_map(&self, region: Region, resolution: (usize, usize)) -> NoiseMap {
+        let mut map = NoiseMap::new(region, resolution);
+        self.fill_region(region, resolution, map.get_values_mut());
+        map
+    }
+
+    /// Samples the region like [`NoiseSource::fill_region`], split into bands of rows across `parallelism` threads
+    ///
+    /// The bands run on scoped standard library threads, so no thread pool or extra dependency is involved. Every band
+    /// is sampled through [`NoiseSource::fill_rows`] and the values are identical to a single threaded fill. Sources
+    /// that batch [`NoiseSource::fill_region`] should batch `fill_rows` as well, or bands fall back to sampling point
+    /// by point. A `parallelism` of `1` or less fills on the calling thread without spawning.
+    ///
+    /// Panics if `out` does not hold exactly `width * height` elements.
+    ///
+    /// ```
+    /// use perlin2d::{NoiseSource, PerlinNoise2D, Region};
+    ///
+    /// let perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 42);
+    /// let region = Region::new((0.0, 0.0), (256.0, 256.0));
+    ///
+    /// let mut out = vec![0.0; 128 * 96];
+    /// perlin.fill_region_threaded(region, (128, 96), &mut out, 4);
+    /// assert_eq!(out, perlin.generate_map(region, (128, 96)).into_values());
+    /// ```
+    fn fill_region_threaded(&self, region: Region, resolution: (usize, usize), out: &mut [f64], parallelism: usize)
+    where
+        Self: Sized + Sync,
+    {
+        assert_eq!(
+            out.len(),
+            resolution.0 * resolution.1,
+            "output does not match the resolution"
+        );
+        let threads = usize::min(parallelism, resolution.1);
+        if threads <= 1 || resolution.0 == 0 {
+            self.fill_region(region, resolution, out);
+            return;
+        }
+        let rows = resolution.1.div_ceil(threads);
+        std::thread::scope(|scope| {
+            for (band, values) in out.chunks_mut(rows * resolution.0).enumerate() {
+                scope.spawn(move || self.fill_rows(region, resolution, band * rows, values));
+            }
+        });
+    }
+
+    /// Samples the region like [`NoiseSource::generate_map`] across `parallelism` threads, see
+    /// [`NoiseSource::fill_region_threaded`]
+    fn generate_map_threaded(&self, region: Region, resolution: (usize, usize), parallelism: usize) -> NoiseMap
+    where
+        Self: Sized + Sync,
+    {
+        let mut map = NoiseMap::new(region, resolution);
+        self.fill_region_threaded(region, resolution, map.get_values_mut(), parallelism);
+        map
+    }
+
+    /// Samples points like [`NoiseSource::sample_points_into`], split into contiguous batches across `parallelism`
+    /// threads
+    ///
+    /// Panics if `out` and `points` have different lengths.
+    fn sample_points_into_threaded(&self, points: &[(f64, f64)], out: &mut [f64], parallelism: usize)
+    where
+        Self: Sized + Sync,
+    {
+        assert_eq!(out.len(), points.len(), "output does not match the number of points");
+        let threads = usize::min(parallelism, points.len());
+        if threads <= 1 {
+            self.sample_points_into(points, out);
+            return;
+        }
+        let batch = points.len().div_ceil(threads);
+        std::thread::scope(|scope| {
+            for (values, points) in out.chunks_mut(batch).zip(points.chunks(batch)) {
+                scope.spawn(move || self.sample_points_into(points, values));
+            }
+        });
+    }
+
+    /// Returns a stable checksum of the values generated over the region, see [`NoiseMap::digest`]
+    ///
+    /// Two machines generating the same source agree on the digest exactly when they agree on every value, so
+    /// comparing digests is a cheap way to detect diverging terrain between clients and a server.
+    ///
+    /// ```
+    /// use perlin2d::{NoiseSource, PerlinNoise2D, Region};
+    ///
+    /// let perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 42);
+    /// let region = Region::new((0.0, 0.0), (256.0, 256.0));
+    /// let digest = perlin.region_digest(region, (64, 64));
+    ///
+    /// assert_eq!(digest, perlin.generate_map(region, (64, 64)).digest());
+    /// let other = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 43);
+    /// assert_ne!(digest, other.region_digest(region, (64, 64)));
+    /// ```
+    fn region_digest(&self, region: Region, resolution: (usize, usize)) -> u64 {
+        self.generate_map(region, resolution).digest()
+    }
+
+    /// Returns conservative `(min, max)` bounds of the values inside a region
+    ///
+    /// The region is sampled on a grid of `resolution_hint` cells that includes its far edges, so every point of the
+    /// region lies within half a cell of a sample. The lowest and highest samples are then widened by
+    /// [`NoiseSource::get_variation_bound`] for half a cell and clipped to [`NoiseSource::get_range`]. Values inside
+    /// the region are guaranteed to lie within the bounds, which makes them safe for budgeting mesh extents or
+    /// deciding whether a chunk can hold water. For [`PerlinNoise2D`] the widening is the slope bound of every
+    /// octave times half the sample spacing, so the bounds tighten as the resolution rises. Sources without a
+    /// variation bound return their full range.
+    ///
+    /// ```
+    /// use perlin2d::{AlgorithmVersion, NoiseSource, PerlinNoise2D, Region};
+    ///
+    /// let mut perlin = PerlinNoise2D::new(6, 100.0, 0.5, 0.5, 2.0, (200.0, 200.0), 0.0, 3);
+    /// perlin.set_version(AlgorithmVersion::V3);
+    /// let region = Region::new((0.0, 0.0), (64.0, 64.0));
+    /// let (lo, hi) = perlin.range_in_region(region, (32, 32));
+    ///
+    /// // every value of the region lies inside, even between the sampled points
+    /// let map = perlin.generate_map(region, (256, 256));
+    /// assert!(map.get_values().iter().all(|&v| lo <= v && v <= hi));
+    /// let (range_lo, range_hi) = perlin.get_range();
+    /// assert!(range_lo < lo && hi < range_hi);
+    /// ```
+    fn range_in_region(&self, region: Region, resolution_hint: (usize, usize)) -> (f64, f64) {
+        let cells = (resolution_hint.0.max(1), resolution_hint.1.max(1));
+        let step = region.step(cells);
+        // one more sample along each axis, so the samples reach the far edges of the region
+        let grid = Region::new(region.min, (region.max.0 + step.0, region.max.1 + step.1));
+        let map = self.generate_map(grid, (cells.0 + 1, cells.1 + 1));
+        let (lo, hi) = map
+            .get_values()
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+                (f64::min(lo, v), f64::max(hi, v))
+            });
+        let change = self.get_variation_bound(region, (0.5 * step.0.abs(), 0.5 * step.1.abs()));
+        let (range_lo, range_hi) = self.get_range();
+        // written so that a NaN bound falls back to the range as well
+        let known = change < f64::INFINITY;
+        if !known {
+            return (range_lo, range_hi);
+        }
+        (f64::max(lo - change, range_lo), f64::min(hi + change, range_hi))
+    }
+
+    /// Returns the point closest to `p0` where the noise crosses `iso` on the segment from `p0` to `p1`
+    ///
+    /// The segment is sampled at [`CROSSING_SAMPLES`] evenly spaced points and the first pair of samples on opposite
+    /// sides of `iso` is narrowed down by bisection. Crossings that enter and leave between two samples are missed.
+    /// Returns `None` when no crossing is found.
+    ///
+    /// ```
+    /// use perlin2d::{NoiseSource, PerlinNoise2D};
+    ///
+    /// let perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 12);
+    /// let (p0, p1) = ((0.0, 0.0), (500.0, 0.0));
+    /// let iso = 0.5 * (perlin.get_noise(p0.0, p0.1) + perlin.get_noise(p1.0, p1.1));
+    ///
+    /// // the endpoints lie on opposite sides of the threshold, so there is always a crossing
+    /// let (x, y) = perlin.find_crossing(p0, p1, iso).unwrap();
+    /// assert!((perlin.get_noise(x, y) - iso).abs() < 1e-9);
+    /// ```
+    fn find_crossing(&self, p0: (f64, f64), p1: (f64, f64), iso: f64) -> Option<(f64, f64)> {
+        let point = |t: f64| (p0.0 + (p1.0 - p0.0) * t, p0.1 + (p1.1 - p0.1) * t);
+        let side = |t: f64| {
+            let (x, y) = point(t);
+            self.get_noise(x, y) - iso
+        };
+
+        let mut a = 0.0;
+        let mut fa = side(a);
+        if fa == 0.0 {
+            return Some(p0);
+        }
+        for k in 1..=CROSSING_SAMPLES {
+            let b = k as f64 / CROSSING_SAMPLES as f64;
+            let fb = side(b);
+            if fb == 0.0 {
+                return Some(point(b));
+            }
+            if (fa < 0.0) != (fb < 0.0) {
+                let mut b = b;
+                // bisect until the interval stops shrinking in f64
+                for _ in 0..64 {
+                    let m = 0.5 * (a + b);
+                    if m <= a || m >= b {
+                        break;
+                    }
+                    let fm = side(m);
+                    if fm == 0.0 {
+                        return Some(point(m));
+                    }
+                    if (fa < 0.0) == (fm < 0.0) {
+                        a = m;
+                        fa = fm;
+                    } else {
+                        b = m;
+                    }
+                }
+                return Some(point(0.5 * (a + b)));
+            }
+            a = b;
+            fa = fb;
+        }
+        None
+    }
+
+    /// Marches a ray against the terrain whose height at `(x, y)` is the noise value and returns the first hit
+    ///
+    /// `origin` and `direction` are `(x, y, z)` triples with `z` pointing up, the direction does not need to be
+    /// normalized. The ray advances by `step` until it passes below the surface or travels `max_dist`, and the hit is
+    /// then refined by bisection. Features thinner than `step` may be passed through. At most [`RAYCAST_STEPS`] steps
+    /// are taken, so a longer ray stops after `RAYCAST_STEPS * step`. An origin below the surface is returned as the
+    /// hit. Returns `None` if nothing is hit, the direction is zero or `max_dist` or `step` is not finite.
+    ///
+    /// ```
+    /// use perlin2d::{NoiseSource, PerlinNoise2D};
+    ///
+    /// let perlin = PerlinNoise2D::new(6, 20.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 5);
+    /// // looking down from above the highest possible terrain
+    /// let (x, y, z) = perlin.raycast((10.0, 20.0, 50.0), (1.0, 0.5, -1.0), 500.0, 0.5).unwrap();
+    /// assert!((z - perlin.get_noise(x, y)).abs() < 1e-6);
+    ///
+    /// // looking up never hits
+    /// assert_eq!(perlin.raycast((10.0, 20.0, 50.0), (1.0, 0.0, 1.0), 500.0, 0.5), None);
+    ///
+    /// // an unbounded ray is rejected instead of marching forever
+    /// assert_eq!(perlin.raycast((10.0, 20.0, 50.0), (1.0, 0.0, 1.0), f64::INFINITY, 0.5), None);
+    /// ```
+    fn raycast(
+        &self,
+        origin: (f64, f64, f64),
+        direction: (f64, f64, f64),
+        max_dist: f64,
+        step: f64,
+    ) -> Option<(f64, f64, f64)> {
+        let length = f64::sqrt(direction.0 * direction.0 + direction.1 * direction.1 + direction.2 * direction.2);
+        if !length.is_normal() || !step.is_normal() || step < 0.0 || !max_dist.is_finite() {
+            return None;
+        }
+        let dir = (direction.0 / length, direction.1 / length, direction.2 / length);
+        let point = |t: f64| (origin.0 + dir.0 * t, origin.1 + dir.1 * t, origin.2 + dir.2 * t);
+        // height of the ray above the terrain
+        let clearance = |t: f64| {
+            let (x, y, z) = point(t);
+            z - self.get_noise(x, y)
+        };
+
+        if clearance(0.0) <= 0.0 {
+            return Some(origin);
+        }
+        // the distance is computed from the step count so that the ray still advances when `step` is far below the
+        // precision of the distance
+        let steps = f64::min((max_dist / step).ceil(), RAYCAST_STEPS as f64) as usize;
+        let mut a = 0.0;
+        for k in 1..=steps {
+            let b = f64::min(k as f64 * step, max_dist);
+            if clearance(b) <= 0.0 {
+                let mut b = b;
+                for _ in 0..64 {
+                    let m = 0.5 * (a + b);
+                    if m <= a || m >= b {
+                        break;
+                    }
+                    if clearance(m) > 0.0 {
+                        a = m;
+                    } else {
+                        b = m;
+                    }
+                }
+                return Some(point(b));
+            }
+            a = b;
+        }
+        None
+    }
+}
+
+/// Number of samples [`NoiseSource::find_crossing`] takes along a segment before bisecting
+pub const CROSSING_SAMPLES: usize = 64;
+
+/// Largest number of steps [`NoiseSource::raycast`] takes along a ray before bisecting
+pub const RAYCAST_STEPS: usize = 1 << 20;
+
+impl<H: LatticeHasher> NoiseSource for PerlinNoise2D<H> {
+    fn get_noise(&self, x: f64, y: f64) -> f64 {
+        PerlinNoise2D::get_noise(self, x, y)
+    }
+
+    fn get_range(&self) -> (f64, f64) {
+        let (lo, hi) = self.octave_range();
+        let amplitude = self.get_effective_amplitude();
+        let (a, b) = (amplitude * lo, amplitude * hi);
+        (self.get_bias() + f64::min(a, b), self.get_bias() + f64::max(a, b))
+    }
+
+    fn get_variation_bound(&self, region: Region, step: (f64, f64)) -> f64 {
+        // the slope bound of the octave sum only holds for plain sums of continuous octaves
+        let shaping = if self.exponent >= 1.0 {
+            self.exponent
+        } else {
+            f64::INFINITY
+        };
+        let fade = match self.interpolation {
+            Interpolation::Linear => 1.0,
+            Interpolation::Cubic => 1.5,
+            Interpolation::Cosine => 0.5 * std::f64::consts::PI,
+            Interpolation::Quintic => 1.875,
+            Interpolation::Custom(_) => f64::INFINITY,
+        };
+        if self.mode != FractalMode::Fbm || !(shaping * fade).is_finite() {
+            return f64::INFINITY;
+        }
+        // largest slope of a lattice value along one lattice axis
+        let slope = match self.version {
+            AlgorithmVersion::ImprovedGradient => 4.0 * fade + 1.0,
+            _ => 2.0 * fade,
+        };
+
+        // the step and the bounding box of the region in unit space
+        let corners = [
+            region.min,
+            (region.max.0, region.min.1),
+            (region.min.0, region.max.1),
+            region.max,
+        ]
+        .map(|(x, y)| self.to_unit_space(x, y));
+        let unit_min = corners.iter().fold((f64::INFINITY, f64::INFINITY), |m, p| {
+            (f64::min(m.0, p.0), f64::min(m.1, p.1))
+        });
+        let unit_max = corners.iter().fold((f64::NEG_INFINITY, f64::NEG_INFINITY), |m, p| {
+            (f64::max(m.0, p.0), f64::max(m.1, p.1))
+        });
+        let unit_step = match self.transform {
+            Some(transform) => {
+                let m = transform.matrix;
+                (
+                    m[0][0].abs() * step.0 + m[0][1].abs() * step.1,
+                    m[1][0].abs() * step.0 + m[1][1].abs() * step.1,
+                )
+            }
+            None => (step.0 / self.scale.0.abs(), step.1 / self.scale.1.abs()),
+        };
+
+        let seed = self.seed as f64;
+        let (mut freq_x, mut freq_y) = self.frequency;
+        let mut gain: f64 = 1.0;
+        let mut change = 0.0;
+        for _ in 0..self.octaves {
+            // earlier versions place cells inconsistently at negative lattice coordinates and V1 saturates beyond
+            // 32 bits, so the octave jumps at every lattice line there
+            let seams = |frequency: f64, lo: f64, hi: f64| {
+                let (a, b) = (lo * frequency + seed, hi * frequency + seed);
+                let (a, b) = (f64::min(a, b), f64::max(a, b));
+                match self.version {
+                    AlgorithmVersion::V1 => a < 0.0 || b >= i32::MAX as f64,
+                    AlgorithmVersion::V2 => a < 0.0,
+                    _ => false,
+                }
+            };
+            let continuous = !seams(freq_x, unit_min.0, unit_max.0) && !seams(freq_y, unit_min.1, unit_max.1);
+            // a lattice value never changes by more than its range of [-1, 1]
+            let octave = if continuous {
+                f64::min(2.0, slope * (freq_x.abs() * unit_step.0 + freq_y.abs() * unit_step.1))
+            } else {
+                2.0
+            };
+            change += gain.abs() * octave;
+            gain *= self.persistence;
+            freq_x *= self.lacunarity;
+            freq_y *= self.lacunarity;
+        }
+        self.get_effective_amplitude().abs() * shaping * change
+    }
+
+    fn fill_region(&self, region: Region, resolution: (usize, usize), out: &mut [f64]) {
+        RowSampler::new(self).fill_region(region, resolution, out)
+    }
+
+    fn fill_rows(&self, region: Region, resolution: (usize, usize), first_row: usize, out: &mut [f64]) {
+        RowSampler::new(self).fill_rows(region, resolution, first_row, out)
+    }
+}
+
+impl<T: NoiseSource + ?Sized> NoiseSource for &T {
+    fn get_noise(&self, x: f64, y: f64) -> f64 {
+        (**self).get_noise(x, y)
+    }
+
+    fn get_range(&self) -> (f64, f64) {
+        (**self).get_range()
+    }
+
+    fn get_variation_bound(&self, region: Region, step: (f64, f64)) -> f64 {
+        (**self).get_variation_bound(region, step)
+    }
+
+    fn fill_region(&self, region: Region, resolution: (usize, usize), out: &mut [f64]) {
+        (**self).fill_region(region, resolution, out)
+    }
+
+    fn fill_rows(&self, region: Region, resolution: (usize, usize), first_row: usize, out: &mut [f64]) {
+        (**self).fill_rows(region, resolution, first_row, out)
+    }
+
+    fn sample_points_into(&self, points: &[(f64, f64)], out: &mut [f64]) {
+        (**self).sample_points_into(points, out)
+    }
+
+    fn get_noise_soa(&self, xs: &[f64], ys: &[f64], out: &mut [f64]) {
+        (**self).get_noise_soa(xs, ys, out)
+    }
+}
+
+impl<T: NoiseSource + ?Sized> NoiseSource for Box<T> {
+    fn get_noise(&self, x: f64, y: f64) -> f64 {
+        (**self).get_noise(x, y)
+    }
+
+    fn get_range(&self) -> (f64, f64) {
+        (**self).get_range()
+    }
+
+    fn get_variation_bound(&self, region: Region, step: (f64, f64)) -> f64 {
+        (**self).get_variation_bound(region, step)
+    }
+
+    fn fill_region(&self, region: Region, resolution: (usize, usize), out: &mut [f64]) {
+        (**self).fill_region(region, resolution, out)
+    }
+
+    fn fill_rows(&self, region: Region, resolution: (usize, usize), first_row: usize, out: &mut [f64]) {
+        (**self).fill_rows(region, resolution, first_row, out)
+    }
+
+    fn sample_points_into(&self, points: &[(f64, f64)], out: &mut [f64]) {
+        (**self).sample_points_into(points, out)
+    }
+
+    fn get_noise_soa(&self, xs: &[f64], ys: &[f64], out: &mut [f64]) {
+        (**self).get_noise_soa(xs, ys, out)
+    }
+}
@@ -0,0 +1,138 @@
+//! Endless sample streams along a straight path, for example as a modulation source in audio.
+//!
+//! ```
+//! use perlin2d::stream::NoiseStream;
+//! use perlin2d::{NoiseSource, PerlinNoise2D};
+//!
+//! let perlin = PerlinNoise2D::new(4, 1.0, 0.5, 0.5, 2.0, (1.0, 1.0), 0.0, 3);
+//! // two noise units per second along x, sampled at 48 kHz
+//! let mut lfo = NoiseStream::new(&perlin, (0.0, 0.5), (2.0, 0.0), 48_000.0);
+//!
+//! let first: Vec<f64> = lfo.by_ref().take(480).collect();
+//! assert_eq!(first[240], perlin.get_noise(240.0 / 48_000.0 * 2.0, 0.5));
+//! assert!((lfo.get_time() - 0.01).abs() < 1e-12);
+//!
+//! let mut buffer = [0.0; 256];
+//! lfo.fill(&mut buffer);
+//! ```
+
+use crate::NoiseSource;
+
+/// An infinite iterator over noise samples taken at a fixed rate along a straight path
+///
+/// Member variables:
+///
+/// * `source` - The sampled noise source.
+/// * `origin` - The position of the sample with index `0`.
+/// * `velocity` - The distance travelled in the noise plane per second.
+/// * `sample_rate` - The number of samples per second.
+/// * `index` - The index of the next sample. Positions are computed from it directly, so long streams do not drift.
+#[derive(Clone, Debug)]
+pub struct NoiseStream<S> {
+    source: S,
+    origin: (f64, f64),
+    velocity: (f64, f64),
+    sample_rate: f64,
+    index: u64,
+}
+
+impl<S: NoiseSource> NoiseStream<S> {
+    /// Create and return a new NoiseStream object
+    ///
+    /// Panics if `sample_rate` is not a positive finite number.
+    pub fn new(source: S, origin: (f64, f64), velocity: (f64, f64), sample_rate: f64) -> Self {
+        assert!(
+            sample_rate > 0.0 && sample_rate.is_finite(),
+            "sample rate must be positive and finite"
+        );
+        Self {
+            source,
+            origin,
+            velocity,
+            sample_rate,
+            index: 0,
+        }
+    }
+
+    /// Getter function for the source
+    pub fn get_source(&self) -> &S {
+        &self.source
+    }
+    /// Getter function for origin
+    pub fn get_origin(&self) -> (f64, f64) {
+        self.origin
+    }
+    /// Getter function for velocity
+    pub fn get_velocity(&self) -> (f64, f64) {
+        self.velocity
+    }
+    /// Getter function for sample_rate
+    pub fn get_sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+    /// Getter function for the index of the next sample
+    pub fn get_index(&self) -> u64 {
+        self.index
+    }
+    /// Time in seconds of the next sample
+    pub fn get_time(&self) -> f64 {
+        self.index as f64 / self.sample_rate
+    }
+
+    /// Setter function for velocity, the stream continues from its current position
+    pub fn set_velocity(&mut self, velocity: (f64, f64)) {
+        let (x, y) = self.get_position();
+        let t = self.get_time();
+        self.velocity = velocity;
+        self.origin = (x - velocity.0 * t, y - velocity.1 * t);
+    }
+    /// Setter function for sample_rate, the stream continues from its current position
+    ///
+    /// Panics if `sample_rate` is not a positive finite number.
+    pub fn set_sample_rate(&mut self, sample_rate: f64) {
+        assert!(
+            sample_rate > 0.0 && sample_rate.is_finite(),
+            "sample rate must be positive and finite"
+        );
+        let (x, y) = self.get_position();
+        self.sample_rate = sample_rate;
+        let t = self.get_time();
+        self.origin = (x - self.velocity.0 * t, y - self.velocity.1 * t);
+    }
+
+    /// Jump to the sample with the given index
+    pub fn seek(&mut self, index: u64) {
+        self.index = index;
+    }
+
+    /// Position of the next sample in the noise plane
+    pub fn get_position(&self) -> (f64, f64) {
+        let t = self.get_time();
+        (self.origin.0 + self.velocity.0 * t, self.origin.1 + self.velocity.1 * t)
+    }
+
+    /// Writes the next `out.len()` samples into `out`
+    pub fn fill(&mut self, out: &mut [f64]) {
+        for value in out.iter_mut() {
+            *value = self.next_sample();
+        }
+    }
+
+    fn next_sample(&mut self) -> f64 {
+        let (x, y) = self.get_position();
+        self.index = self.index.wrapping_add(1);
+        self.source.get_noise(x, y)
+    }
+}
+
+impl<S: NoiseSource> Iterator for NoiseStream<S> {
+    type Item = f64;
+
+    fn next(&mut self) -> Option<f64> {
+        Some(self.next_sample())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
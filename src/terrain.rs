@@ -0,0 +1,778 @@
+//! Terrain features of heightfields stored in a [`NoiseMap`].
+//!
+//! [`critical_points`] finds the peaks, basins and saddles of a map together with their prominence. Results only
+//! depend on the map values, so features derived from a seed are stable and can be used to name mountains or place
+//! landmarks deterministically. [`watersheds`] splits a map into drainage basins, for example to place lakes at the
+//! level where each basin overflows, and [`fill_depressions`] raises every depression to that level to find the lakes
+//! and make every cell drain off the map. [`bake_ao`] shades a heightfield with ambient occlusion for previews,
+//! [`bake_shadows`] adds the shadows cast by a directional light such as the sun, and [`coast_distance`] measures how
+//! far every point lies from the coastline for beaches, shallows and shore foam.
+//!
+//! ```
+//! use perlin2d::terrain::{critical_points, CriticalKind};
+//! use perlin2d::{NoiseSource, PerlinNoise2D, Region};
+//!
+//! let perlin = PerlinNoise2D::new(6, 100.0, 0.5, 0.5, 2.0, (64.0, 64.0), 0.0, 42);
+//! let map = perlin.generate_map(Region::new((0.0, 0.0), (256.0, 256.0)), (128, 128));
+//!
+//! let mut mountains: Vec<_> = critical_points(&map, 10.0)
+//!     .into_iter()
+//!     .filter(|p| p.kind == CriticalKind::Peak)
+//!     .collect();
+//! mountains.sort_by(|a, b| b.prominence.total_cmp(&a.prominence));
+//! ```
+
+use crate::{NoiseMap, NoiseSource, Region};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// Classification of a critical point
+///
+/// * `Peak` - Higher than all eight neighbors.
+/// * `Basin` - Lower than all eight neighbors.
+/// * `Saddle` - Neighbors alternate at least twice between higher and lower going around the point.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CriticalKind {
+    Peak,
+    Basin,
+    Saddle,
+}
+
+/// A critical point of a heightfield
+///
+/// * `kind` - Whether the point is a peak, a basin or a saddle.
+/// * `index` - The `(column, row)` of the point in the map.
+/// * `position` - The position of the point in the noise plane.
+/// * `value` - The map value at the point.
+/// * `prominence` - For peaks, the height above the highest saddle that connects them to higher terrain. For basins,
+///   the depth below the lowest saddle that connects them to deeper terrain. The highest peak and the deepest basin
+///   measure against the lowest and highest value of the map. For saddles, the larger of how far the terrain rises
+///   to the lower of the peaks it separates and how far it falls to the shallower of the basins it separates.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CriticalPoint {
+    pub kind: CriticalKind,
+    pub index: (usize, usize),
+    pub position: (f64, f64),
+    pub value: f64,
+    pub prominence: f64,
+}
+
+/// Finds the peaks, basins and saddles of a map with a prominence of at least `min_prominence`
+///
+/// Points on the border of the map are never reported since their surroundings are unknown, but the terrain behind
+/// them still counts towards the prominence of interior points. Equal values are ordered by their index in the map,
+/// so flat areas produce a single deterministic result. Points are returned in row-major order.
+pub fn critical_points(map: &NoiseMap, min_prominence: f64) -> Vec<CriticalPoint> {
+    let (w, h) = map.get_resolution();
+    if w < 3 || h < 3 {
+        return Vec::new();
+    }
+    let values = map.get_values();
+    let cmp = |a: usize, b: usize| values[a].total_cmp(&values[b]).then(a.cmp(&b));
+
+    let mut order: Vec<usize> = (0..w * h).collect();
+    order.sort_unstable_by(|&a, &b| cmp(b, a));
+    let (peaks, peak_saddles) = sweep(map, &order, |a, b| values[a] - values[b]);
+    order.reverse();
+    let (basins, basin_saddles) = sweep(map, &order, |a, b| values[b] - values[a]);
+
+    let mut points = Vec::new();
+    for j in 1..h - 1 {
+        for i in 1..w - 1 {
+            let index = j * w + i;
+            // neighbors in order around the point
+            let ring = [
+                index - w - 1,
+                index - w,
+                index - w + 1,
+                index + 1,
+                index + w + 1,
+                index + w,
+                index + w - 1,
+                index - 1,
+            ];
+            let higher: Vec<bool> = ring.iter().map(|&n| cmp(n, index) == Ordering::Greater).collect();
+            let changes = (0..8).filter(|&k| higher[k] != higher[(k + 1) % 8]).count();
+
+            let (kind, prominence) = if higher.iter().all(|&above| !above) {
+                (CriticalKind::Peak, peaks[index])
+            } else if higher.iter().all(|&above| above) {
+                (CriticalKind::Basin, basins[index])
+            } else if changes >= 4 {
+                (
+                    CriticalKind::Saddle,
+                    f64::max(peak_saddles[index], basin_saddles[index]),
+                )
+            } else {
+                continue;
+            };
+            if prominence >= min_prominence {
+                points.push(CriticalPoint {
+                    kind,
+                    index: (i, j),
+                    position: map.get_position(i, j),
+                    value: values[index],
+                    prominence,
+                });
+            }
+        }
+    }
+    points
+}
+
+/// Adds cells to the map in the given order, merging connected components as they touch
+///
+/// Returns the prominence of every cell that starts a component and the drop from the second highest summit to every
+/// cell that joins several components, both measured with `height(summit, cell)`.
+fn sweep<F: Fn(usize, usize) -> f64>(map: &NoiseMap, order: &[usize], height: F) -> (Vec<f64>, Vec<f64>) {
+    let (w, h) = map.get_resolution();
+    let mut parent: Vec<usize> = (0..w * h).collect();
+    let mut added = vec![false; w * h];
+    let mut prominence = vec![0.0; w * h];
+    let mut saddles = vec![0.0; w * h];
+    let mut rank = vec![0; w * h];
+    for (r, &index) in order.iter().enumerate() {
+        rank[index] = r;
+    }
+
+    fn find(parent: &mut [usize], mut x: usize) -> usize {
+        while parent[x] != x {
+            parent[x] = parent[parent[x]];
+            x = parent[x];
+        }
+        x
+    }
+
+    let mut roots = Vec::with_capacity(8);
+    for &index in order {
+        added[index] = true;
+        let (i, j) = (index % w, index / w);
+        roots.clear();
+        for nj in j.saturating_sub(1)..usize::min(j + 2, h) {
+            for ni in i.saturating_sub(1)..usize::min(i + 2, w) {
+                let neighbor = nj * w + ni;
+                if added[neighbor] && neighbor != index {
+                    let root = find(&mut parent, neighbor);
+                    if !roots.contains(&root) {
+                        roots.push(root);
+                    }
+                }
+            }
+        }
+        if roots.is_empty() {
+            continue;
+        }
+
+        // roots are always the summit of their component, the one reached first survives and the others end here
+        roots.sort_unstable_by_key(|&root| rank[root]);
+        let main = roots[0];
+        if roots.len() > 1 {
+            saddles[index] = height(roots[1], index);
+        }
+        for &root in roots[1..].iter() {
+            prominence[root] = height(root, index);
+            parent[root] = main;
+        }
+        parent[index] = main;
+    }
+
+    if let (Some(&first), Some(&last)) = (order.first(), order.last()) {
+        prominence[first] = height(first, last);
+    }
+    (prominence, saddles)
+}
+
+/// A drainage basin of a heightfield
+///
+/// * `sink` - The `(column, row)` of the lowest cell, where all water in the basin collects.
+/// * `outlet` - The `(column, row)` of the cell where water leaves once the basin is filled, either over the divide
+///   into a neighboring basin or over the edge of the map.
+/// * `spill_level` - The water level at which the basin overflows through the outlet, the surface of a lake filling it.
+/// * `cells` - The number of map cells draining into the basin.
+/// * `area` - The area of those cells in the noise plane.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Basin {
+    pub sink: (usize, usize),
+    pub outlet: (usize, usize),
+    pub spill_level: f64,
+    pub cells: usize,
+    pub area: f64,
+}
+
+/// Partition of a map into drainage basins, returned by [`watersheds`]
+///
+/// * `width` - Number of columns of the labeled map.
+/// * `height` - Number of rows of the labeled map.
+/// * `labels` - Row-major index into `basins` for every cell.
+/// * `basins` - Metadata of every basin, ordered by the row-major position of their sink.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Watersheds {
+    width: usize,
+    height: usize,
+    labels: Vec<usize>,
+    basins: Vec<Basin>,
+}
+
+impl Watersheds {
+    /// Getter function for width
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+    /// Getter function for height
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+    /// Getter function for the row-major labels
+    pub fn get_labels(&self) -> &[usize] {
+        &self.labels
+    }
+    /// Getter function for basins
+    pub fn get_basins(&self) -> &[Basin] {
+        &self.basins
+    }
+
+    /// Returns the basin label of the cell in column `i` and row `j`
+    pub fn get_label(&self, i: usize, j: usize) -> usize {
+        self.labels[j * self.width + i]
+    }
+    /// Returns the basin that the cell in column `i` and row `j` drains into
+    pub fn get_basin(&self, i: usize, j: usize) -> &Basin {
+        &self.basins[self.get_label(i, j)]
+    }
+}
+
+/// Partitions a heightfield into drainage basins
+///
+/// Water on every cell flows to the neighbor with the steepest descent among the eight surrounding cells, and cells
+/// whose water ends in the same sink form a basin. Equal values are ordered by their index in the map, so flat areas
+/// drain deterministically.
+///
+/// ```
+/// use perlin2d::terrain::watersheds;
+/// use perlin2d::{NoiseSource, PerlinNoise2D, Region};
+///
+/// let perlin = PerlinNoise2D::new(4, 50.0, 0.5, 0.5, 2.0, (32.0, 32.0), 0.0, 9);
+/// let map = perlin.generate_map(Region::new((0.0, 0.0), (128.0, 128.0)), (64, 64));
+/// let sheds = watersheds(&map);
+///
+/// let cells: usize = sheds.get_basins().iter().map(|b| b.cells).sum();
+/// assert_eq!(cells, 64 * 64);
+/// for basin in sheds.get_basins() {
+///     // a lake filling a basin is never below its floor
+///     assert!(basin.spill_level >= map.get_value(basin.sink.0, basin.sink.1));
+/// }
+/// ```
+pub fn watersheds(map: &NoiseMap) -> Watersheds {
+    let (w, h) = map.get_resolution();
+    let values = map.get_values();
+    let cmp = |a: usize, b: usize| values[a].total_cmp(&values[b]).then(a.cmp(&b));
+    let (step_x, step_y) = map.get_region().step((w, h));
+    let diagonal = f64::hypot(step_x, step_y);
+
+    // steepest descent receiver of every cell, sinks drain into themselves
+    let mut receiver: Vec<usize> = (0..w * h).collect();
+    for index in 0..w * h {
+        let (i, j) = (index % w, index / w);
+        let mut best: Option<(f64, usize)> = None;
+        for nj in j.saturating_sub(1)..usize::min(j + 2, h) {
+            for ni in i.saturating_sub(1)..usize::min(i + 2, w) {
+                let neighbor = nj * w + ni;
+                if cmp(neighbor, index) != Ordering::Less {
+                    continue;
+                }
+                let distance = match (ni != i, nj != j) {
+                    (true, true) => diagonal,
+                    (true, false) => step_x.abs(),
+                    _ => step_y.abs(),
+                };
+                let slope = (values[index] - values[neighbor]) / distance;
+                let steeper = match best {
+                    None => true,
+                    Some((s, b)) => slope > s || (slope == s && cmp(neighbor, b) == Ordering::Less),
+                };
+                if steeper {
+                    best = Some((slope, neighbor));
+                }
+            }
+        }
+        if let Some((_, neighbor)) = best {
+            receiver[index] = neighbor;
+        }
+    }
+
+    // label sinks in row-major order, then follow every flow path down to its sink
+    const UNLABELED: usize = usize::MAX;
+    let mut labels = vec![UNLABELED; w * h];
+    let mut basins = Vec::new();
+    for index in 0..w * h {
+        if receiver[index] == index {
+            labels[index] = basins.len();
+            basins.push(Basin {
+                sink: (index % w, index / w),
+                outlet: (index % w, index / w),
+                spill_level: f64::INFINITY,
+                cells: 0,
+                area: 0.0,
+            });
+        }
+    }
+    let mut path = Vec::new();
+    for start in 0..w * h {
+        let mut index = start;
+        while labels[index] == UNLABELED {
+            path.push(index);
+            index = receiver[index];
+        }
+        let label = labels[index];
+        for &visited in path.iter() {
+            labels[visited] = label;
+        }
+        path.clear();
+    }
+
+    // the outlet is the cell with the lowest spill over a divide or the map edge
+    let cell_area = (step_x * step_y).abs();
+    for index in 0..w * h {
+        let (i, j) = (index % w, index / w);
+        let basin = &mut basins[labels[index]];
+        basin.cells += 1;
+        basin.area += cell_area;
+
+        let mut spill = if i == 0 || j == 0 || i == w - 1 || j == h - 1 {
+            values[index]
+        } else {
+            f64::INFINITY
+        };
+        for nj in j.saturating_sub(1)..usize::min(j + 2, h) {
+            for ni in i.saturating_sub(1)..usize::min(i + 2, w) {
+                let neighbor = nj * w + ni;
+                if labels[neighbor] != labels[index] {
+                    spill = f64::min(spill, f64::max(values[index], values[neighbor]));
+                }
+            }
+        }
+        if spill < basin.spill_level {
+            basin.spill_level = spill;
+            basin.outlet = (i, j);
+        }
+    }
+
+    Watersheds {
+        width: w,
+        height: h,
+        labels,
+        basins,
+    }
+}
+
+/// A heightfield with its depressions filled, returned by [`fill_depressions`]
+///
+/// * `filled` - The filled surface.
+/// * `lakes` - Row-major mask, `true` for every cell the filling raised above the original surface.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FilledSurface {
+    filled: NoiseMap,
+    lakes: Vec<bool>,
+}
+
+impl FilledSurface {
+    /// Getter function for the filled surface
+    pub fn get_filled(&self) -> &NoiseMap {
+        &self.filled
+    }
+    /// Getter function for the row-major lake mask
+    pub fn get_lakes(&self) -> &[bool] {
+        &self.lakes
+    }
+    /// Whether the cell in column `i` and row `j` lies under a lake
+    pub fn is_lake(&self, i: usize, j: usize) -> bool {
+        self.lakes[j * self.filled.get_width() + i]
+    }
+    /// Consume the result and return the filled surface
+    pub fn into_filled(self) -> NoiseMap {
+        self.filled
+    }
+}
+
+/// A cell queued by [`fill_depressions`], ordered so the heap pops the lowest value first
+#[derive(Copy, Clone, PartialEq)]
+struct Flooded {
+    value: f64,
+    index: usize,
+}
+
+impl Eq for Flooded {}
+
+impl Ord for Flooded {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .value
+            .total_cmp(&self.value)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+impl PartialOrd for Flooded {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Fills every depression of a heightfield up to the level where it spills over, with the Priority-Flood algorithm
+///
+/// Water leaves the map over its border. Flooding inwards from the border, lowest cell first, raises every cell
+/// enclosed by higher terrain to the lowest rim around it, so afterwards a path leading downhill or level to the
+/// border starts at every cell. Cells are connected to their eight neighbors.
+///
+/// With an `epsilon` of zero lakes are perfectly flat. A small positive `epsilon` raises every filled cell that much
+/// above the cell it was flooded from, so lake surfaces slope gently towards their outlet and flow routing over the
+/// result never meets a flat area.
+///
+/// ```
+/// use perlin2d::terrain::fill_depressions;
+/// use perlin2d::{NoiseSource, PerlinNoise2D, Region};
+///
+/// let perlin = PerlinNoise2D::new(5, 50.0, 0.5, 0.5, 2.0, (32.0, 32.0), 0.0, 3);
+/// let map = perlin.generate_map(Region::new((0.0, 0.0), (128.0, 128.0)), (64, 64));
+/// let surface = fill_depressions(&map, 0.0);
+///
+/// let filled = surface.get_filled();
+/// for j in 0..64 {
+///     for i in 0..64 {
+///         // filling only ever raises the terrain, and lakes are where it did
+///         assert!(filled.get_value(i, j) >= map.get_value(i, j));
+///         assert_eq!(surface.is_lake(i, j), filled.get_value(i, j) > map.get_value(i, j));
+///     }
+/// }
+/// ```
+pub fn fill_depressions(map: &NoiseMap, epsilon: f64) -> FilledSurface {
+    let (w, h) = map.get_resolution();
+    let values = map.get_values();
+    let mut filled = values.to_vec();
+    let mut done = vec![false; w * h];
+    let mut open = BinaryHeap::new();
+    for index in 0..w * h {
+        let (i, j) = (index % w, index / w);
+        if i == 0 || j == 0 || i == w - 1 || j == h - 1 {
+            done[index] = true;
+            open.push(Flooded {
+                value: values[index],
+                index,
+            });
+        }
+    }
+    while let Some(Flooded { value, index }) = open.pop() {
+        let (i, j) = (index % w, index / w);
+        for nj in j.saturating_sub(1)..usize::min(j + 2, h) {
+            for ni in i.saturating_sub(1)..usize::min(i + 2, w) {
+                let neighbor = nj * w + ni;
+                if done[neighbor] {
+                    continue;
+                }
+                done[neighbor] = true;
+                if values[neighbor] <= value {
+                    filled[neighbor] = value + epsilon;
+                }
+                open.push(Flooded {
+                    value: filled[neighbor],
+                    index: neighbor,
+                });
+            }
+        }
+    }
+    let lakes = filled.iter().zip(values.iter()).map(|(f, v)| f > v).collect();
+    FilledSurface {
+        filled: NoiseMap::from_values(map.get_region(), (w, h), filled),
+        lakes,
+    }
+}
+
+/// Bakes horizon based ambient occlusion for a source over a region, sampled at the given `(width, height)` resolution
+///
+/// See [`map_ao`].
+pub fn bake_ao<S: NoiseSource + ?Sized>(
+    source: &S,
+    region: Region,
+    resolution: (usize, usize),
+    samples: usize,
+    radius: f64,
+) -> NoiseMap {
+    map_ao(&source.generate_map(region, resolution), samples, radius)
+}
+
+/// Bakes horizon based ambient occlusion for a heightfield
+///
+/// For every cell the horizon is traced in `samples` evenly spaced directions up to `radius` units of the noise plane
+/// away, and the returned map holds the fraction of the sky that is visible: `1.0` on open ground and lower in
+/// valleys and creases. Values are in `[0, 1]` and can be used directly as a grayscale shading image. Heights are
+/// taken in the same units as distances in the noise plane, so scale the amplitude of the source to exaggerate or
+/// soften the effect. Terrain beyond the edges of the map is treated as continuing at the height of the edge.
+///
+/// ```
+/// use perlin2d::terrain::bake_ao;
+/// use perlin2d::{PerlinNoise2D, Region};
+///
+/// let perlin = PerlinNoise2D::new(6, 20.0, 0.5, 0.5, 2.0, (64.0, 64.0), 0.0, 5);
+/// let ao = bake_ao(&perlin, Region::new((0.0, 0.0), (128.0, 128.0)), (64, 64), 8, 16.0);
+/// assert!(ao.get_values().iter().all(|&v| (0.0..=1.0).contains(&v)));
+/// ```
+pub fn map_ao(map: &NoiseMap, samples: usize, radius: f64) -> NoiseMap {
+    let (w, h) = map.get_resolution();
+    let region = map.get_region();
+    let mut ao = NoiseMap::new(region, (w, h));
+    if w == 0 || h == 0 || samples == 0 {
+        ao.get_values_mut().iter_mut().for_each(|v| *v = 1.0);
+        return ao;
+    }
+    let (step_x, step_y) = region.step((w, h));
+    let (step_x, step_y) = (step_x.abs(), step_y.abs());
+    // march in steps of one cell along the shorter side
+    let stride = f64::min(step_x, step_y);
+    let steps = if stride > 0.0 {
+        (radius / stride).ceil() as usize
+    } else {
+        0
+    };
+    let directions: Vec<(f64, f64)> = (0..samples)
+        .map(|k| f64::sin_cos(2.0 * std::f64::consts::PI * k as f64 / samples as f64))
+        .map(|(s, c)| (c, s))
+        .collect();
+
+    for j in 0..h {
+        for i in 0..w {
+            let base = map.get_value(i, j);
+            let mut visible = 0.0;
+            for &(dx, dy) in directions.iter() {
+                // sine of the highest elevation angle seen along the direction
+                let mut horizon: f64 = 0.0;
+                for s in 1..=steps {
+                    let distance = f64::min(s as f64 * stride, radius);
+                    let x = i as f64 + dx * distance / step_x;
+                    let y = j as f64 + dy * distance / step_y;
+                    let rise = sample_bilinear(map, x, y) - base;
+                    if rise > 0.0 {
+                        horizon = f64::max(horizon, rise / f64::hypot(rise, distance));
+                    }
+                }
+                visible += 1.0 - horizon;
+            }
+            ao.set_value(i, j, visible / samples as f64);
+        }
+    }
+    ao
+}
+
+/// Bakes the self-shadowing of a source over a region lit by the sun, sampled at the given `(width, height)`
+/// resolution
+///
+/// See [`map_shadows`].
+pub fn bake_shadows<S: NoiseSource + ?Sized>(
+    source: &S,
+    region: Region,
+    resolution: (usize, usize),
+    sun_azimuth: f64,
+    sun_altitude: f64,
+) -> NoiseMap {
+    map_shadows(&source.generate_map(region, resolution), sun_azimuth, sun_altitude)
+}
+
+/// Bakes the self-shadowing of a heightfield lit by the sun
+///
+/// `sun_azimuth` is the direction towards the sun in radians, counterclockwise from the positive `x` axis of the noise
+/// plane, and `sun_altitude` its angle above the horizon in radians. For every cell the horizon is scanned towards
+/// the sun one cell at a time, and the returned map holds `1.0` where the sun is visible and `0.0` where terrain
+/// blocks it, so it can be multiplied into a shading image or lightmap. A sun at or below the horizon leaves the
+/// whole map in shadow. Heights are taken in the same units as distances in the noise plane, and terrain beyond the
+/// edges of the map is treated as continuing at the height of the edge, so it never casts shadows.
+///
+/// ```
+/// use perlin2d::terrain::bake_shadows;
+/// use perlin2d::{PerlinNoise2D, Region};
+/// use std::f64::consts::FRAC_PI_2;
+///
+/// let perlin = PerlinNoise2D::new(6, 20.0, 0.5, 0.5, 2.0, (64.0, 64.0), 0.0, 5);
+/// let region = Region::new((0.0, 0.0), (128.0, 128.0));
+/// let shadowed = |altitude: f64| {
+///     let shadows = bake_shadows(&perlin, region, (64, 64), 0.0, altitude);
+///     shadows.get_values().iter().filter(|&&v| v == 0.0).count()
+/// };
+///
+/// // shadows grow longer as the sun sets, and disappear with the sun overhead
+/// assert!(shadowed(0.05) > shadowed(0.1));
+/// assert!(shadowed(0.1) > 0);
+/// assert_eq!(shadowed(FRAC_PI_2), 0);
+/// ```
+pub fn map_shadows(map: &NoiseMap, sun_azimuth: f64, sun_altitude: f64) -> NoiseMap {
+    let (w, h) = map.get_resolution();
+    let region = map.get_region();
+    let mut shadows = NoiseMap::new(region, (w, h));
+    let below_horizon = sun_altitude <= 0.0;
+    if w == 0 || h == 0 || below_horizon {
+        return shadows;
+    }
+    let (step_x, step_y) = region.step((w, h));
+    // cell offsets per unit of distance towards the sun, following the orientation of the region
+    let (sin, cos) = f64::sin_cos(sun_azimuth);
+    let (dx, dy) = (cos / step_x, sin / step_y);
+    // march in steps of one cell along the axis the sun direction crosses fastest
+    let stride = 1.0 / f64::max(dx.abs(), dy.abs());
+    if !stride.is_normal() {
+        // regions without extent in one axis have nothing to cast shadows along it
+        shadows.get_values_mut().iter_mut().for_each(|v| *v = 1.0);
+        return shadows;
+    }
+    let rise = sun_altitude.tan();
+    let highest = map.get_values().iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+    for j in 0..h {
+        for i in 0..w {
+            let base = map.get_value(i, j);
+            let mut lit = true;
+            let mut s = 1;
+            loop {
+                let distance = s as f64 * stride;
+                let ray = base + distance * rise;
+                let (x, y) = (i as f64 + dx * distance, j as f64 + dy * distance);
+                let outside = !(0.0..=(w - 1) as f64).contains(&x) || !(0.0..=(h - 1) as f64).contains(&y);
+                // the ray has left the map or risen above everything on it
+                if outside || ray > highest || !ray.is_finite() {
+                    break;
+                }
+                if sample_bilinear(map, x, y) > ray {
+                    lit = false;
+                    break;
+                }
+                s += 1;
+            }
+            if lit {
+                shadows.set_value(i, j, 1.0);
+            }
+        }
+    }
+    shadows
+}
+
+/// Computes the approximate signed distance to the coastline for a source over a region, sampled at the given
+/// `(width, height)` resolution
+///
+/// See [`map_coast_distance`].
+pub fn coast_distance<S: NoiseSource + ?Sized>(
+    source: &S,
+    region: Region,
+    resolution: (usize, usize),
+    sea_level: f64,
+) -> NoiseMap {
+    map_coast_distance(&source.generate_map(region, resolution), sea_level)
+}
+
+/// Computes the approximate signed distance to the coastline of a heightfield
+///
+/// Cells at or above `sea_level` are land and get positive distances, cells below it are water and get negative ones,
+/// measured in units of the noise plane. The coastline is placed between neighboring land and water cells where the
+/// linear interpolation of their values crosses the sea level, and distances spread from there with a two pass
+/// chamfer transform over the eight neighbors of every cell. The result overestimates true distances by at most about
+/// 8% away from the coast. Maps without both land and water have no coastline and hold infinite distances.
+///
+/// ```
+/// use perlin2d::terrain::coast_distance;
+/// use perlin2d::{NoiseSource, PerlinNoise2D, Region};
+///
+/// let perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (64.0, 64.0), 0.0, 8);
+/// let region = Region::new((0.0, 0.0), (256.0, 256.0));
+/// let distance = coast_distance(&perlin, region, (128, 128), 0.0);
+/// let heights = perlin.generate_map(region, (128, 128));
+///
+/// // the sign tells land from water
+/// for (d, h) in distance.get_values().iter().zip(heights.get_values()) {
+///     assert_eq!(*d >= 0.0, *h >= 0.0);
+/// }
+/// // sand within four units of the water line
+/// let beach = distance.get_values().iter().filter(|&&d| (0.0..4.0).contains(&d)).count();
+/// assert!(beach > 0);
+/// ```
+pub fn map_coast_distance(map: &NoiseMap, sea_level: f64) -> NoiseMap {
+    let (w, h) = map.get_resolution();
+    let region = map.get_region();
+    let values = map.get_values();
+    let (step_x, step_y) = region.step((w, h));
+    let (step_x, step_y) = (step_x.abs(), step_y.abs());
+    let diagonal = f64::hypot(step_x, step_y);
+    let land = |index: usize| values[index] >= sea_level;
+
+    // distance from every cell next to the coast to the sea level crossing on the edges it shares with the other side
+    let mut distance = vec![f64::INFINITY; w * h];
+    for j in 0..h {
+        for i in 0..w {
+            let index = j * w + i;
+            let mut crossing = |neighbor: usize, step: f64| {
+                if land(neighbor) != land(index) {
+                    let t = (values[index] - sea_level) / (values[index] - values[neighbor]);
+                    distance[index] = f64::min(distance[index], t * step);
+                }
+            };
+            if i > 0 {
+                crossing(index - 1, step_x);
+            }
+            if i + 1 < w {
+                crossing(index + 1, step_x);
+            }
+            if j > 0 {
+                crossing(index - w, step_y);
+            }
+            if j + 1 < h {
+                crossing(index + w, step_y);
+            }
+        }
+    }
+
+    // the forward pass spreads distances from the neighbors before a cell, the backward pass from those after it
+    let forward = [(-1, -1, diagonal), (0, -1, step_y), (1, -1, diagonal), (-1, 0, step_x)];
+    let backward = [(1, 1, diagonal), (0, 1, step_y), (-1, 1, diagonal), (1, 0, step_x)];
+    let mut relax = |i: usize, j: usize, offsets: &[(isize, isize, f64); 4]| {
+        for &(di, dj, step) in offsets.iter() {
+            let (ni, nj) = (i as isize + di, j as isize + dj);
+            if ni < 0 || nj < 0 || ni >= w as isize || nj >= h as isize {
+                continue;
+            }
+            let through = distance[nj as usize * w + ni as usize] + step;
+            if through < distance[j * w + i] {
+                distance[j * w + i] = through;
+            }
+        }
+    };
+    for j in 0..h {
+        for i in 0..w {
+            relax(i, j, &forward);
+        }
+    }
+    for j in (0..h).rev() {
+        for i in (0..w).rev() {
+            relax(i, j, &backward);
+        }
+    }
+
+    for (index, d) in distance.iter_mut().enumerate() {
+        if !land(index) {
+            *d = -*d;
+        }
+    }
+    NoiseMap::from_values(region, (w, h), distance)
+}
+
+/// Bilinear lookup at fractional `(column, row)` coordinates, clamped to the map
+fn sample_bilinear(map: &NoiseMap, x: f64, y: f64) -> f64 {
+    let (w, h) = map.get_resolution();
+    let x = x.max(0.0).min((w - 1) as f64);
+    let y = y.max(0.0).min((h - 1) as f64);
+    let (i0, j0) = (x as usize, y as usize);
+    let (i1, j1) = (usize::min(i0 + 1, w - 1), usize::min(j0 + 1, h - 1));
+    let (fx, fy) = (x - i0 as f64, y - j0 as f64);
+    let top = map.get_value(i0, j0) * (1.0 - fx) + map.get_value(i1, j0) * fx;
+    let bottom = map.get_value(i0, j1) * (1.0 - fx) + map.get_value(i1, j1) * fx;
+    top * (1.0 - fy) + bottom * fy
+}
@@ -0,0 +1,183 @@
+//! Wang tile sets of noise that can be assembled aperiodically.
+//!
+//! A [`WangTileSet`] holds one small tile for every combination of corner colors. Two tiles fit side by side whenever
+//! the colors of the corners at both ends of their shared edge match, so a large texture can be assembled at runtime
+//! from a handful of tiles instead of generating and storing a unique map. [`WangTileSet::select`] picks matching tiles
+//! for any grid position from a seed, without storing the layout. Since every corner of the layout has its own color,
+//! the areas where four tiles meet vary across the layout as well.
+//!
+//! ```
+//! use perlin2d::tiles::WangTileSet;
+//! use perlin2d::PerlinNoise2D;
+//!
+//! let perlin = PerlinNoise2D::new(4, 1.0, 0.5, 0.5, 2.0, (16.0, 16.0), 0.0, 8);
+//! let set = WangTileSet::generate(&perlin, 32.0, 64, 2);
+//! assert_eq!(set.len(), 16);
+//!
+//! // neighbors always agree on the colors of the corners they share
+//! let (left, right) = (set.select(4, 7, 99), set.select(5, 7, 99));
+//! let (left, right) = (set.get_corners(left), set.get_corners(right));
+//! assert_eq!((left.1, left.2), (right.0, right.3));
+//!
+//! // so the first row of any tile only depends on its north-west and north-east corner colors
+//! let same_north = (0..set.len()).filter(|&t| matches!(set.get_corners(t), (1, 0, _, _)));
+//! let rows: Vec<&[f64]> = same_north.map(|t| &set.get_tile(t)[..64]).collect();
+//! assert!(rows.windows(2).all(|pair| pair[0] == pair[1]));
+//! ```
+
+use crate::{HashBackend, LatticeHasher, NoiseSource};
+
+/// A complete set of noise tiles with matching edges
+///
+/// Member variables:
+///
+/// * `colors` - The number of corner colors. The set holds `colors^4` tiles.
+/// * `resolution` - The number of samples along each side of a tile.
+/// * `tiles` - The row-major samples of every tile, indexed as described in [`WangTileSet::tile_index`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WangTileSet {
+    colors: usize,
+    resolution: usize,
+    tiles: Vec<Vec<f64>>,
+}
+
+impl WangTileSet {
+    /// Generates every tile of a set from a noise source
+    ///
+    /// `tile_size` is the side length of a tile in the noise plane and `resolution` the number of samples along each
+    /// side. Each corner color, each pair of corner colors along an edge and every tile interior samples its own area
+    /// of the source, and the areas are blended with smooth weights so that tiles sharing an edge are continuous
+    /// across it and all tiles agree around their corners. Panics if `colors` is zero.
+    pub fn generate<S: NoiseSource + ?Sized>(source: &S, tile_size: f64, resolution: usize, colors: usize) -> Self {
+        assert!(colors > 0, "a tile set needs at least one corner color");
+        let count = colors.pow(4);
+        // every patch is read up to one tile away from its anchor, so anchors are spaced four tiles apart
+        let spacing = 4.0 * tile_size;
+        let anchor = |row: usize, k: usize| (k as f64 * spacing, row as f64 * spacing);
+        let sample =
+            |origin: (f64, f64), u: f64, v: f64| source.get_noise(origin.0 + u * tile_size, origin.1 + v * tile_size);
+        // an edge is identified by the colors of the corners at its ends
+        let pair = |a: usize, b: usize| a * colors + b;
+
+        let mut tiles = Vec::with_capacity(count);
+        for index in 0..count {
+            let (nw, ne, se, sw) = corners_of(index, colors);
+            let mut tile = Vec::with_capacity(resolution * resolution);
+            for j in 0..resolution {
+                let v = j as f64 / resolution as f64;
+                let (bv, cv, y_edge, top) = if v < 0.5 {
+                    (falloff(v), 0.0, anchor(1, pair(nw, ne)), true)
+                } else {
+                    (falloff(1.0 - v), 1.0, anchor(1, pair(sw, se)), false)
+                };
+                for i in 0..resolution {
+                    let u = i as f64 / resolution as f64;
+                    let (bu, cu, x_edge, corner) = match (u < 0.5, top) {
+                        (true, true) => (falloff(u), 0.0, anchor(2, pair(nw, sw)), nw),
+                        (true, false) => (falloff(u), 0.0, anchor(2, pair(nw, sw)), sw),
+                        (false, true) => (falloff(1.0 - u), 1.0, anchor(2, pair(ne, se)), ne),
+                        (false, false) => (falloff(1.0 - u), 1.0, anchor(2, pair(ne, se)), se),
+                    };
+                    // vertical edges are shared along x, horizontal edges along y and corners by all four tiles
+                    let interior = sample(anchor(3, index), u, v);
+                    let vertical = sample(x_edge, u - cu, v);
+                    let horizontal = sample(y_edge, u, v - cv);
+                    let corner = sample(anchor(0, corner), u - cu, v - cv);
+                    tile.push(
+                        interior * (1.0 - bu) * (1.0 - bv)
+                            + vertical * bu * (1.0 - bv)
+                            + horizontal * (1.0 - bu) * bv
+                            + corner * bu * bv,
+                    );
+                }
+            }
+            tiles.push(tile);
+        }
+        Self {
+            colors,
+            resolution,
+            tiles,
+        }
+    }
+
+    /// Getter function for colors
+    pub fn get_colors(&self) -> usize {
+        self.colors
+    }
+    /// Getter function for resolution
+    pub fn get_resolution(&self) -> usize {
+        self.resolution
+    }
+    /// Number of tiles in the set
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+    /// Returns true if the set holds no tiles
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Returns the row-major samples of a tile
+    pub fn get_tile(&self, index: usize) -> &[f64] {
+        &self.tiles[index]
+    }
+
+    /// Returns the `(north-west, north-east, south-east, south-west)` corner colors of a tile, north being row `0`
+    pub fn get_corners(&self, index: usize) -> (usize, usize, usize, usize) {
+        corners_of(index, self.colors)
+    }
+
+    /// Returns the index of the tile with the given `(north-west, north-east, south-east, south-west)` corner colors
+    pub fn tile_index(&self, corners: (usize, usize, usize, usize)) -> usize {
+        let c = self.colors;
+        ((corners.0 * c + corners.1) * c + corners.2) * c + corners.3
+    }
+
+    /// Picks the tile for column `x` and row `y` of an infinite layout
+    ///
+    /// The color of every corner of the layout is hashed from its position and `seed`, so neighboring positions always
+    /// receive matching tiles and the layout does not repeat.
+    pub fn select(&self, x: i64, y: i64, seed: u64) -> usize {
+        let corner = |x: i64, y: i64| {
+            let h = HashBackend::Wy.hash(x ^ (seed as i64), y);
+            // hashes lie in (-1, 1]
+            usize::min(((1.0 - h) * 0.5 * self.colors as f64) as usize, self.colors - 1)
+        };
+        // corners are numbered by the tile south-east of them
+        let (x1, y1) = (x.wrapping_add(1), y.wrapping_add(1));
+        self.tile_index((corner(x, y), corner(x1, y), corner(x1, y1), corner(x, y1)))
+    }
+
+    /// Assembles `columns * rows` tiles of the seeded layout starting at tile `origin` into one row-major grid
+    pub fn assemble(&self, origin: (i64, i64), columns: usize, rows: usize, seed: u64) -> Vec<f64> {
+        let res = self.resolution;
+        let width = columns * res;
+        let mut out = vec![0.0; width * rows * res];
+        for ty in 0..rows {
+            for tx in 0..columns {
+                let tile = &self.tiles[self.select(origin.0 + tx as i64, origin.1 + ty as i64, seed)];
+                for (j, row) in tile.chunks(res).enumerate() {
+                    let start = (ty * res + j) * width + tx * res;
+                    out[start..start + res].copy_from_slice(row);
+                }
+            }
+        }
+        out
+    }
+}
+
+fn corners_of(index: usize, colors: usize) -> (usize, usize, usize, usize) {
+    (
+        index / (colors * colors * colors),
+        index / (colors * colors) % colors,
+        index / colors % colors,
+        index % colors,
+    )
+}
+
+/// Weight of an edge at distance `t` across the tile, `1` on the edge and `0` from the middle on
+fn falloff(t: f64) -> f64 {
+    let s = 1.0 - f64::min(t / 0.5, 1.0);
+    s * s * (3.0 - 2.0 * s)
+}
@@ -0,0 +1,108 @@
+//! Affine transforms of the noise plane.
+
+#[cfg(not(feature = "std"))]
+use crate::math::Float;
+
+/// An affine map `p' = matrix * p + translation` applied to input coordinates
+///
+/// Setting a transform on a [`crate::PerlinNoise2D`] replaces its per-axis scale, so rotated, sheared and anisotropic
+/// noise can be described in one place. Transforms compose with [`Affine::then`].
+///
+/// ```
+/// use perlin2d::{Affine, PerlinNoise2D};
+///
+/// let mut perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (50.0, 50.0), 0.0, 4);
+/// let plain = perlin.get_noise(30.0, 40.0);
+///
+/// // the same noise rotated by 30 degrees, with the scale expressed in the transform
+/// let scale = Affine::scaling(1.0 / 50.0, 1.0 / 50.0);
+/// let rotation = Affine::rotation(std::f64::consts::PI / 6.0);
+/// perlin.set_transform(Some(rotation.then(scale)));
+/// let rotated = perlin.get_noise(30.0, 40.0);
+///
+/// perlin.set_transform(Some(scale));
+/// assert!((perlin.get_noise(30.0, 40.0) - plain).abs() < 1e-9);
+/// // which is the scaled noise sampled at the rotated point
+/// let (x, y) = rotation.apply(30.0, 40.0);
+/// assert!((perlin.get_noise(x, y) - rotated).abs() < 1e-9);
+/// assert!((rotated - plain).abs() > 1e-6);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Affine {
+    /// Row-major 2x2 matrix, `x' = m[0][0] * x + m[0][1] * y`.
+    pub matrix: [[f64; 2]; 2],
+    pub translation: (f64, f64),
+}
+
+impl Default for Affine {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Affine {
+    /// Create and return a new Affine object
+    pub const fn new(matrix: [[f64; 2]; 2], translation: (f64, f64)) -> Self {
+        Self { matrix, translation }
+    }
+
+    /// The transform that leaves coordinates unchanged
+    pub const fn identity() -> Self {
+        Self::new([[1.0, 0.0], [0.0, 1.0]], (0.0, 0.0))
+    }
+
+    /// Multiplies x by `sx` and y by `sy`
+    ///
+    /// Note that this multiplies where the `scale` of a generator divides, `scaling(1.0 / s, 1.0 / s)` matches a
+    /// scale of `(s, s)`.
+    pub const fn scaling(sx: f64, sy: f64) -> Self {
+        Self::new([[sx, 0.0], [0.0, sy]], (0.0, 0.0))
+    }
+
+    /// Rotates counterclockwise by `angle` radians around the origin
+    pub fn rotation(angle: f64) -> Self {
+        let (s, c) = f64::sin_cos(angle);
+        Self::new([[c, -s], [s, c]], (0.0, 0.0))
+    }
+
+    /// Shears x by `kx * y` and y by `ky * x`
+    pub const fn shear(kx: f64, ky: f64) -> Self {
+        Self::new([[1.0, kx], [ky, 1.0]], (0.0, 0.0))
+    }
+
+    /// Moves coordinates by `(dx, dy)`
+    pub const fn translation(dx: f64, dy: f64) -> Self {
+        Self::new([[1.0, 0.0], [0.0, 1.0]], (dx, dy))
+    }
+
+    /// Returns the transform that applies `self` first and then `next`
+    pub fn then(self, next: Affine) -> Affine {
+        let a = next.matrix;
+        let b = self.matrix;
+        let (x, y) = next.apply(self.translation.0, self.translation.1);
+        Affine::new(
+            [
+                [
+                    a[0][0] * b[0][0] + a[0][1] * b[1][0],
+                    a[0][0] * b[0][1] + a[0][1] * b[1][1],
+                ],
+                [
+                    a[1][0] * b[0][0] + a[1][1] * b[1][0],
+                    a[1][0] * b[0][1] + a[1][1] * b[1][1],
+                ],
+            ],
+            (x, y),
+        )
+    }
+
+    /// Applies the transform to a point
+    #[inline]
+    pub fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        let m = self.matrix;
+        (
+            m[0][0] * x + m[0][1] * y + self.translation.0,
+            m[1][0] * x + m[1][1] * y + self.translation.1,
+        )
+    }
+}
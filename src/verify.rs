@@ -0,0 +1,169 @@
+//! Self-test of the noise algorithms against published reference values.
+//!
+//! Worlds generated from a seed only stay the same if every platform computes the same noise. The values below were
+//! generated by this crate for a fixed reference generator and a fixed set of points for every [`AlgorithmVersion`],
+//! and are part of its stability guarantee: they only change together with a new algorithm version.
+//! [`PerlinNoise2D::verify`] recomputes them, so applications can check at startup that a new platform, compiler or
+//! set of optimization flags has not changed their world generation.
+//!
+//! ```
+//! use perlin2d::{AlgorithmVersion, PerlinNoise2D};
+//!
+//! PerlinNoise2D::verify().expect("world generation differs on this platform");
+//! PerlinNoise2D::verify_version(AlgorithmVersion::V3).unwrap();
+//! ```
+
+use crate::{AlgorithmVersion, PerlinNoise2D};
+use std::fmt;
+
+/// Largest difference from a reference value that [`PerlinNoise2D::verify`] accepts
+///
+/// Reference values are in the range of a generator with an amplitude of `10`. The tolerance leaves room for fused
+/// multiply-add and similar rounding differences between platforms, far below anything visible in generated terrain.
+pub const REFERENCE_TOLERANCE: f64 = 1e-9;
+
+/// Points sampled by the reference generator, covering negative, fractional and far away coordinates
+///
+/// None of them lies on a lattice point of any octave, where gradient noise is zero whatever the gradients are.
+const REFERENCE_POINTS: [(f64, f64); 10] = [
+    (0.37, -0.81),
+    (5.0, 10.0),
+    (-37.25, 81.5),
+    (1234.5, -987.25),
+    (0.001, 99.999),
+    (-250000.0, 31337.75),
+    (1000000.37, 2500000.81),
+    (-73.125, -0.5),
+    (-12345.6, -6789.1),
+    (300000000123.37, -400000000456.81),
+];
+
+const REFERENCE_V1: [f64; 10] = [
+    -3.5525069976404904,
+    -3.524358791204027,
+    -3.7473151242172573,
+    2.0816341330538437,
+    -2.9298019753180076,
+    0.7151028302182325,
+    2.3858761644506443,
+    -3.7091590490444197,
+    -2.183982162789576,
+    1.3248867678325233,
+];
+
+const REFERENCE_V2: [f64; 10] = [
+    -3.5525069976404904,
+    -3.524358791204027,
+    -3.7473151242172573,
+    2.0816341330538437,
+    -2.9298019753180076,
+    0.7151028302182325,
+    2.3858761644506443,
+    -3.7091590490444197,
+    -2.183982162789576,
+    1.598180617888366,
+];
+
+const REFERENCE_V3: [f64; 10] = [
+    -3.5525069976404904,
+    -3.524358791204027,
+    -3.7473151242172573,
+    2.1003009315424377,
+    -2.9298019753180076,
+    0.7151028302182325,
+    2.3858761644506443,
+    -3.7091590490444197,
+    -3.865531207753895,
+    0.6365891511680168,
+];
+
+const REFERENCE_IMPROVED_GRADIENT: [f64; 10] = [
+    0.13011210983345362,
+    0.5478623398436779,
+    2.410312414047322,
+    1.1713030311314174,
+    0.49982505998104504,
+    0.0885453808107523,
+    0.42663198940373726,
+    3.607310174340618,
+    0.7793121426007803,
+    0.7754951756066601,
+];
+
+/// A reference value that was not reproduced, returned by [`PerlinNoise2D::verify`]
+///
+/// * `version` - The algorithm version that differs.
+/// * `point` - The `(x, y)` point at which it differs.
+/// * `expected` - The reference value.
+/// * `actual` - The value computed on this platform.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct VerifyError {
+    pub version: AlgorithmVersion,
+    pub point: (f64, f64),
+    pub expected: f64,
+    pub actual: f64,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} noise at ({}, {}) is {} instead of the reference value {}",
+            self.version, self.point.0, self.point.1, self.actual, self.expected
+        )
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+impl PerlinNoise2D {
+    /// The generator the reference values are computed with, using the given algorithm version
+    ///
+    /// It has six octaves, an amplitude of `10`, a frequency of `0.5`, a persistence of `0.5`, a lacunarity of `2`,
+    /// a scale of `100`, a bias of `0.5`, the seed `101` and default settings otherwise.
+    pub fn reference(version: AlgorithmVersion) -> Self {
+        let mut perlin = Self::new(6, 10.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.5, 101);
+        perlin.set_version(version);
+        perlin
+    }
+
+    /// Checks that every algorithm version reproduces its reference values on this platform
+    ///
+    /// Returns the first value that differs by more than [`REFERENCE_TOLERANCE`].
+    pub fn verify() -> Result<(), VerifyError> {
+        [
+            AlgorithmVersion::V1,
+            AlgorithmVersion::V2,
+            AlgorithmVersion::V3,
+            AlgorithmVersion::ImprovedGradient,
+        ]
+        .iter()
+        .try_for_each(|&version| Self::verify_version(version))
+    }
+
+    /// Checks that one algorithm version reproduces its reference values on this platform, see
+    /// [`PerlinNoise2D::verify`]
+    pub fn verify_version(version: AlgorithmVersion) -> Result<(), VerifyError> {
+        let expected = match version {
+            AlgorithmVersion::V1 => &REFERENCE_V1,
+            AlgorithmVersion::V2 => &REFERENCE_V2,
+            AlgorithmVersion::V3 => &REFERENCE_V3,
+            AlgorithmVersion::ImprovedGradient => &REFERENCE_IMPROVED_GRADIENT,
+        };
+        let perlin = Self::reference(version);
+        for (&point, &expected) in REFERENCE_POINTS.iter().zip(expected.iter()) {
+            let actual = perlin.get_noise(point.0, point.1);
+            // written so that NaN fails as well
+            let matches = (actual - expected).abs() <= REFERENCE_TOLERANCE;
+            if !matches {
+                return Err(VerifyError {
+                    version,
+                    point,
+                    expected,
+                    actual,
+                });
+            }
+        }
+        Ok(())
+    }
+}
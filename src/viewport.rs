@@ -0,0 +1,139 @@
+//! Pan and zoom over the noise plane, as in a map editor.
+
+use crate::{NoiseMap, NoiseSource, Region};
+
+/// A rectangular view of the noise plane rendered at a fixed output resolution
+///
+/// Member variables:
+///
+/// * `center` - The point of the noise plane at the center of the view.
+/// * `zoom` - Pixels per unit of the noise plane. Larger values show a smaller area in more detail.
+/// * `resolution` - The `(width, height)` of the output in pixels.
+///
+/// Pixel `(0, 0)` is the minimum corner of the view, matching row `0` of the rendered map.
+///
+/// ```
+/// use perlin2d::{NoiseSource, PerlinNoise2D, Viewport};
+///
+/// let perlin = PerlinNoise2D::new(6, 1.0, 0.5, 0.5, 2.0, (100.0, 100.0), 0.0, 17);
+/// let mut view = Viewport::new((0.0, 0.0), 1.0, (320, 240));
+///
+/// // zooming in around a pixel keeps the point under it in place
+/// let before = view.screen_to_world(40.0, 30.0);
+/// view.zoom_at(2.0, (40.0, 30.0));
+/// let after = view.screen_to_world(40.0, 30.0);
+/// assert!((after.0 - before.0).abs() < 1e-9 && (after.1 - before.1).abs() < 1e-9);
+///
+/// view.pan(16.0, -8.0);
+/// let map = view.render(&perlin);
+/// assert_eq!(map.get_resolution(), (320, 240));
+/// let (x, y) = map.get_position(5, 7);
+/// assert_eq!(map.get_value(5, 7), perlin.get_noise(x, y));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Viewport {
+    center: (f64, f64),
+    zoom: f64,
+    resolution: (usize, usize),
+}
+
+impl Viewport {
+    /// Create and return a new Viewport object
+    ///
+    /// Panics if `zoom` is not a positive finite number.
+    pub fn new(center: (f64, f64), zoom: f64, resolution: (usize, usize)) -> Self {
+        assert!(zoom > 0.0 && zoom.is_finite(), "zoom must be positive and finite");
+        Self {
+            center,
+            zoom,
+            resolution,
+        }
+    }
+
+    /// Getter function for center
+    pub fn get_center(&self) -> (f64, f64) {
+        self.center
+    }
+    /// Getter function for zoom
+    pub fn get_zoom(&self) -> f64 {
+        self.zoom
+    }
+    /// Getter function for resolution
+    pub fn get_resolution(&self) -> (usize, usize) {
+        self.resolution
+    }
+
+    /// Setter function for center
+    pub fn set_center(&mut self, center: (f64, f64)) {
+        self.center = center;
+    }
+    /// Setter function for zoom
+    ///
+    /// Panics if `zoom` is not a positive finite number.
+    pub fn set_zoom(&mut self, zoom: f64) {
+        assert!(zoom > 0.0 && zoom.is_finite(), "zoom must be positive and finite");
+        self.zoom = zoom;
+    }
+    /// Setter function for resolution, the center and zoom are kept
+    pub fn set_resolution(&mut self, resolution: (usize, usize)) {
+        self.resolution = resolution;
+    }
+
+    /// Returns the area of the noise plane covered by the view
+    pub fn get_region(&self) -> Region {
+        let half = (
+            0.5 * self.resolution.0 as f64 / self.zoom,
+            0.5 * self.resolution.1 as f64 / self.zoom,
+        );
+        Region::new(
+            (self.center.0 - half.0, self.center.1 - half.1),
+            (self.center.0 + half.0, self.center.1 + half.1),
+        )
+    }
+
+    /// Converts a pixel position to a point of the noise plane
+    pub fn screen_to_world(&self, px: f64, py: f64) -> (f64, f64) {
+        (
+            self.center.0 + (px - 0.5 * self.resolution.0 as f64) / self.zoom,
+            self.center.1 + (py - 0.5 * self.resolution.1 as f64) / self.zoom,
+        )
+    }
+
+    /// Converts a point of the noise plane to a pixel position
+    pub fn world_to_screen(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            (x - self.center.0) * self.zoom + 0.5 * self.resolution.0 as f64,
+            (y - self.center.1) * self.zoom + 0.5 * self.resolution.1 as f64,
+        )
+    }
+
+    /// Moves the view by `(dx, dy)` pixels
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        self.center.0 += dx / self.zoom;
+        self.center.1 += dy / self.zoom;
+    }
+
+    /// Multiplies the zoom by `factor`, keeping the point under the pixel `anchor` in place
+    ///
+    /// Panics if the resulting zoom is not a positive finite number.
+    pub fn zoom_at(&mut self, factor: f64, anchor: (f64, f64)) {
+        let fixed = self.screen_to_world(anchor.0, anchor.1);
+        self.set_zoom(self.zoom * factor);
+        let moved = self.screen_to_world(anchor.0, anchor.1);
+        self.center.0 += fixed.0 - moved.0;
+        self.center.1 += fixed.1 - moved.1;
+    }
+
+    /// Samples the source over the view, one value per pixel
+    pub fn render<S: NoiseSource + ?Sized>(&self, source: &S) -> NoiseMap {
+        source.generate_map(self.get_region(), self.resolution)
+    }
+
+    /// Samples the source over the view into `out`, one value per pixel, for redrawing without allocating
+    ///
+    /// Panics if `out` does not hold exactly `width * height` elements.
+    pub fn render_into<S: NoiseSource + ?Sized>(&self, source: &S, out: &mut [f64]) {
+        source.fill_region(self.get_region(), self.resolution, out)
+    }
+}
@@ -0,0 +1,262 @@
+//! Voronoi cells for region maps such as provinces, territories or biomes.
+//!
+//! Every cell of a square grid holds one site, placed by hashing the cell with the same [`LatticeHasher`] as the
+//! noise generators. Each point belongs to the region of its nearest site. Offsetting the sample position with a
+//! noise source through [`VoronoiMap::sample_warped`] turns the straight borders into natural looking ones.
+//!
+//! ```
+//! use perlin2d::voronoi::VoronoiMap;
+//! use perlin2d::{NoiseSource, PerlinNoise2D};
+//!
+//! let provinces = VoronoiMap::new(64.0, 1.0, 7);
+//! let cell = provinces.sample(100.0, 250.0);
+//! assert!(cell.distance <= 64.0 * 1.5);
+//!
+//! // the site of a region belongs to that region
+//! assert_eq!(provinces.sample(cell.site.0, cell.site.1).id, cell.id);
+//!
+//! let jitter = PerlinNoise2D::new(4, 1.0, 0.5, 0.5, 2.0, (40.0, 40.0), 0.0, 7);
+//! let warped = provinces.sample_warped(100.0, 250.0, &jitter, 12.0);
+//!
+//! // the same as sampling the displaced point
+//! let dx = jitter.get_noise(100.0, 250.0);
+//! let dy = jitter.get_noise(100.0 + 5147.0, 250.0 + 5147.0);
+//! assert_eq!(warped, provinces.sample(100.0 + 12.0 * dx, 250.0 + 12.0 * dy));
+//! ```
+
+use crate::hash::mix_seed;
+use crate::{LatticeHasher, NoiseMap, NoiseSource, PrimeHasher, Region};
+
+/// Lattice offsets that decorrelate the hashes used for the x and y position of a site and its value
+const SITE_X_OFFSET: i64 = 0;
+const SITE_Y_OFFSET: i64 = 1_000_003;
+const VALUE_OFFSET: i64 = 2_000_006;
+/// Offset between the two warp samples, so x and y are displaced independently
+const WARP_OFFSET: f64 = 5_147.0;
+
+/// Result of a Voronoi lookup
+///
+/// * `cell` - The grid cell holding the nearest site.
+/// * `id` - A stable 64 bit identifier of the region, derived from the cell and the seed.
+/// * `value` - A pseudo random value in `[-1, 1]` for the region, for example to pick a biome.
+/// * `site` - The position of the nearest site.
+/// * `distance` - The distance to the nearest site.
+/// * `edge_distance` - The distance to the nearest border with a neighboring region.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VoronoiSample {
+    pub cell: (i64, i64),
+    pub id: u64,
+    pub value: f64,
+    pub site: (f64, f64),
+    pub distance: f64,
+    pub edge_distance: f64,
+}
+
+/// Voronoi region generator
+///
+/// Member variables:
+///
+/// * `cell_size` - The side length of the grid cells. Regions are about this large.
+/// * `jitter` - How far sites move from the cell centers, from `0` for a regular grid to `1` for the full cell.
+/// * `seed` - A value that changes the placement of sites.
+/// * `hasher` - The hash that places the sites. Defaults to [`PrimeHasher`].
+///
+/// As a [`NoiseSource`] the map returns the `value` of the region at every point.
+#[derive(Copy, Clone, Debug)]
+pub struct VoronoiMap<H = PrimeHasher> {
+    cell_size: f64,
+    jitter: f64,
+    seed: i32,
+    hasher: H,
+}
+
+impl VoronoiMap {
+    /// Create and return a new VoronoiMap object, `jitter` is clamped to `[0, 1]`
+    pub fn new(cell_size: f64, jitter: f64, seed: i32) -> Self {
+        Self {
+            cell_size,
+            jitter: jitter.clamp(0.0, 1.0),
+            seed,
+            hasher: PrimeHasher,
+        }
+    }
+}
+
+impl<H> VoronoiMap<H> {
+    /// Returns a copy of this map that places sites with a different lattice hash
+    pub fn with_hasher<G: LatticeHasher>(self, hasher: G) -> VoronoiMap<G> {
+        VoronoiMap {
+            cell_size: self.cell_size,
+            jitter: self.jitter,
+            seed: self.seed,
+            hasher,
+        }
+    }
+
+    /// Getter function for cell_size
+    pub fn get_cell_size(&self) -> f64 {
+        self.cell_size
+    }
+    /// Getter function for jitter
+    pub fn get_jitter(&self) -> f64 {
+        self.jitter
+    }
+    /// Getter function for seed
+    pub fn get_seed(&self) -> i32 {
+        self.seed
+    }
+    /// Getter function for hasher
+    pub fn get_hasher(&self) -> &H {
+        &self.hasher
+    }
+
+    /// Setter function for cell_size
+    pub fn set_cell_size(&mut self, cell_size: f64) {
+        self.cell_size = cell_size;
+    }
+    /// Setter function for jitter, clamped to `[0, 1]`
+    pub fn set_jitter(&mut self, jitter: f64) {
+        self.jitter = jitter.clamp(0.0, 1.0);
+    }
+    /// Setter function for seed
+    pub fn set_seed(&mut self, seed: i32) {
+        self.seed = seed;
+    }
+}
+
+impl<H: LatticeHasher> VoronoiMap<H> {
+    /// Position of the site of a cell
+    pub fn get_site(&self, cell: (i64, i64)) -> (f64, f64) {
+        let (i, j) = self.seeded(cell);
+        let dx = 0.5 * self.jitter * self.hasher.hash(i.wrapping_add(SITE_X_OFFSET), j);
+        let dy = 0.5 * self.jitter * self.hasher.hash(i.wrapping_add(SITE_Y_OFFSET), j);
+        (
+            (cell.0 as f64 + 0.5 + dx) * self.cell_size,
+            (cell.1 as f64 + 0.5 + dy) * self.cell_size,
+        )
+    }
+
+    /// Finds the region containing the point `(x, y)`
+    pub fn sample(&self, x: f64, y: f64) -> VoronoiSample {
+        let (ci, cj) = (
+            f64::floor(x / self.cell_size) as i64,
+            f64::floor(y / self.cell_size) as i64,
+        );
+        // the 3x3 block around the point holds the nearest site and the closest bisector when both lie well inside the
+        // block border, otherwise the surrounding ring of cells is searched as well
+        let mut sites = [(0.0, 0.0); 25];
+        let mut count = 0;
+        let mut nearest = 0;
+        let mut cell = (ci, cj);
+        let mut nearest_distance = f64::INFINITY;
+        let (fx, fy) = (x / self.cell_size - ci as f64, y / self.cell_size - cj as f64);
+        let border = f64::min(f64::min(fx + 1.0, 2.0 - fx), f64::min(fy + 1.0, 2.0 - fy)) * self.cell_size.abs();
+        for ring in 1..=2i64 {
+            // a site beyond the border is at least `border` away, so its bisector with the nearest site is at least
+            // `(border - nearest_distance) / 2` away
+            if ring == 2 && nearest_distance + 2.0 * bisector_distance(&sites[..count], nearest, (x, y)) <= border {
+                break;
+            }
+            for dj in -ring..=ring {
+                for di in -ring..=ring {
+                    if ring == 2 && di.abs() < 2 && dj.abs() < 2 {
+                        // already visited
+                        continue;
+                    }
+                    let candidate = (ci.wrapping_add(di), cj.wrapping_add(dj));
+                    let position = self.get_site(candidate);
+                    sites[count] = position;
+                    let distance = f64::hypot(position.0 - x, position.1 - y);
+                    if distance < nearest_distance {
+                        nearest = count;
+                        nearest_distance = distance;
+                        cell = candidate;
+                    }
+                    count += 1;
+                }
+            }
+        }
+        let sites = &sites[..count];
+
+        let a = sites[nearest];
+        let edge_distance = bisector_distance(sites, nearest, (x, y));
+
+        let (i, j) = self.seeded(cell);
+        VoronoiSample {
+            cell,
+            id: mix_seed(cell.0 as u64 ^ (self.seed as i64 as u64).rotate_left(32), cell.1 as u64),
+            value: self.hasher.hash(i.wrapping_add(VALUE_OFFSET), j),
+            site: a,
+            distance: nearest_distance,
+            edge_distance: edge_distance.max(0.0),
+        }
+    }
+
+    /// Finds the region containing `(x, y)` after displacing it by `strength` times the values of `warp`
+    ///
+    /// The x and y displacements are read from two distant areas of `warp`, at `(x, y)` and at `(x + 5147, y + 5147)`,
+    /// so borders wobble in both directions.
+    pub fn sample_warped<S: NoiseSource + ?Sized>(&self, x: f64, y: f64, warp: &S, strength: f64) -> VoronoiSample {
+        let dx = warp.get_noise(x, y);
+        let dy = warp.get_noise(x + WARP_OFFSET, y + WARP_OFFSET);
+        self.sample(x + strength * dx, y + strength * dy)
+    }
+
+    /// Samples the region at the given `(width, height)` resolution, row by row like [`NoiseSource::generate_map`]
+    pub fn sample_region(&self, region: Region, resolution: (usize, usize)) -> Vec<VoronoiSample> {
+        let mut out = Vec::with_capacity(resolution.0 * resolution.1);
+        for j in 0..resolution.1 {
+            for i in 0..resolution.0 {
+                let (x, y) = region.sample_point(resolution, i, j);
+                out.push(self.sample(x, y));
+            }
+        }
+        out
+    }
+
+    /// Returns a map of the distance to the nearest site at every sample of the region
+    pub fn distance_map(&self, region: Region, resolution: (usize, usize)) -> NoiseMap {
+        let values = self
+            .sample_region(region, resolution)
+            .iter()
+            .map(|sample| sample.distance)
+            .collect();
+        NoiseMap::from_values(region, resolution, values)
+    }
+
+    #[inline]
+    fn seeded(&self, cell: (i64, i64)) -> (i64, i64) {
+        let seed = self.seed as i64;
+        (cell.0.wrapping_add(seed), cell.1.wrapping_add(seed))
+    }
+}
+
+impl<H: LatticeHasher> NoiseSource for VoronoiMap<H> {
+    fn get_noise(&self, x: f64, y: f64) -> f64 {
+        self.sample(x, y).value
+    }
+
+    fn get_range(&self) -> (f64, f64) {
+        (-1.0, 1.0)
+    }
+}
+
+/// Distance from `point` to the closest bisector between the nearest site and any other one
+fn bisector_distance(sites: &[(f64, f64)], nearest: usize, point: (f64, f64)) -> f64 {
+    let a = sites[nearest];
+    let mut distance = f64::INFINITY;
+    for (k, &b) in sites.iter().enumerate() {
+        if k == nearest {
+            continue;
+        }
+        let (nx, ny) = (b.0 - a.0, b.1 - a.1);
+        let length = f64::hypot(nx, ny);
+        if length > 0.0 {
+            let (mx, my) = (0.5 * (a.0 + b.0), 0.5 * (a.1 + b.1));
+            let d = ((mx - point.0) * nx + (my - point.1) * ny) / length;
+            distance = distance.min(d);
+        }
+    }
+    distance
+}